@@ -13,10 +13,28 @@ pub struct Model {
     pub mode: protocol::ModeKind,
     #[sea_orm(nullable)]
     pub title: Option<String>,
+    #[sea_orm(nullable)]
+    pub leaf_id: Option<i32>,
+    #[sea_orm(nullable)]
+    pub folder_id: Option<i32>,
+    pub pinned: bool,
+    pub archived: bool,
+    #[sea_orm(nullable)]
+    pub custom_instructions: Option<protocol::CustomInstructions>,
+    #[sea_orm(nullable)]
+    pub summary: Option<protocol::ChatSummary>,
+    #[sea_orm(nullable)]
+    pub assistant_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::chat_tag::Entity")]
+    ChatTag,
+    #[sea_orm(has_many = "super::comparison::Entity")]
+    Comparison,
+    #[sea_orm(has_many = "super::memory::Entity")]
+    Memory,
     #[sea_orm(has_many = "super::message::Entity")]
     Message,
     #[sea_orm(
@@ -27,6 +45,8 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Model,
+    #[sea_orm(has_many = "super::share::Entity")]
+    Share,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::OwnerId",
@@ -37,6 +57,24 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::chat_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatTag.def()
+    }
+}
+
+impl Related<super::comparison::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Comparison.def()
+    }
+}
+
+impl Related<super::memory::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Memory.def()
+    }
+}
+
 impl Related<super::message::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Message.def()
@@ -49,6 +87,22 @@ impl Related<super::model::Entity> for Entity {
     }
 }
 
+impl Related<super::share::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Share.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::chat_tag::Relation::Tag.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::chat_tag::Relation::Chat.def().rev())
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
@@ -11,6 +11,9 @@ pub struct Model {
     pub price: f32,
     pub token_count: i32,
     pub inner: protocol::MessageInner,
+    pub created_at: i64,
+    #[sea_orm(nullable)]
+    pub parent_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,10 +2,31 @@
 
 pub mod prelude;
 
+pub mod api_token;
+pub mod assistant;
+pub mod audit_log;
+pub mod budget;
 pub mod chat;
+pub mod chat_tag;
+pub mod comparison;
+pub mod comparison_reply;
 pub mod config;
 pub mod file;
+pub mod folder;
+pub mod knowledge;
+pub mod knowledge_chunk;
+pub mod knowledge_file;
+pub mod memory;
 pub mod message;
 pub mod model;
+pub mod model_access;
+pub mod oidc_identity;
+pub mod session;
+pub mod share;
+pub mod spending;
+pub mod tag;
 pub mod tool;
+pub mod totp;
 pub mod user;
+pub mod user_group;
+pub mod user_group_member;
@@ -12,8 +12,20 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::assistant::Entity")]
+    Assistant,
     #[sea_orm(has_many = "super::chat::Entity")]
     Chat,
+    #[sea_orm(has_many = "super::comparison_reply::Entity")]
+    ComparisonReply,
+    #[sea_orm(has_many = "super::model_access::Entity")]
+    ModelAccess,
+}
+
+impl Related<super::assistant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Assistant.def()
+    }
 }
 
 impl Related<super::chat::Entity> for Entity {
@@ -22,4 +34,16 @@ impl Related<super::chat::Entity> for Entity {
     }
 }
 
+impl Related<super::comparison_reply::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComparisonReply.def()
+    }
+}
+
+impl Related<super::model_access::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ModelAccess.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,8 +1,30 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
+pub use super::api_token::Entity as ApiToken;
+pub use super::assistant::Entity as Assistant;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::budget::Entity as Budget;
 pub use super::chat::Entity as Chat;
+pub use super::chat_tag::Entity as ChatTag;
+pub use super::comparison::Entity as Comparison;
+pub use super::comparison_reply::Entity as ComparisonReply;
 pub use super::config::Entity as Config;
+pub use super::file::Entity as File;
+pub use super::folder::Entity as Folder;
+pub use super::knowledge::Entity as Knowledge;
+pub use super::knowledge_chunk::Entity as KnowledgeChunk;
+pub use super::knowledge_file::Entity as KnowledgeFile;
+pub use super::memory::Entity as Memory;
 pub use super::message::Entity as Message;
 pub use super::model::Entity as Model;
+pub use super::model_access::Entity as ModelAccess;
+pub use super::oidc_identity::Entity as OidcIdentity;
+pub use super::session::Entity as Session;
+pub use super::share::Entity as Share;
+pub use super::spending::Entity as Spending;
+pub use super::tag::Entity as Tag;
 pub use super::tool::Entity as Tool;
+pub use super::totp::Entity as Totp;
 pub use super::user::Entity as User;
+pub use super::user_group::Entity as UserGroup;
+pub use super::user_group_member::Entity as UserGroupMember;
@@ -10,12 +10,59 @@ pub struct Model {
     pub name: String,
     pub password: String,
     pub preference: protocol::UserPreference,
+    pub role: protocol::UserRole,
+    pub display_name: Option<String>,
+    pub must_change_password: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::assistant::Entity")]
+    Assistant,
+    #[sea_orm(has_many = "super::api_token::Entity")]
+    ApiToken,
+    #[sea_orm(has_one = "super::budget::Entity")]
+    Budget,
     #[sea_orm(has_many = "super::chat::Entity")]
     Chat,
+    #[sea_orm(has_many = "super::folder::Entity")]
+    Folder,
+    #[sea_orm(has_many = "super::knowledge::Entity")]
+    Knowledge,
+    #[sea_orm(has_many = "super::memory::Entity")]
+    Memory,
+    #[sea_orm(has_many = "super::model_access::Entity")]
+    ModelAccess,
+    #[sea_orm(has_many = "super::oidc_identity::Entity")]
+    OidcIdentity,
+    #[sea_orm(has_many = "super::session::Entity")]
+    Session,
+    #[sea_orm(has_many = "super::spending::Entity")]
+    Spending,
+    #[sea_orm(has_many = "super::tag::Entity")]
+    Tag,
+    #[sea_orm(has_one = "super::totp::Entity")]
+    Totp,
+    #[sea_orm(has_many = "super::user_group_member::Entity")]
+    UserGroupMember,
+}
+
+impl Related<super::assistant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Assistant.def()
+    }
+}
+
+impl Related<super::api_token::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiToken.def()
+    }
+}
+
+impl Related<super::budget::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Budget.def()
+    }
 }
 
 impl Related<super::chat::Entity> for Entity {
@@ -24,4 +71,64 @@ impl Related<super::chat::Entity> for Entity {
     }
 }
 
+impl Related<super::folder::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Folder.def()
+    }
+}
+
+impl Related<super::knowledge::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Knowledge.def()
+    }
+}
+
+impl Related<super::memory::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Memory.def()
+    }
+}
+
+impl Related<super::model_access::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ModelAccess.def()
+    }
+}
+
+impl Related<super::oidc_identity::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OidcIdentity.def()
+    }
+}
+
+impl Related<super::session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Session.def()
+    }
+}
+
+impl Related<super::spending::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Spending.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tag.def()
+    }
+}
+
+impl Related<super::totp::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Totp.def()
+    }
+}
+
+impl Related<super::user_group_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserGroupMember.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -3,6 +3,27 @@ use sea_orm_migration::sea_orm::Database;
 
 mod m20250908_082005_create_table;
 mod m20251227_085232_add_valid_until_to_file;
+mod m20261018_101500_add_role_to_user;
+mod m20261018_113000_create_session;
+mod m20261018_130000_create_api_token;
+mod m20261018_143000_add_display_name_to_user;
+mod m20261018_153000_create_totp;
+mod m20261018_163000_add_must_change_password_to_user;
+mod m20261018_173000_create_audit_log;
+mod m20261018_183000_create_budget;
+mod m20261018_193000_add_created_at_to_message;
+mod m20261018_203000_create_model_access;
+mod m20261018_213000_add_branch_to_message;
+mod m20261018_223000_create_message_fts;
+mod m20261018_233000_create_share;
+mod m20261019_003000_create_folder_and_tag;
+mod m20261019_013000_add_custom_instructions_to_chat;
+mod m20261019_023000_create_assistant;
+mod m20261019_033000_add_summary_to_chat;
+mod m20261019_043000_create_memory;
+mod m20261019_053000_create_knowledge;
+mod m20261019_063000_create_comparison;
+mod m20261019_073000_create_oidc_identity;
 
 pub struct Migrator;
 
@@ -12,6 +33,27 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20250908_082005_create_table::Migration),
             Box::new(m20251227_085232_add_valid_until_to_file::Migration),
+            Box::new(m20261018_101500_add_role_to_user::Migration),
+            Box::new(m20261018_113000_create_session::Migration),
+            Box::new(m20261018_130000_create_api_token::Migration),
+            Box::new(m20261018_143000_add_display_name_to_user::Migration),
+            Box::new(m20261018_153000_create_totp::Migration),
+            Box::new(m20261018_163000_add_must_change_password_to_user::Migration),
+            Box::new(m20261018_173000_create_audit_log::Migration),
+            Box::new(m20261018_183000_create_budget::Migration),
+            Box::new(m20261018_193000_add_created_at_to_message::Migration),
+            Box::new(m20261018_203000_create_model_access::Migration),
+            Box::new(m20261018_213000_add_branch_to_message::Migration),
+            Box::new(m20261018_223000_create_message_fts::Migration),
+            Box::new(m20261018_233000_create_share::Migration),
+            Box::new(m20261019_003000_create_folder_and_tag::Migration),
+            Box::new(m20261019_013000_add_custom_instructions_to_chat::Migration),
+            Box::new(m20261019_023000_create_assistant::Migration),
+            Box::new(m20261019_033000_add_summary_to_chat::Migration),
+            Box::new(m20261019_043000_create_memory::Migration),
+            Box::new(m20261019_053000_create_knowledge::Migration),
+            Box::new(m20261019_063000_create_comparison::Migration),
+            Box::new(m20261019_073000_create_oidc_identity::Migration),
             // Box::new(m20251219_060552_add_embedding::Migration),
         ]
     }
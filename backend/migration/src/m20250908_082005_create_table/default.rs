@@ -1,5 +1,5 @@
 // password hash of P@88w0rd
-const PASSWORD_HASH_ENCODE: &str =
+pub(crate) const PASSWORD_HASH_ENCODE: &str =
     "$argon2id$v=19$m=16,t=2,p=1$aTg5eTNyMmRzLTA$FM4qzh9B/+DdCVOiQQruGw";
 
 const DEFAULT_MODEL_CONFIG: &str = r#"
@@ -11,7 +11,7 @@ mod chat;
 mod file;
 mod message;
 
-mod default;
+pub(crate) mod default;
 // WAL is not presistent across connections, however, we should flush wal
 // mod wal;
 
@@ -0,0 +1,57 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(integer(User::Role).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // the seeded `admin` user becomes the first administrator
+        let promote_admin = Query::update()
+            .table(User::Table)
+            .value(User::Role, 1)
+            .and_where(Expr::col(User::Name).eq("admin"))
+            .to_owned();
+        manager.exec_stmt(promote_admin).await?;
+
+        // fallback for instances where the seeded user was renamed
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE user SET role = 1 WHERE id = (SELECT MIN(id) FROM user) \
+                 AND NOT EXISTS (SELECT 1 FROM user WHERE role = 1)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Role)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Name,
+    Role,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiToken::Id))
+                    .col(integer(ApiToken::UserId))
+                    .col(string(ApiToken::Name))
+                    // public part of the token, used to find the row to verify
+                    .col(string_uniq(ApiToken::Prefix))
+                    // argon2 hash of the secret part
+                    .col(string(ApiToken::Hash))
+                    .col(string(ApiToken::Scopes).default("[]"))
+                    .col(big_integer(ApiToken::CreatedAt))
+                    .col(big_integer_null(ApiToken::LastUsedAt))
+                    .col(big_integer_null(ApiToken::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-api_token-user_id-user")
+                            .from(ApiToken::Table, ApiToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-api_token-user_id")
+                    .table(ApiToken::Table)
+                    .col(ApiToken::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-api_token-user_id")
+                    .table(ApiToken::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ApiToken::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ApiToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Prefix,
+    Hash,
+    Scopes,
+    CreatedAt,
+    LastUsedAt,
+    ExpiresAt,
+}
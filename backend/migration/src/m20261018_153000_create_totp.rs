@@ -0,0 +1,62 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Totp::Table)
+                    .if_not_exists()
+                    .col(integer(Totp::UserId).primary_key())
+                    // base32 secret shared with the authenticator app
+                    .col(string(Totp::Secret))
+                    // set once the user proved the app is set up
+                    .col(boolean(Totp::Enabled).default(false))
+                    .col(string(Totp::RecoveryCodes).default("[]"))
+                    // highest accepted time step, to reject replayed codes
+                    .col(big_integer(Totp::LastUsedStep).default(0))
+                    .col(big_integer(Totp::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-totp-user_id-user")
+                            .from(Totp::Table, Totp::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Totp::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Totp {
+    Table,
+    UserId,
+    Secret,
+    Enabled,
+    RecoveryCodes,
+    LastUsedStep,
+    CreatedAt,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::m20250908_082005_create_table::default::PASSWORD_HASH_ENCODE;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(boolean(User::MustChangePassword).default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        // only instances still using the seeded password are affected
+        let flag_admin = Query::update()
+            .table(User::Table)
+            .value(User::MustChangePassword, true)
+            .and_where(Expr::col(User::Name).eq("admin"))
+            .and_where(Expr::col(User::Password).eq(PASSWORD_HASH_ENCODE))
+            .to_owned();
+        manager.exec_stmt(flag_admin).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::MustChangePassword)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Name,
+    Password,
+    MustChangePassword,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // no foreign keys, entries must outlive the users they mention
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuditLog::Id))
+                    .col(integer_null(AuditLog::ActorId))
+                    .col(integer(AuditLog::Action))
+                    .col(integer_null(AuditLog::TargetId))
+                    .col(string_null(AuditLog::Detail))
+                    .col(string_null(AuditLog::Ip))
+                    .col(big_integer(AuditLog::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-audit_log-actor_id")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::ActorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-audit_log-actor_id")
+                    .table(AuditLog::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    Id,
+    ActorId,
+    Action,
+    TargetId,
+    Detail,
+    Ip,
+    CreatedAt,
+}
@@ -0,0 +1,113 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Budget::Table)
+                    .if_not_exists()
+                    .col(integer(Budget::UserId).primary_key())
+                    // amounts are in the currency OpenRouter reports, USD
+                    .col(double(Budget::Limit))
+                    .col(double_null(Budget::Warn))
+                    .col(integer(Budget::Period).default(0))
+                    .col(integer(Budget::WindowDays).default(30))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-budget-user_id-user")
+                            .from(Budget::Table, Budget::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // spending is kept apart from messages so deleting a chat does not
+        // refund its cost
+        manager
+            .create_table(
+                Table::create()
+                    .table(Spending::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Spending::Id))
+                    .col(integer(Spending::UserId))
+                    .col(double(Spending::Amount))
+                    .col(big_integer(Spending::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-spending-user_id-user")
+                            .from(Spending::Table, Spending::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-spending-user_id-created_at")
+                    .table(Spending::Table)
+                    .col(Spending::UserId)
+                    .col(Spending::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-spending-user_id-created_at")
+                    .table(Spending::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Spending::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Budget::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Budget {
+    Table,
+    UserId,
+    Limit,
+    Warn,
+    Period,
+    WindowDays,
+}
+
+#[derive(DeriveIden)]
+enum Spending {
+    Table,
+    Id,
+    UserId,
+    Amount,
+    CreatedAt,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(big_integer(Message::CreatedAt).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing messages carry no time information. They are dated to the
+        // moment of this migration, so their spending still shows up in usage
+        // reports, attributed to the day of the upgrade.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE message SET created_at = CAST(strftime('%s', 'now') AS INTEGER) \
+                 WHERE created_at = 0",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-message-created_at")
+                    .table(Message::Table)
+                    .col(Message::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-message-created_at")
+                    .table(Message::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(Message::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    CreatedAt,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserGroup::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserGroup::Id))
+                    .col(string_uniq(UserGroup::Name))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserGroupMember::Table)
+                    .if_not_exists()
+                    .col(integer(UserGroupMember::GroupId))
+                    .col(integer(UserGroupMember::UserId))
+                    .primary_key(
+                        Index::create()
+                            .col(UserGroupMember::GroupId)
+                            .col(UserGroupMember::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_group_member-group_id-user_group")
+                            .from(UserGroupMember::Table, UserGroupMember::GroupId)
+                            .to(UserGroup::Table, UserGroup::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_group_member-user_id-user")
+                            .from(UserGroupMember::Table, UserGroupMember::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // a model without any row here stays available to everyone
+        manager
+            .create_table(
+                Table::create()
+                    .table(ModelAccess::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ModelAccess::Id))
+                    .col(integer(ModelAccess::ModelId))
+                    .col(integer_null(ModelAccess::UserId))
+                    .col(integer_null(ModelAccess::GroupId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-model_access-model_id-model")
+                            .from(ModelAccess::Table, ModelAccess::ModelId)
+                            .to(Model::Table, Model::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-model_access-user_id-user")
+                            .from(ModelAccess::Table, ModelAccess::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-model_access-group_id-user_group")
+                            .from(ModelAccess::Table, ModelAccess::GroupId)
+                            .to(UserGroup::Table, UserGroup::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ModelAccess::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserGroupMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserGroup::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Model {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum UserGroup {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum UserGroupMember {
+    Table,
+    GroupId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum ModelAccess {
+    Table,
+    Id,
+    ModelId,
+    UserId,
+    GroupId,
+}
@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite cannot add a column with a foreign key, descendants are
+        // removed by the message delete route instead
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .add_column(integer_null(Message::ParentId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .add_column(integer_null(Chat::LeafId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // existing chats are linear, chain each message to the one before it
+        let conn = manager.get_connection();
+        conn.execute_unprepared(
+            "UPDATE message SET parent_id = (SELECT MAX(m.id) FROM message m \
+             WHERE m.chat_id = message.chat_id AND m.id < message.id)",
+        )
+        .await?;
+        conn.execute_unprepared(
+            "UPDATE chat SET leaf_id = (SELECT MAX(m.id) FROM message m WHERE m.chat_id = chat.id)",
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-message-parent_id")
+                    .table(Message::Table)
+                    .col(Message::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-message-parent_id")
+                    .table(Message::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .drop_column(Chat::LeafId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Message::Table)
+                    .drop_column(Message::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    ParentId,
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    LeafId,
+}
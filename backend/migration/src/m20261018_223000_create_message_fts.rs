@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        // rowid is the message id, the server keeps the index up to date when
+        // messages are written
+        conn.execute_unprepared(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS message_fts USING fts5(\
+             content, chat_id UNINDEXED, tokenize = 'unicode61 remove_diacritics 2')",
+        )
+        .await?;
+
+        // deleting a chat cascades to its messages, which fires this as well
+        conn.execute_unprepared(
+            "CREATE TRIGGER IF NOT EXISTS message_fts_delete AFTER DELETE ON message \
+             BEGIN DELETE FROM message_fts WHERE rowid = old.id; END",
+        )
+        .await?;
+
+        // user text and assistant text chunks of existing messages
+        conn.execute_unprepared(
+            "INSERT INTO message_fts (rowid, content, chat_id) \
+             SELECT id, json_extract(\"inner\", '$.c.text'), chat_id FROM message \
+             WHERE json_extract(\"inner\", '$.t') = 'user'",
+        )
+        .await?;
+        conn.execute_unprepared(
+            "INSERT INTO message_fts (rowid, content, chat_id) \
+             SELECT m.id, group_concat(json_extract(chunk.value, '$.c'), char(10)), m.chat_id \
+             FROM message m, json_each(m.\"inner\", '$.c') chunk \
+             WHERE json_extract(m.\"inner\", '$.t') = 'assistant' \
+             AND json_extract(chunk.value, '$.t') = 'text' \
+             GROUP BY m.id",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+        conn.execute_unprepared("DROP TRIGGER IF EXISTS message_fts_delete")
+            .await?;
+        conn.execute_unprepared("DROP TABLE IF EXISTS message_fts")
+            .await?;
+
+        Ok(())
+    }
+}
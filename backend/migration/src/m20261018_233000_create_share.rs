@@ -0,0 +1,82 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Share::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Share::Id))
+                    .col(integer(Share::ChatId))
+                    // unguessable part of the public link
+                    .col(string_uniq(Share::Slug))
+                    .col(string_null(Share::Title))
+                    // messages of the active branch at share time, as JSON
+                    .col(string(Share::Messages).default("[]"))
+                    .col(big_integer(Share::CreatedAt))
+                    .col(big_integer_null(Share::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-share-chat_id-chat")
+                            .from(Share::Table, Share::ChatId)
+                            .to(Chat::Table, Chat::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-share-chat_id")
+                    .table(Share::Table)
+                    .col(Share::ChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-share-chat_id")
+                    .table(Share::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Share::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Share {
+    Table,
+    Id,
+    ChatId,
+    Slug,
+    Title,
+    Messages,
+    CreatedAt,
+    ExpiresAt,
+}
@@ -0,0 +1,220 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Folder::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Folder::Id))
+                    .col(integer(Folder::OwnerId))
+                    .col(string(Folder::Name))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-folder-owner_id-user")
+                            .from(Folder::Table, Folder::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-folder-owner_id-name")
+                    .table(Folder::Table)
+                    .col(Folder::OwnerId)
+                    .col(Folder::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Tag::Id))
+                    .col(integer(Tag::OwnerId))
+                    .col(string(Tag::Name))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-tag-owner_id-user")
+                            .from(Tag::Table, Tag::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-tag-owner_id-name")
+                    .table(Tag::Table)
+                    .col(Tag::OwnerId)
+                    .col(Tag::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatTag::Table)
+                    .if_not_exists()
+                    .col(integer(ChatTag::ChatId))
+                    .col(integer(ChatTag::TagId))
+                    .primary_key(Index::create().col(ChatTag::ChatId).col(ChatTag::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-chat_tag-chat_id-chat")
+                            .from(ChatTag::Table, ChatTag::ChatId)
+                            .to(Chat::Table, Chat::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-chat_tag-tag_id-tag")
+                            .from(ChatTag::Table, ChatTag::TagId)
+                            .to(Tag::Table, Tag::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-chat_tag-tag_id")
+                    .table(ChatTag::Table)
+                    .col(ChatTag::TagId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite cannot add a column with a foreign key, chats are moved out
+        // of a folder by the folder delete route instead
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .add_column(integer_null(Chat::FolderId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .add_column(boolean(Chat::Pinned).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .add_column(boolean(Chat::Archived).default(false))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-chat-folder_id")
+                    .table(Chat::Table)
+                    .col(Chat::FolderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-chat-folder_id")
+                    .table(Chat::Table)
+                    .to_owned(),
+            )
+            .await?;
+        for column in [Chat::Archived, Chat::Pinned, Chat::FolderId] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Chat::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .drop_table(Table::drop().table(ChatTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Folder::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    Id,
+    FolderId,
+    Pinned,
+    Archived,
+}
+
+#[derive(DeriveIden)]
+enum Folder {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum Tag {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum ChatTag {
+    Table,
+    ChatId,
+    TagId,
+}
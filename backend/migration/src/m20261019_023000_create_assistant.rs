@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Assistant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Assistant::Id))
+                    .col(integer(Assistant::OwnerId))
+                    .col(string(Assistant::Name))
+                    .col(string(Assistant::Prompt))
+                    .col(integer_null(Assistant::ModelId))
+                    .col(integer(Assistant::Mode).default(0))
+                    // metadata of the starter files, as JSON
+                    .col(string(Assistant::Files).default("[]"))
+                    // visible to every user when set, editable by the owner only
+                    .col(boolean(Assistant::Shared).default(false))
+                    .col(big_integer(Assistant::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-assistant-owner_id-user")
+                            .from(Assistant::Table, Assistant::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-assistant-model_id-model")
+                            .from(Assistant::Table, Assistant::ModelId)
+                            .to(Model::Table, Model::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite cannot add a column with a foreign key, the assistant delete
+        // route detaches chats instead
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .add_column(integer_null(Chat::AssistantId))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chat::Table)
+                    .drop_column(Chat::AssistantId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Assistant::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Model {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    AssistantId,
+}
+
+#[derive(DeriveIden)]
+enum Assistant {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+    Prompt,
+    ModelId,
+    Mode,
+    Files,
+    Shared,
+    CreatedAt,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Memory::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Memory::Id))
+                    .col(integer(Memory::OwnerId))
+                    .col(string(Memory::Content))
+                    // little-endian f32 vector, only comparable within a model
+                    .col(blob(Memory::Embedding))
+                    .col(string(Memory::EmbeddingModel))
+                    // chat the model remembered it in, none when added by the user
+                    .col(integer_null(Memory::ChatId))
+                    .col(big_integer(Memory::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-memory-owner_id-user")
+                            .from(Memory::Table, Memory::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-memory-chat_id-chat")
+                            .from(Memory::Table, Memory::ChatId)
+                            .to(Chat::Table, Chat::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-memory-owner_id")
+                    .table(Memory::Table)
+                    .col(Memory::OwnerId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-memory-owner_id")
+                    .table(Memory::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Memory::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Memory {
+    Table,
+    Id,
+    OwnerId,
+    Content,
+    Embedding,
+    EmbeddingModel,
+    ChatId,
+    CreatedAt,
+}
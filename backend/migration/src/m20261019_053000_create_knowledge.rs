@@ -0,0 +1,175 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Knowledge::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Knowledge::Id))
+                    .col(integer(Knowledge::OwnerId))
+                    .col(string(Knowledge::Name))
+                    .col(big_integer(Knowledge::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-knowledge-owner_id-user")
+                            .from(Knowledge::Table, Knowledge::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-knowledge-owner_id-name")
+                    .table(Knowledge::Table)
+                    .col(Knowledge::OwnerId)
+                    .col(Knowledge::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(KnowledgeFile::Table)
+                    .if_not_exists()
+                    .col(pk_auto(KnowledgeFile::Id))
+                    .col(integer(KnowledgeFile::KnowledgeId))
+                    .col(string(KnowledgeFile::Name))
+                    .col(big_integer(KnowledgeFile::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-knowledge_file-knowledge_id-knowledge")
+                            .from(KnowledgeFile::Table, KnowledgeFile::KnowledgeId)
+                            .to(Knowledge::Table, Knowledge::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(KnowledgeChunk::Table)
+                    .if_not_exists()
+                    .col(pk_auto(KnowledgeChunk::Id))
+                    .col(integer(KnowledgeChunk::KnowledgeId))
+                    .col(integer(KnowledgeChunk::FileId))
+                    // one-based, none for documents without pages
+                    .col(integer_null(KnowledgeChunk::Page))
+                    .col(string(KnowledgeChunk::Content))
+                    // little-endian f32 vector, only comparable within a model
+                    .col(blob(KnowledgeChunk::Embedding))
+                    .col(string(KnowledgeChunk::EmbeddingModel))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-knowledge_chunk-knowledge_id-knowledge")
+                            .from(KnowledgeChunk::Table, KnowledgeChunk::KnowledgeId)
+                            .to(Knowledge::Table, Knowledge::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-knowledge_chunk-file_id-knowledge_file")
+                            .from(KnowledgeChunk::Table, KnowledgeChunk::FileId)
+                            .to(KnowledgeFile::Table, KnowledgeFile::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-knowledge_chunk-knowledge_id")
+                    .table(KnowledgeChunk::Table)
+                    .col(KnowledgeChunk::KnowledgeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-knowledge_chunk-knowledge_id")
+                    .table(KnowledgeChunk::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(KnowledgeChunk::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(KnowledgeFile::Table).to_owned())
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-knowledge-owner_id-name")
+                    .table(Knowledge::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Knowledge::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Knowledge {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum KnowledgeFile {
+    Table,
+    Id,
+    KnowledgeId,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum KnowledgeChunk {
+    Table,
+    Id,
+    KnowledgeId,
+    FileId,
+    Page,
+    Content,
+    Embedding,
+    EmbeddingModel,
+}
@@ -0,0 +1,160 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Comparison::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Comparison::Id))
+                    .col(integer(Comparison::ChatId))
+                    .col(integer(Comparison::UserMessageId))
+                    // reply kept as the canonical branch, none until picked
+                    .col(integer_null(Comparison::WinnerId))
+                    .col(big_integer(Comparison::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison-chat_id-chat")
+                            .from(Comparison::Table, Comparison::ChatId)
+                            .to(Chat::Table, Chat::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison-user_message_id-message")
+                            .from(Comparison::Table, Comparison::UserMessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison-winner_id-message")
+                            .from(Comparison::Table, Comparison::WinnerId)
+                            .to(Message::Table, Message::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-comparison-chat_id")
+                    .table(Comparison::Table)
+                    .col(Comparison::ChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComparisonReply::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ComparisonReply::Id))
+                    .col(integer(ComparisonReply::ComparisonId))
+                    .col(integer_null(ComparisonReply::ModelId))
+                    .col(integer(ComparisonReply::MessageId))
+                    .col(float(ComparisonReply::Cost).default(0.0))
+                    .col(integer(ComparisonReply::TokenCount).default(0))
+                    // until the reply is saved, none while streaming
+                    .col(big_integer_null(ComparisonReply::LatencyMs))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison_reply-comparison_id-comparison")
+                            .from(ComparisonReply::Table, ComparisonReply::ComparisonId)
+                            .to(Comparison::Table, Comparison::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison_reply-model_id-model")
+                            .from(ComparisonReply::Table, ComparisonReply::ModelId)
+                            .to(Model::Table, Model::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comparison_reply-message_id-message")
+                            .from(ComparisonReply::Table, ComparisonReply::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ComparisonReply::Table).to_owned())
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-comparison-chat_id")
+                    .table(Comparison::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Comparison::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Chat {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Model {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Comparison {
+    Table,
+    Id,
+    ChatId,
+    UserMessageId,
+    WinnerId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ComparisonReply {
+    Table,
+    Id,
+    ComparisonId,
+    ModelId,
+    MessageId,
+    Cost,
+    TokenCount,
+    LatencyMs,
+}
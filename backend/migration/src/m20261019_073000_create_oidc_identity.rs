@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OidcIdentity::Table)
+                    .if_not_exists()
+                    .col(pk_auto(OidcIdentity::Id))
+                    .col(integer(OidcIdentity::UserId))
+                    // "iss" and "sub" claims, together they identify the account at the provider
+                    .col(string(OidcIdentity::Issuer))
+                    .col(string(OidcIdentity::Subject))
+                    .col(big_integer(OidcIdentity::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-oidc_identity-user_id-user")
+                            .from(OidcIdentity::Table, OidcIdentity::UserId)
+                            .to(User::Table, User::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-oidc_identity-issuer-subject")
+                    .table(OidcIdentity::Table)
+                    .col(OidcIdentity::Issuer)
+                    .col(OidcIdentity::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-oidc_identity-issuer-subject")
+                    .table(OidcIdentity::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(OidcIdentity::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum OidcIdentity {
+    Table,
+    Id,
+    UserId,
+    Issuer,
+    Subject,
+    CreatedAt,
+}
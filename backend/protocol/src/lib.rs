@@ -389,8 +389,178 @@ pub struct UserPreference {
     // due to <Select> in frontend, string is used here instead of boolean
     #[serde(skip_serializing_if = "Option::is_none")]
     pub submit_on_enter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<CustomInstructions>,
+}
+
+/// Added to the system prompt of every chat, a chat may override it.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize, FromJsonQueryResult)]
+#[typeshare]
+pub struct CustomInstructions {
+    /// What the model should know about the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub about_me: Option<String>,
+    /// How the model should answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_style: Option<String>,
+}
+
+impl CustomInstructions {
+    /// Drop blank fields, so templates only see what was filled in.
+    pub fn normalized(self) -> Self {
+        let keep = |x: Option<String>| x.filter(|x| !x.trim().is_empty());
+        Self {
+            about_me: keep(self.about_me),
+            response_style: keep(self.response_style),
+        }
+    }
 }
 
+/// Summary standing in for the oldest messages of a chat.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct ChatSummary {
+    /// Last message covered by the summary
+    pub until: i32,
+    pub text: String,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter, DeriveActiveEnum,
+)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum UserRole {
+    #[default]
+    Member = 0,
+    Admin = 1,
+}
+
+/// How the spending of a budget is accumulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum BudgetPeriod {
+    /// Resets on the first day of each calendar month (UTC)
+    Monthly = 0,
+    /// Covers the last `window_days` days
+    Rolling = 1,
+}
+
+/// Kind of event recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum AuditAction {
+    LoginSuccess = 0,
+    LoginFail = 1,
+    AccountLocked = 2,
+    UserCreate = 3,
+    UserDelete = 4,
+    UserPasswordChange = 5,
+    UserRoleChange = 6,
+    UserUnlock = 7,
+    ModelCreate = 8,
+    ModelUpdate = 9,
+    ModelDelete = 10,
+    ChatDelete = 11,
+    ModelAccessChange = 12,
+    GroupCreate = 13,
+    GroupUpdate = 14,
+    GroupDelete = 15,
+    ShareCreate = 16,
+    ShareDelete = 17,
+    BudgetUpdate = 18,
+    BudgetDelete = 19,
+    TotpDisable = 20,
+    SessionRevoke = 21,
+    ApiTokenCreate = 22,
+    ApiTokenDelete = 23,
+}
+
+/// What the `target_id` of an audit log entry refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTarget {
+    User,
+    Model,
+    Chat,
+    Group,
+    Share,
+    Session,
+    ApiToken,
+}
+
+impl AuditAction {
+    /// Kind of the target of this action, `None` when it has no target.
+    pub fn target(self) -> Option<AuditTarget> {
+        match self {
+            AuditAction::LoginSuccess => None,
+            AuditAction::LoginFail
+            | AuditAction::AccountLocked
+            | AuditAction::UserCreate
+            | AuditAction::UserDelete
+            | AuditAction::UserPasswordChange
+            | AuditAction::UserRoleChange
+            | AuditAction::UserUnlock
+            | AuditAction::BudgetUpdate
+            | AuditAction::BudgetDelete
+            | AuditAction::TotpDisable => Some(AuditTarget::User),
+            AuditAction::ModelCreate
+            | AuditAction::ModelUpdate
+            | AuditAction::ModelDelete
+            | AuditAction::ModelAccessChange => Some(AuditTarget::Model),
+            AuditAction::ChatDelete => Some(AuditTarget::Chat),
+            AuditAction::GroupCreate | AuditAction::GroupUpdate | AuditAction::GroupDelete => {
+                Some(AuditTarget::Group)
+            }
+            AuditAction::ShareCreate | AuditAction::ShareDelete => Some(AuditTarget::Share),
+            AuditAction::SessionRevoke => Some(AuditTarget::Session),
+            AuditAction::ApiTokenCreate | AuditAction::ApiTokenDelete => {
+                Some(AuditTarget::ApiToken)
+            }
+        }
+    }
+}
+
+/// Permission granted to a personal API token.
+///
+/// Tokens are denied by default, each scope unlocks a fixed set of routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Read chats, messages, files and the model list
+    ChatRead,
+    /// Create chats, send messages, upload files and stream replies
+    MessageCreate,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct ApiTokenScopes(pub Vec<ApiTokenScope>);
+
+/// Messages of the active branch of a chat, frozen when it was shared.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct ShareSnapshot(pub Vec<SharedMessage>);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SharedMessage {
+    pub id: i32,
+    pub created_at: i64,
+    pub inner: MessageInner,
+}
+
+/// Starter files of an assistant, copied into each chat created from it.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct AssistantFiles(pub Vec<FileMetadata>);
+
+/// Keyed hashes of unused TOTP recovery codes.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct RecoveryCodes(pub Vec<String>);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
 pub enum OcrEngine {
     Native,
@@ -471,6 +641,9 @@ pub struct ModelConfig {
     pub parameter: ModelParameter,
     #[serde(default)]
     pub media_gen: MediaGenerationConfig,
+    /// Tokens the model accepts, read from the model listing when omitted
+    #[serde(default)]
+    pub context_length: Option<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Default)]
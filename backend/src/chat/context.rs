@@ -1,13 +1,24 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::Context as _;
-use sea_orm::DatabaseConnection;
+use entity::{chat, comparison, comparison_reply, message};
+use futures_util::future::{BoxFuture, join_all};
+use protocol::{FileMetadata, MessageInner};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait,
+    QueryFilter, TransactionTrait,
+};
 
 use super::prompt::Prompt;
-use super::session::CompletionSession;
+use super::queue::{Queue, Queued};
+use super::session::{ChatBusy, CompletionSession, SharedPublisher};
 use super::strategies::{self, Strategy};
 use super::token::Token;
 use super::tools::Tools;
+use crate::utils::{
+    branch, chat::ChatMode, embedding::Embedder, memory::MemoryStore, search, timestamp,
+};
 
 pub(crate) use super::channel;
 
@@ -17,6 +28,14 @@ pub enum StreamEndReason {
     Exhausted,
 }
 
+/// What a saved reply took.
+struct Usage {
+    cost: f32,
+    token_count: i32,
+    /// Time spent generating the reply
+    elapsed: Duration,
+}
+
 /// The global context for the chat system.
 pub struct Context {
     pub(crate) db: DatabaseConnection,
@@ -25,6 +44,9 @@ pub struct Context {
     pub(crate) prompt: Arc<Prompt>,
     pub(crate) blob: Arc<crate::utils::blob::BlobDB>,
     pub(crate) tools: Tools,
+    pub(crate) embedder: Arc<Embedder>,
+    pub(crate) memory: MemoryStore,
+    pub(crate) queue: Queue,
 }
 
 impl Context {
@@ -34,6 +56,7 @@ impl Context {
         db: DatabaseConnection,
         openrouter: Arc<crate::openrouter::Openrouter>,
         blob: Arc<crate::utils::blob::BlobDB>,
+        embedder: Arc<Embedder>,
     ) -> Result<Self, anyhow::Error> {
         let prompt = Prompt::new().context("failed to load prompt templates")?;
 
@@ -44,6 +67,9 @@ impl Context {
             prompt: Arc::new(prompt),
             blob,
             tools: Tools::new(),
+            memory: MemoryStore::new(embedder.clone()),
+            embedder,
+            queue: Queue::new(),
         })
     }
 
@@ -77,19 +103,328 @@ impl Context {
         !self.channel.publishable(chat_id)
     }
 
-    /// Runs a complete chat turn: dispatches the strategy, then saves.
-    /// Called from a spawned task.
+    /// Writes a user message below `parent_id` and makes it the active
+    /// branch.
+    pub async fn insert_user_message(
+        &self,
+        chat_id: i32,
+        parent_id: Option<i32>,
+        text: String,
+        files: Vec<FileMetadata>,
+    ) -> anyhow::Result<message::Model> {
+        let user_msg = message::ActiveModel {
+            chat_id: Set(chat_id),
+            inner: Set(MessageInner::User { text, files }),
+            created_at: Set(timestamp::now()),
+            parent_id: Set(parent_id),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        branch::set_leaf(&self.db, chat_id, Some(user_msg.id)).await?;
+
+        if let Err(e) = search::index(&self.db, user_msg.id, chat_id, &user_msg.inner).await {
+            log::warn!("cannot index message {}: {}", user_msg.id, e);
+        }
+
+        Ok(user_msg)
+    }
+
+    /// Starts the assistant reply to `user_msg`, which ends the active
+    /// branch, and returns the id of the reply.
+    ///
+    /// When the reply cannot be started, a `user_msg` created for this reply
+    /// is removed again and the branch active before is restored, unless the
+    /// active branch no longer ends at `user_msg`.
+    pub async fn reply(
+        self: &Arc<Self>,
+        user_id: i32,
+        model_id: i32,
+        mode: ChatMode,
+        user_msg: &message::Model,
+        previous_leaf: Option<i32>,
+        created: bool,
+    ) -> anyhow::Result<i32> {
+        let chat_id = user_msg.chat_id;
+        let session = match self
+            .get_session(user_id, chat_id, model_id, mode.into())
+            .await
+        {
+            Ok(session) => session,
+            Err(e) => {
+                // nothing will answer it, so don't keep the message around
+                if created {
+                    user_msg.clone().delete(&self.db).await?;
+                }
+                // a reply started meanwhile keeps its branch
+                branch::reset_leaf(&self.db, chat_id, user_msg.id, previous_leaf).await?;
+                return Err(e);
+            }
+        };
+
+        let id = session.message.id;
+        let processor = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = processor.process(mode.into(), session).await {
+                log::error!("Failed to process message: {:?}", e);
+            }
+        });
+
+        Ok(id)
+    }
+
+    /// Ends the reservation of a message admitted to the idle chat, then
+    /// starts the messages queued behind it once the chat is free.
+    pub async fn release(self: &Arc<Self>, chat_id: i32) {
+        self.queue.started(chat_id);
+        self.clone().start_next(chat_id).await;
+    }
+
+    /// Starts the reply to the next queued message of the chat, unless the
+    /// chat is busy. Messages that can no longer be answered are dropped and
+    /// reported to listeners of the queue.
+    ///
+    /// Boxed, as it starts sessions which start it again once saved.
+    fn start_next(self: Arc<Self>, chat_id: i32) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            // checked again after each start, a reply that ended before
+            // `started` found nothing to take
+            while !self.is_streaming(chat_id) {
+                let Some(next) = self.queue.pop(chat_id) else {
+                    break;
+                };
+                if self.is_streaming(chat_id) {
+                    // another reply took the chat, it continues once saved
+                    self.queue.push_front(chat_id, next);
+                } else if let Err(e) = self.start_queued(chat_id, next.clone()).await {
+                    match e.downcast_ref::<ChatBusy>() {
+                        // taken right after the check above
+                        Some(_) => self.queue.push_front(chat_id, next),
+                        None => {
+                            log::warn!("cannot start queued message of chat {}: {:#}", chat_id, e);
+                            self.queue.refuse(chat_id, next.id, e.to_string());
+                        }
+                    }
+                }
+                self.queue.started(chat_id);
+            }
+        })
+    }
+
+    async fn start_queued(self: &Arc<Self>, chat_id: i32, next: Queued) -> anyhow::Result<()> {
+        let chat = chat::Entity::find_by_id(chat_id)
+            .one(&self.db)
+            .await?
+            .context("chat not found")?;
+        let user_msg = self
+            .insert_user_message(chat_id, chat.leaf_id, next.text, next.files)
+            .await?;
+        self.reply(
+            next.user_id,
+            next.model_id,
+            next.mode,
+            &user_msg,
+            chat.leaf_id,
+            true,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Starts a reply to `user_msg` from each of `model_ids` side by side and
+    /// returns the comparison with its replies, in the order of `model_ids`.
+    ///
+    /// The replies share the stream of the chat, each token tagged with its
+    /// reply. The reply of the first model is the active branch until a
+    /// winner is picked. When any reply is refused, none is started, a
+    /// `user_msg` created for this comparison is removed again and the branch
+    /// active before is restored, unless it no longer ends at `user_msg`.
+    pub async fn compare(
+        self: &Arc<Self>,
+        user_id: i32,
+        model_ids: &[i32],
+        mode: ChatMode,
+        user_msg: &message::Model,
+        previous_leaf: Option<i32>,
+        created: bool,
+    ) -> anyhow::Result<(comparison::Model, Vec<comparison_reply::Model>)> {
+        anyhow::ensure!(!model_ids.is_empty(), "no model to compare");
+        let chat_id = user_msg.chat_id;
+
+        let prepared = match self.channel.clone().publish(chat_id) {
+            Some(publisher) => {
+                let publisher: SharedPublisher = Arc::new(Mutex::new(publisher));
+                self.compare_sessions(user_id, chat_id, model_ids, mode, &publisher)
+                    .await
+                    .map(|sessions| (publisher, sessions))
+            }
+            None => Err(ChatBusy.into()),
+        };
+        let (publisher, sessions) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                // nothing will answer it, so don't keep the message around
+                if created {
+                    user_msg.clone().delete(&self.db).await?;
+                }
+                // a reply started meanwhile keeps its branch
+                branch::reset_leaf(&self.db, chat_id, user_msg.id, previous_leaf).await?;
+                return Err(e);
+            }
+        };
+
+        let txn = self.db.begin().await?;
+        let comparison = comparison::ActiveModel {
+            chat_id: Set(chat_id),
+            user_message_id: Set(user_msg.id),
+            winner_id: Set(None),
+            created_at: Set(timestamp::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+        let mut replies = Vec::with_capacity(sessions.len());
+        for session in &sessions {
+            let reply = comparison_reply::ActiveModel {
+                comparison_id: Set(comparison.id),
+                model_id: Set(Some(session.model.id)),
+                message_id: Set(session.message.id),
+                cost: Set(0.0),
+                token_count: Set(0),
+                latency_ms: Set(None),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            replies.push(reply);
+        }
+        txn.commit().await?;
+
+        branch::set_leaf(&self.db, chat_id, Some(sessions[0].message.id)).await?;
+
+        let reply_ids = replies.iter().map(|x| x.id).collect();
+        tokio::spawn(
+            self.clone()
+                .run_comparison(chat_id, mode, publisher, reply_ids, sessions),
+        );
+
+        Ok((comparison, replies))
+    }
+
+    /// Prepares a session per compared model. Should one be refused, the
+    /// replies prepared so far are removed again.
+    async fn compare_sessions(
+        self: &Arc<Self>,
+        user_id: i32,
+        chat_id: i32,
+        model_ids: &[i32],
+        mode: ChatMode,
+        publisher: &SharedPublisher,
+    ) -> anyhow::Result<Vec<CompletionSession>> {
+        let mut sessions = Vec::with_capacity(model_ids.len());
+        for &model_id in model_ids {
+            let session = CompletionSession::compare(
+                self.clone(),
+                user_id,
+                chat_id,
+                model_id,
+                mode.into(),
+                publisher.clone(),
+            )
+            .await;
+            match session {
+                Ok(session) => sessions.push(session),
+                Err(e) => {
+                    let ids = sessions.iter().map(|x| x.message.id).collect::<Vec<_>>();
+                    message::Entity::delete_many()
+                        .filter(message::Column::Id.is_in(ids))
+                        .exec(&self.db)
+                        .await?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Runs the replies of a comparison concurrently and records what each
+    /// took, then moves on to the next queued message. Called from a spawned
+    /// task.
+    async fn run_comparison(
+        self: Arc<Self>,
+        chat_id: i32,
+        mode: ChatMode,
+        publisher: SharedPublisher,
+        reply_ids: Vec<i32>,
+        mut sessions: Vec<CompletionSession>,
+    ) {
+        // summarize once, rather than once per model
+        CompletionSession::prepare_all(&mut sessions).await;
+
+        join_all(reply_ids.into_iter().zip(sessions).enumerate().map(
+            |(i, (reply_id, session))| {
+                let ctx = self.clone();
+                async move {
+                    // the first reply is the active one, so it speaks for the chat
+                    let usage = match ctx.complete(mode.into(), session, i == 0).await {
+                        Ok(usage) => usage,
+                        Err(e) => {
+                            log::error!("Failed to process comparison reply: {:?}", e);
+                            return;
+                        }
+                    };
+                    let reply = comparison_reply::ActiveModel {
+                        id: Set(reply_id),
+                        cost: Set(usage.cost),
+                        token_count: Set(usage.token_count),
+                        latency_ms: Set(Some(usage.elapsed.as_millis() as i64)),
+                        ..Default::default()
+                    };
+                    if let Err(e) = comparison_reply::Entity::update(reply).exec(&ctx.db).await {
+                        log::warn!("cannot record comparison reply {}: {}", reply_id, e);
+                    }
+                }
+            },
+        ))
+        .await;
+
+        // every reply is saved, release the chat
+        drop(publisher);
+        self.start_next(chat_id).await;
+    }
+
+    /// Runs a complete chat turn: dispatches the strategy, then saves and
+    /// moves on to the next queued message. Called from a spawned task.
     pub async fn process(
         self: Arc<Self>,
         strategy: Strategy,
-        mut session: CompletionSession,
+        session: CompletionSession,
     ) -> anyhow::Result<()> {
+        let chat_id = session.chat.id;
+        let saved = self.complete(strategy, session, true).await;
+        self.start_next(chat_id).await;
+        saved?;
+        Ok(())
+    }
+
+    /// Dispatches the strategy of a session and saves its reply. Only the
+    /// `primary` reply of a turn updates the model and the title of the chat.
+    async fn complete(
+        self: &Arc<Self>,
+        strategy: Strategy,
+        mut session: CompletionSession,
+        primary: bool,
+    ) -> anyhow::Result<Usage> {
         let msg_id = session.message.id;
         log::debug!("session started: msg_id={}", msg_id);
 
         // Sync model/mode to chat
-        if let Err(e) = session.sync_chat_model().await {
-            log::error!("model sync error: {e:#}");
+        if primary {
+            let synced = session.sync_chat_model().await;
+            if let Err(e) = synced {
+                log::error!("model sync error: {e:#}");
+            }
         }
 
         // Emit Start token
@@ -99,14 +434,26 @@ impl Context {
             user_msg_id,
         });
 
+        if let Some(status) = session.budget_warning.take() {
+            session.add_token(Token::BudgetWarning {
+                spent: status.spent,
+                limit: status.limit,
+            });
+        }
+
+        session.prepare().await;
+
         // Run the selected strategy
-        match strategies::dispatch(self.clone(), strategy, &mut session).await {
-            Ok(true) => {}
-            Ok(false) => {
+        let started = Instant::now();
+        let result = strategies::dispatch(self.clone(), strategy, &mut session).await;
+        let elapsed = started.elapsed();
+        match result {
+            Ok(false) if primary => {
                 if let Err(e) = session.try_generate_title().await {
                     log::error!("title generation error: {e:#}");
                 }
             }
+            Ok(_) => {}
             Err(e) => {
                 log::error!("completion error: {e:#}");
                 session.add_error(format!("{e}"));
@@ -114,9 +461,14 @@ impl Context {
         }
 
         // Persist
+        let (cost, token_count) = session.usage();
         session.save().await?;
 
         log::debug!("session completed: msg_id={}", msg_id);
-        Ok(())
+        Ok(Usage {
+            cost,
+            token_count,
+            elapsed,
+        })
     }
 }
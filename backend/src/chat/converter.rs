@@ -63,6 +63,23 @@ pub fn token_to_sse(token: Token) -> Option<SseResp> {
         Token::DeepReport(content) => Some(SseResp::DeepReport(content)),
         Token::Image(file_id) => Some(SseResp::Image(file_id)),
         Token::UrlCitation(citations) => Some(SseResp::UrlCitation(citations)),
+        Token::BudgetWarning { spent, limit } => {
+            Some(SseResp::BudgetWarning(SseRespBudgetWarning {
+                spent,
+                limit,
+            }))
+        }
+        Token::Compare {
+            model_id,
+            message_id,
+            token,
+        } => token_to_sse(*token).map(|event| {
+            SseResp::Compare(SseRespCompare {
+                model_id,
+                id: message_id,
+                event: Box::new(event),
+            })
+        }),
         Token::Empty => None,
     }
 }
@@ -286,6 +303,25 @@ mod tests {
         })
         .unwrap();
         assert!(matches!(sse, SseResp::ToolCall(tc) if tc.name == "web_search"));
+
+        let sse = token_to_sse(Token::Compare {
+            model_id: 2,
+            message_id: 7,
+            token: Box::new(Token::Assistant("hi".into())),
+        })
+        .unwrap();
+        assert!(matches!(
+            sse,
+            SseResp::Compare(c) if c.id == 7 && matches!(*c.event, SseResp::Token(_))
+        ));
+        assert!(
+            token_to_sse(Token::Compare {
+                model_id: 2,
+                message_id: 7,
+                token: Box::new(Token::Empty),
+            })
+            .is_none()
+        );
     }
 
     #[test]
@@ -0,0 +1,229 @@
+//! Keeps the history sent upstream within the context window of the model.
+//!
+//! Once the active branch outgrows the window, its oldest turns are folded
+//! into a summary stored on the chat. Later turns extend that summary instead
+//! of recomputing it. Token counts are estimated from the text length.
+
+use entity::message;
+use protocol::{AssistantChunk, MessageInner};
+
+use crate::config::HISTORY_CONTEXT_RATIO;
+
+/// Rough characters per token of common tokenizers
+const CHARS_PER_TOKEN: usize = 4;
+/// Flat estimate for an attached file or image
+const FILE_TOKENS: usize = 1000;
+/// Role markers and separators of each message
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Estimate the tokens of a piece of text.
+pub fn estimate_text_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Estimate the tokens a message takes once sent upstream.
+///
+/// Reasoning is left out, as it is not sent back to the model.
+pub fn estimate_tokens(inner: &MessageInner) -> usize {
+    let tokens = match inner {
+        MessageInner::User { text, files } => {
+            estimate_text_tokens(text) + files.len() * FILE_TOKENS
+        }
+        MessageInner::Assistant(chunks) => chunks
+            .iter()
+            .map(|chunk| match chunk {
+                AssistantChunk::Text(text) => estimate_text_tokens(text),
+                AssistantChunk::ToolCall { arg, name, .. } => {
+                    estimate_text_tokens(name) + estimate_text_tokens(arg)
+                }
+                AssistantChunk::ToolResult {
+                    response, files, ..
+                } => estimate_text_tokens(response) + files.len() * FILE_TOKENS,
+                AssistantChunk::Image(_) | AssistantChunk::ImageWithDimensions { .. } => {
+                    FILE_TOKENS
+                }
+                _ => 0,
+            })
+            .sum(),
+    };
+    tokens + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// Tokens the history may take in a context window of `context_length`.
+pub fn budget(context_length: u32) -> usize {
+    (context_length as f32 * HISTORY_CONTEXT_RATIO) as usize
+}
+
+/// Number of leading messages to fold into the summary, `None` when the
+/// history and the current summary already fit in `budget`.
+///
+/// Enough is folded to bring the rest down to half of the budget, so the
+/// following turns can reuse the summary. The latest message is always
+/// kept and the rest starts at a user message.
+pub fn fold_point(
+    history: &[message::Model],
+    summary_tokens: usize,
+    budget: usize,
+) -> Option<usize> {
+    let tokens: Vec<usize> = history.iter().map(|x| estimate_tokens(&x.inner)).collect();
+    let mut rest: usize = tokens.iter().sum();
+    if summary_tokens + rest <= budget {
+        return None;
+    }
+
+    let mut point = 0;
+    for token in &tokens {
+        if rest <= budget / 2 {
+            break;
+        }
+        rest -= token;
+        point += 1;
+    }
+
+    let last = history.len().saturating_sub(1);
+    point = point.min(last);
+    while point < last && !matches!(history[point].inner, MessageInner::User { .. }) {
+        point += 1;
+    }
+
+    (point > 0).then_some(point)
+}
+
+/// Plain-text transcript handed to the task model, starting from the
+/// previous summary when there is one.
+pub fn transcript(summary: Option<&str>, history: &[message::Model]) -> String {
+    let mut lines = Vec::new();
+    if let Some(summary) = summary {
+        lines.push(format!("Summary of earlier conversation:\n{}", summary));
+    }
+    for message in history {
+        match &message.inner {
+            MessageInner::User { text, files } => {
+                let mut line = format!("User: {}", text);
+                for file in files {
+                    line.push_str(&format!("\n[attached {}]", file.name));
+                }
+                lines.push(line);
+            }
+            MessageInner::Assistant(chunks) => {
+                let text = chunks
+                    .iter()
+                    .filter_map(|x| x.as_text())
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join("");
+                if !text.is_empty() {
+                    lines.push(format!("Assistant: {}", text));
+                }
+            }
+        }
+    }
+    lines.join("\n\n")
+}
+
+/// Message standing in for the folded turns.
+pub fn summary_message(summary: &str) -> String {
+    format!(
+        "<conversation_summary>\n{}\n</conversation_summary>",
+        summary
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::{FileKind, FileMetadata};
+
+    use super::*;
+
+    fn user(id: i32, text: &str) -> message::Model {
+        message(
+            id,
+            MessageInner::User {
+                text: text.to_owned(),
+                files: Vec::new(),
+            },
+        )
+    }
+
+    fn assistant(id: i32, text: &str) -> message::Model {
+        message(
+            id,
+            MessageInner::Assistant(vec![
+                AssistantChunk::Reasoning("thinking".repeat(100)),
+                AssistantChunk::Text(text.to_owned()),
+            ]),
+        )
+    }
+
+    fn message(id: i32, inner: MessageInner) -> message::Model {
+        message::Model {
+            id,
+            chat_id: 1,
+            price: 0.0,
+            token_count: 0,
+            inner,
+            created_at: 0,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(&user(1, "abcdefgh").inner), 2 + 4);
+        // reasoning is not counted
+        assert_eq!(estimate_tokens(&assistant(1, "abcd").inner), 1 + 4);
+
+        let inner = MessageInner::User {
+            text: String::new(),
+            files: vec![FileMetadata {
+                name: "a.png".to_owned(),
+                id: 1,
+                kind: FileKind::Image,
+                dimensions: None,
+            }],
+        };
+        assert_eq!(estimate_tokens(&inner), FILE_TOKENS + 4);
+    }
+
+    #[test]
+    fn test_fold_point_fits() {
+        let history = vec![user(1, "hi"), assistant(2, "hello"), user(3, "bye")];
+        assert_eq!(fold_point(&history, 0, 100), None);
+        assert_eq!(fold_point(&[], 0, 0), None);
+    }
+
+    #[test]
+    fn test_fold_point_starts_at_user() {
+        // 104 tokens per message
+        let long = "x".repeat(400);
+        let history = vec![
+            user(1, &long),
+            assistant(2, &long),
+            user(3, &long),
+            assistant(4, &long),
+            user(5, &long),
+        ];
+        // the rest must come down to 220 after folding 3 messages, but the
+        // 4th is a reply
+        assert_eq!(fold_point(&history, 0, 440), Some(4));
+        // the summary counts against the budget
+        assert_eq!(fold_point(&history, 0, 520), None);
+        assert_eq!(fold_point(&history, 10, 520), Some(4));
+    }
+
+    #[test]
+    fn test_fold_point_keeps_latest() {
+        let long = "x".repeat(4000);
+        let history = vec![user(1, &long), assistant(2, &long), user(3, &long)];
+        assert_eq!(fold_point(&history, 0, 10), Some(2));
+    }
+
+    #[test]
+    fn test_transcript() {
+        let history = vec![user(1, "hi"), assistant(2, "hello")];
+        assert_eq!(
+            transcript(Some("greeted"), &history),
+            "Summary of earlier conversation:\ngreeted\n\nUser: hi\n\nAssistant: hello"
+        );
+    }
+}
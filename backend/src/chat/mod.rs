@@ -5,7 +5,9 @@ pub(crate) mod channel {
 mod context;
 pub(crate) mod converter;
 mod helper;
+mod history;
 mod prompt;
+pub(crate) mod queue;
 mod session;
 mod strategies;
 mod stream_buffer;
@@ -16,6 +18,7 @@ mod tools;
 mod deep_research;
 
 pub(crate) use context::Context;
+pub(crate) use session::ChatBusy;
 pub(crate) use session::CompletionSession;
 pub(crate) use session::TokenSink;
 pub(crate) use token::Token;
@@ -2,10 +2,12 @@
 //!
 //! Templates live in `agent/prompt/` and are embedded at compile time.
 //! Each render method populates locale/time/model variables and returns
-//! the final system-prompt string.
+//! the final system-prompt string. Chat prompts also carry the
+//! [`Customization`] of the chat.
 
 use anyhow::{Context as _, Result};
 use minijinja::Environment;
+use protocol::CustomInstructions;
 use rust_embed_for_web::{EmbedableFile, RustEmbed};
 use time::macros::format_description;
 
@@ -32,6 +34,13 @@ fn current_time() -> String {
         .unwrap_or_default()
 }
 
+/// Additions to the mode template chosen by the user.
+pub struct Customization<'a> {
+    /// Role prompt of the assistant the chat was created from
+    pub assistant_prompt: Option<&'a str>,
+    pub instructions: &'a CustomInstructions,
+}
+
 pub struct Prompt {
     env: Environment<'static>,
 }
@@ -50,6 +59,11 @@ impl Prompt {
             ),
             ("includes/de_ai.j2", "includes/de_ai.j2"),
             ("includes/formatting.j2", "includes/formatting.j2"),
+            ("includes/assistant.j2", "includes/assistant.j2"),
+            (
+                "includes/custom_instructions.j2",
+                "includes/custom_instructions.j2",
+            ),
         ];
 
         for (name, path) in include_templates {
@@ -67,6 +81,7 @@ impl Prompt {
             ("coordinator", "coordinator.j2"),
             ("context", "context.j2"),
             ("title_generation", "title_generation.j2"),
+            ("summary", "summary.j2"),
         ];
 
         #[cfg(feature = "deep-research")]
@@ -77,6 +92,7 @@ impl Prompt {
             ("coordinator", "coordinator.j2"),
             ("context", "context.j2"),
             ("title_generation", "title_generation.j2"),
+            ("summary", "summary.j2"),
             ("deep/prompt_enhancer", "deepresearch/prompt_enhancer.j2"),
             ("deep/planner", "deepresearch/planner.j2"),
             ("deep/researcher", "deepresearch/researcher.j2"),
@@ -114,6 +130,7 @@ impl Prompt {
         model_id: &str,
         model_provider: &str,
         model_supported_parameters: &[String],
+        custom: &Customization<'_>,
     ) -> Result<String> {
         let tmpl = self.env.get_template("normal")?;
         Ok(tmpl.render(minijinja::context! {
@@ -122,6 +139,8 @@ impl Prompt {
             model_id,
             model_provider,
             model_supported_parameters,
+            assistant_prompt => custom.assistant_prompt,
+            instructions => custom.instructions,
         })?)
     }
 
@@ -132,6 +151,7 @@ impl Prompt {
         model_id: &str,
         model_provider: &str,
         model_supported_parameters: &[String],
+        custom: &Customization<'_>,
     ) -> Result<String> {
         let tmpl = self.env.get_template("search")?;
         Ok(tmpl.render(minijinja::context! {
@@ -140,6 +160,8 @@ impl Prompt {
             model_id,
             model_provider,
             model_supported_parameters,
+            assistant_prompt => custom.assistant_prompt,
+            instructions => custom.instructions,
         })?)
     }
 
@@ -154,6 +176,7 @@ impl Prompt {
         video_model_id: Option<&str>,
         image_model_supported_parameters: &[String],
         video_model_supported_parameters: &[String],
+        custom: &Customization<'_>,
     ) -> Result<String> {
         let tmpl = self.env.get_template("media")?;
         Ok(tmpl.render(minijinja::context! {
@@ -166,6 +189,8 @@ impl Prompt {
             video_model_id,
             image_model_supported_parameters,
             video_model_supported_parameters,
+            assistant_prompt => custom.assistant_prompt,
+            instructions => custom.instructions,
         })?)
     }
 
@@ -175,13 +200,15 @@ impl Prompt {
         time: &str,
         chat_title: Option<&str>,
         contain_dollar_sign: bool,
+        memories: &[String],
     ) -> Result<String> {
         let tmpl = self.env.get_template("context")?;
         Ok(tmpl.render(minijinja::context! {
             llumen_related,
             time,
             chat_title,
-            contain_dollar_sign
+            contain_dollar_sign,
+            memories
         })?)
     }
 
@@ -189,6 +216,11 @@ impl Prompt {
         let tmpl = self.env.get_template("title_generation")?;
         Ok(tmpl.render(minijinja::context! { locale })?)
     }
+
+    pub fn render_summary(&self, locale: &str) -> Result<String> {
+        let tmpl = self.env.get_template("summary")?;
+        Ok(tmpl.render(minijinja::context! { locale })?)
+    }
 }
 
 #[cfg(feature = "deep-research")]
@@ -0,0 +1,340 @@
+//! Per-chat FIFO of user messages sent while a reply is still streaming.
+//!
+//! Queued messages are kept in memory only; they are written to the chat
+//! once their turn comes, after the streaming session is saved. Listeners
+//! are told about every change of a queue and about messages refused when
+//! their turn came, see [`Queue::subscribe`].
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use protocol::FileMetadata;
+use tokio::sync::broadcast;
+
+use crate::utils::chat::ChatMode;
+
+/// Changes kept for slow listeners before they miss some
+const CHANGE_CAPACITY: usize = 64;
+
+/// A user message waiting for its turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Queued {
+    pub id: i32,
+    pub user_id: i32,
+    pub model_id: i32,
+    pub mode: ChatMode,
+    pub text: String,
+    pub files: Vec<FileMetadata>,
+}
+
+/// What happened to the queue of a chat.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Messages of the chat were queued, started or removed
+    Updated(i32),
+    /// A queued message was refused when its turn came
+    Refused {
+        chat_id: i32,
+        id: i32,
+        reason: String,
+    },
+}
+
+/// Outcome of [`Queue::admit`].
+#[derive(Debug, PartialEq)]
+pub enum Admission {
+    /// Queued behind the current reply with the given id
+    Queued(i32),
+    /// Nothing is pending, the message can be answered right away. The chat
+    /// is reserved for it until [`Queue::started`] is called.
+    Idle(Queued),
+}
+
+#[derive(Default)]
+struct Lane {
+    pending: VecDeque<Queued>,
+    /// Set while the message taken by [`Queue::pop`], or admitted to the
+    /// idle chat, is being started
+    starting: bool,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: i32,
+    lanes: HashMap<i32, Lane>,
+}
+
+pub struct Queue {
+    inner: Mutex<Inner>,
+    changes: broadcast::Sender<Change>,
+}
+
+impl Queue {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            changes: broadcast::channel(CHANGE_CAPACITY).0,
+        }
+    }
+
+    /// Queue `message` when the chat is `streaming` or other messages are
+    /// already waiting, so replies keep the order messages were sent in.
+    ///
+    /// `streaming` is checked while the queue is locked, so a message is
+    /// never left behind by a session that just ended. The id of `message`
+    /// is assigned here.
+    pub fn admit(
+        &self,
+        chat_id: i32,
+        streaming: impl FnOnce() -> bool,
+        mut message: Queued,
+    ) -> Admission {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        message.id = inner.next_id;
+        let lane = inner.lanes.entry(chat_id).or_default();
+        if !lane.starting && lane.pending.is_empty() && !streaming() {
+            // messages sent until it started wait behind it
+            lane.starting = true;
+            return Admission::Idle(message);
+        }
+
+        let id = message.id;
+        lane.pending.push_back(message);
+        drop(inner);
+
+        self.notify(chat_id);
+        Admission::Queued(id)
+    }
+
+    /// Take the next message of the chat, unless another one is still being
+    /// started. Until [`Queue::started`] is called, new messages are queued
+    /// behind it.
+    pub fn pop(&self, chat_id: i32) -> Option<Queued> {
+        let mut inner = self.inner.lock().unwrap();
+        let lane = inner.lanes.get_mut(&chat_id)?;
+        if lane.starting {
+            return None;
+        }
+        let message = lane.pending.pop_front();
+        lane.starting = message.is_some();
+        if !lane.starting {
+            inner.lanes.remove(&chat_id);
+        }
+        drop(inner);
+
+        if message.is_some() {
+            self.notify(chat_id);
+        }
+        message
+    }
+
+    /// Put a message taken by [`Queue::pop`] or admitted to the idle chat
+    /// back in front, when the chat turned out to be busy.
+    pub fn push_front(&self, chat_id: i32, message: Queued) {
+        self.inner
+            .lock()
+            .unwrap()
+            .lanes
+            .entry(chat_id)
+            .or_default()
+            .pending
+            .push_front(message);
+        self.notify(chat_id);
+    }
+
+    /// Mark the message taken by [`Queue::pop`] or admitted to the idle chat
+    /// as started, or given up.
+    pub fn started(&self, chat_id: i32) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(lane) = inner.lanes.get_mut(&chat_id) else {
+            return;
+        };
+        lane.starting = false;
+        if lane.pending.is_empty() {
+            inner.lanes.remove(&chat_id);
+        }
+    }
+
+    /// Messages waiting in the chat, next first.
+    pub fn list(&self, chat_id: i32) -> Vec<Queued> {
+        self.inner
+            .lock()
+            .unwrap()
+            .lanes
+            .get(&chat_id)
+            .map(|x| x.pending.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Remove a waiting message, returning it unless it already started.
+    pub fn cancel(&self, chat_id: i32, id: i32) -> Option<Queued> {
+        let mut inner = self.inner.lock().unwrap();
+        let lane = inner.lanes.get_mut(&chat_id)?;
+        let pos = lane.pending.iter().position(|x| x.id == id)?;
+        let message = lane.pending.remove(pos);
+        if lane.pending.is_empty() && !lane.starting {
+            inner.lanes.remove(&chat_id);
+        }
+        drop(inner);
+
+        self.notify(chat_id);
+        message
+    }
+
+    /// Drop every waiting message of the chat.
+    pub fn clear(&self, chat_id: i32) {
+        let removed = self.inner.lock().unwrap().lanes.remove(&chat_id);
+        if removed.is_some_and(|x| !x.pending.is_empty()) {
+            self.notify(chat_id);
+        }
+    }
+
+    /// Tell listeners a message taken by [`Queue::pop`] can't be answered,
+    /// it is gone from the queue.
+    pub fn refuse(&self, chat_id: i32, id: i32, reason: String) {
+        // no listener is not an error
+        let _ = self.changes.send(Change::Refused {
+            chat_id,
+            id,
+            reason,
+        });
+    }
+
+    /// Changes of the queues of all chats.
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.changes.subscribe()
+    }
+
+    fn notify(&self, chat_id: i32) {
+        // no listener is not an error
+        let _ = self.changes.send(Change::Updated(chat_id));
+    }
+}
+
+impl Default for Queue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> Queued {
+        Queued {
+            id: 0,
+            user_id: 1,
+            model_id: 1,
+            mode: ChatMode::Normal,
+            text: text.to_owned(),
+            files: Vec::new(),
+        }
+    }
+
+    fn queued(queue: &Queue, chat_id: i32, text: &str) -> i32 {
+        match queue.admit(chat_id, || true, message(text)) {
+            Admission::Queued(id) => id,
+            Admission::Idle(_) => panic!("expected the message to be queued"),
+        }
+    }
+
+    #[test]
+    fn test_idle_chat_starts_right_away() {
+        let queue = Queue::new();
+        let Admission::Idle(first) = queue.admit(1, || false, message("first")) else {
+            panic!("expected the message to start right away");
+        };
+        assert_eq!(first.text, "first");
+        assert!(queue.list(1).is_empty());
+
+        // the chat stays reserved until the first one started
+        let second = queued(&queue, 1, "second");
+        assert_ne!(first.id, second);
+        assert!(matches!(
+            queue.admit(1, || false, message("third")),
+            Admission::Queued(_)
+        ));
+        assert_eq!(queue.pop(1), None);
+
+        queue.started(1);
+        assert_eq!(queue.pop(1).unwrap().id, second);
+    }
+
+    #[test]
+    fn test_fifo() {
+        let queue = Queue::new();
+        let first = queued(&queue, 1, "first");
+        // waiting messages keep later ones queued once streaming ended
+        assert!(matches!(
+            queue.admit(1, || false, message("second")),
+            Admission::Queued(_)
+        ));
+        queued(&queue, 2, "other chat");
+
+        assert_eq!(queue.list(1).len(), 2);
+        let next = queue.pop(1).unwrap();
+        assert_eq!((next.id, next.text.as_str()), (first, "first"));
+
+        // still starting, so new messages wait too
+        assert!(matches!(
+            queue.admit(1, || false, message("third")),
+            Admission::Queued(_)
+        ));
+        queue.started(1);
+        assert_eq!(queue.pop(1).unwrap().text, "second");
+        queue.started(1);
+        assert_eq!(queue.pop(1).unwrap().text, "third");
+        queue.started(1);
+        assert_eq!(queue.pop(1), None);
+        assert_eq!(queue.list(2).len(), 1);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let queue = Queue::new();
+        let first = queued(&queue, 1, "first");
+        let second = queued(&queue, 1, "second");
+
+        assert_eq!(queue.cancel(1, first).unwrap().text, "first");
+        assert_eq!(queue.cancel(1, first), None);
+        assert_eq!(queue.cancel(2, second), None);
+        assert_eq!(
+            queue.list(1).iter().map(|x| x.id).collect::<Vec<_>>(),
+            [second]
+        );
+    }
+
+    #[test]
+    fn test_push_front() {
+        let queue = Queue::new();
+        queued(&queue, 1, "first");
+        queued(&queue, 1, "second");
+
+        let next = queue.pop(1).unwrap();
+        queue.push_front(1, next);
+        queue.started(1);
+        assert_eq!(queue.pop(1).unwrap().text, "first");
+    }
+
+    #[test]
+    fn test_notifies_changes() {
+        let queue = Queue::new();
+        let mut changes = queue.subscribe();
+        let id = queued(&queue, 3, "first");
+        queue.cancel(3, id);
+        queue.refuse(3, id, "model not found".to_owned());
+        assert_eq!(changes.try_recv(), Ok(Change::Updated(3)));
+        assert_eq!(changes.try_recv(), Ok(Change::Updated(3)));
+        assert_eq!(
+            changes.try_recv(),
+            Ok(Change::Refused {
+                chat_id: 3,
+                id,
+                reason: "model not found".to_owned()
+            })
+        );
+        assert!(changes.try_recv().is_err());
+    }
+}
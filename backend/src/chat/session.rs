@@ -7,7 +7,7 @@
 use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
-use ::entity::{chat, message, prelude::*, user};
+use ::entity::{chat, knowledge, message, prelude::*, user};
 use ::entity::file;
 use ::entity::model as entity_model;
 use futures_util::TryStreamExt;
@@ -18,10 +18,17 @@ use toml::de;
 
 use super::context::{Context, StreamEndReason};
 use super::converter;
+use super::history;
+use super::prompt::Customization;
 use super::token::Token;
-use crate::config::TITLE_GENERATION_TEMPERATURE;
+use crate::config::{SUMMARY_MAX_TOKENS, SUMMARY_TEMPERATURE, TITLE_GENERATION_TEMPERATURE};
 use crate::openrouter;
+use crate::utils::branch;
+use crate::utils::budget::{self, BudgetExceeded, BudgetStatus};
 use crate::utils::model::ModelChecker;
+use crate::utils::model_access::{self, ModelNotAllowed};
+use crate::utils::search;
+use crate::utils::timestamp;
 
 /// Loaded information about the model driving this session.
 pub struct SessionModel {
@@ -35,6 +42,57 @@ pub struct SessionUser {
     pub preference: UserPreference,
 }
 
+/// Returned when another reply already streams on the chat.
+#[derive(Debug, Clone, Copy)]
+pub struct ChatBusy;
+
+impl std::fmt::Display for ChatBusy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "another session is already streaming on this chat")
+    }
+}
+
+impl std::error::Error for ChatBusy {}
+
+/// Publisher of a chat shared by the replies of a comparison.
+pub(crate) type SharedPublisher = Arc<std::sync::Mutex<super::channel::Publisher<Token>>>;
+
+/// Where the tokens of a session are published.
+enum Outlet {
+    /// The session streams the chat alone
+    Chat(super::channel::Publisher<Token>),
+    /// One reply of a comparison, its tokens are tagged with the reply
+    Compare {
+        publisher: SharedPublisher,
+        model_id: i32,
+        message_id: i32,
+    },
+}
+
+impl Outlet {
+    fn publish(&mut self, token: Token) {
+        match self {
+            Outlet::Chat(publisher) => publisher.publish(token),
+            Outlet::Compare {
+                publisher,
+                model_id,
+                message_id,
+            } => publisher.lock().unwrap().publish(Token::Compare {
+                model_id: *model_id,
+                message_id: *message_id,
+                token: Box::new(token),
+            }),
+        }
+    }
+
+    fn wait_halt(&self) -> impl Future<Output = ()> + Send + 'static {
+        match self {
+            Outlet::Chat(publisher) => publisher.wait_halt(),
+            Outlet::Compare { publisher, .. } => publisher.lock().unwrap().wait_halt(),
+        }
+    }
+}
+
 /// A single completion run.  Holds mutable message state, a reference
 /// back to the shared [`Context`], and a streaming publisher.
 pub struct CompletionSession {
@@ -43,12 +101,25 @@ pub struct CompletionSession {
     pub model: SessionModel,
     pub chat: chat::Model,
     pub message: message::Model,
+    /// Prompt of the assistant the chat was created from
+    pub assistant_prompt: Option<String>,
     pub(super) history: Vec<message::Model>,
+    /// Leading messages of `history` replaced by `summary` upstream
+    history_skip: usize,
+    summary: Option<String>,
+    /// Memories of the user related to the latest message
+    memories: Vec<String>,
+    /// Set once summary and memories are in place
+    prepared: bool,
+    /// Knowledge bases of the user, searched through the knowledge tool
+    pub knowledge: Vec<knowledge::Model>,
     file_mime_types: Vec<(i32, Option<String>)>,
     cost: f32,
     token_count: i32,
-    publisher: super::channel::Publisher<Token>,
+    publisher: Outlet,
     mode: protocol::ModeKind,
+    /// Set when the soft budget threshold is reached, sent once on start
+    pub(super) budget_warning: Option<BudgetStatus>,
 }
 
 impl CompletionSession {
@@ -123,10 +194,37 @@ impl CompletionSession {
         chat_id: i32,
         model_id: i32,
         mode: protocol::ModeKind,
+    ) -> Result<Self> {
+        Self::load(ctx, user_id, chat_id, model_id, mode, None).await
+    }
+
+    /// Like [`CompletionSession::new`], for one reply of a comparison
+    /// streaming through the `shared` publisher of the chat.
+    ///
+    /// The reply is not made the active branch, so the replies of all
+    /// compared models answer the same user message.
+    pub async fn compare(
+        ctx: Arc<Context>,
+        user_id: i32,
+        chat_id: i32,
+        model_id: i32,
+        mode: protocol::ModeKind,
+        shared: SharedPublisher,
+    ) -> Result<Self> {
+        Self::load(ctx, user_id, chat_id, model_id, mode, Some(shared)).await
+    }
+
+    async fn load(
+        ctx: Arc<Context>,
+        user_id: i32,
+        chat_id: i32,
+        model_id: i32,
+        mode: protocol::ModeKind,
+        shared: Option<SharedPublisher>,
     ) -> Result<Self> {
         let db = &ctx.db;
 
-        let (user, chat, model_entity) = tokio::try_join!(
+        let (user, mut chat, model_entity) = tokio::try_join!(
             async {
                 user::Entity::find_by_id(user_id)
                     .one(db)
@@ -147,26 +245,83 @@ impl CompletionSession {
             },
         )?;
 
+        if !model_access::load(db, user.id, user.role)
+            .await?
+            .permits(model_id)
+        {
+            return Err(ModelNotAllowed.into());
+        }
+
         let model_config = <ModelConfig as ModelChecker>::from_toml(&model_entity.config)?;
 
+        // assistants made private later stop applying to chats of others
+        let assistant_prompt = match chat.assistant_id {
+            Some(id) => Assistant::find_by_id(id)
+                .one(db)
+                .await?
+                .filter(|x| x.shared || x.owner_id == user_id)
+                .map(|x| x.prompt),
+            None => None,
+        };
+
+        // refuse before anything is sent upstream
+        let budget = budget::status(db, user_id, timestamp::now()).await?;
+        if let Some(status) = budget.as_ref().filter(|x| x.exceeded()) {
+            return Err(BudgetExceeded {
+                spent: status.spent,
+                limit: status.limit,
+            }
+            .into());
+        }
+        let budget_warning = budget.filter(|x| x.warning());
+
+        // only the active branch is sent upstream
+        let nodes = branch::nodes(db, chat_id).await?;
+        let path = branch::leaf(&chat, &nodes)
+            .map(|leaf| branch::active_path(&nodes, leaf))
+            .unwrap_or_default();
+        let parent_id = path.last().copied();
         let history = Message::find()
-            .filter(message::Column::ChatId.eq(chat_id))
+            .filter(message::Column::Id.is_in(path))
             .order_by_asc(message::Column::Id)
             .all(db)
             .await?;
 
         let file_mime_types = Self::load_history_file_mime_types(db, &history).await?;
 
+        let knowledge = Knowledge::find()
+            .filter(knowledge::Column::OwnerId.eq(user_id))
+            .order_by_asc(knowledge::Column::Name)
+            .all(db)
+            .await?;
+
+        // claim the chat before touching it, a reply may still be streaming
+        let mut publisher = match shared {
+            Some(publisher) => Outlet::Compare {
+                publisher,
+                model_id,
+                message_id: 0,
+            },
+            None => Outlet::Chat(ctx.channel.clone().publish(chat_id).ok_or(ChatBusy)?),
+        };
+
         // Create a placeholder assistant message that strategies will populate.
+        let created_at = timestamp::now();
         let new_msg = message::ActiveModel {
             chat_id: Set(chat_id),
             price: Set(0.0),
             token_count: Set(0),
             inner: Set(MessageInner::default()),
+            created_at: Set(created_at),
+            parent_id: Set(parent_id),
             ..Default::default()
         };
         let insert_result = message::Entity::insert(new_msg).exec(db).await?;
         let msg_id = insert_result.last_insert_id;
+        if matches!(publisher, Outlet::Chat(_)) {
+            branch::set_leaf(db, chat_id, Some(msg_id)).await?;
+            chat.leaf_id = Some(msg_id);
+        }
 
         let message = message::Model {
             id: msg_id,
@@ -174,13 +329,13 @@ impl CompletionSession {
             price: 0.0,
             token_count: 0,
             inner: MessageInner::default(),
+            created_at,
+            parent_id,
         };
 
-        let publisher = ctx
-            .channel
-            .clone()
-            .publish(chat_id)
-            .context("another session is already streaming on this chat")?;
+        if let Outlet::Compare { message_id, .. } = &mut publisher {
+            *message_id = msg_id;
+        }
 
         log::debug!(
             "session created: chat_id={}, user_id={}, model_id={}, msg_id={}",
@@ -190,7 +345,7 @@ impl CompletionSession {
             msg_id
         );
 
-        Ok(Self {
+        let session = Self {
             ctx,
             user: SessionUser {
                 id: user_id,
@@ -202,13 +357,192 @@ impl CompletionSession {
             },
             chat,
             message,
+            assistant_prompt,
             history,
+            history_skip: 0,
+            summary: None,
+            memories: Vec::new(),
+            prepared: false,
+            knowledge,
             file_mime_types,
             cost: 0.0,
             token_count: 0,
             publisher,
             mode,
-        })
+            budget_warning,
+        };
+
+        Ok(session)
+    }
+
+    /// Summarizes the history and recalls memories, which both call
+    /// upstream. Left out of loading so requests starting a reply don't wait
+    /// for it; must run before [`CompletionSession::assemble_messages`].
+    pub async fn prepare(&mut self) {
+        if self.prepared {
+            return;
+        }
+        self.compact_history().await;
+        let (memories, cost) = self.recall_memories().await;
+        self.memories = memories;
+        self.update_usage(cost, 0);
+        self.prepared = true;
+    }
+
+    /// Prepares the replies of a comparison, which answer the same history.
+    ///
+    /// The history is summarized once, for the smallest known context window
+    /// among the models, and the result is shared by every reply.
+    pub async fn prepare_all(sessions: &mut [CompletionSession]) {
+        let mut lengths = Vec::with_capacity(sessions.len());
+        for session in sessions.iter() {
+            lengths.push(session.context_length().await);
+        }
+        let smallest = lengths
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| Some((i, (*x)?)))
+            .min_by_key(|(_, x)| *x)
+            .map_or(0, |(i, _)| i);
+        let Some(source) = sessions.get_mut(smallest) else {
+            return;
+        };
+        source.prepare().await;
+
+        let chat_summary = source.chat.summary.clone();
+        let summary = source.summary.clone();
+        let history_skip = source.history_skip;
+        let memories = source.memories.clone();
+        for session in sessions.iter_mut() {
+            session.chat.summary.clone_from(&chat_summary);
+            session.summary.clone_from(&summary);
+            session.history_skip = history_skip;
+            session.memories.clone_from(&memories);
+            session.prepared = true;
+        }
+    }
+
+    async fn context_length(&self) -> Option<u32> {
+        match self.model.config.context_length {
+            Some(x) => Some(x),
+            None => {
+                self.ctx
+                    .openrouter
+                    .get_context_length(&self.model.config.model_id)
+                    .await
+            }
+        }
+    }
+
+    /// Fold the oldest turns into the summary of the chat when the history
+    /// would not fit the context window of the model.
+    ///
+    /// The stored summary is reused as long as it covers a prefix of the
+    /// active branch. Should summarizing fail, the oldest turns are dropped
+    /// instead.
+    async fn compact_history(&mut self) {
+        let Some(context_length) = self.context_length().await else {
+            return;
+        };
+
+        // a summary of another branch is of no use
+        let stored = self.chat.summary.clone().and_then(|summary| {
+            let pos = self.history.iter().position(|x| x.id == summary.until)?;
+            Some((pos + 1, summary.text))
+        });
+        let (start, summary) = match stored {
+            Some((start, text)) => (start, Some(text)),
+            None => (0, None),
+        };
+        self.history_skip = start;
+        self.summary = summary;
+
+        let summary_tokens = self
+            .summary
+            .as_deref()
+            .map_or(0, history::estimate_text_tokens);
+        let Some(point) = history::fold_point(
+            &self.history[start..],
+            summary_tokens,
+            history::budget(context_length),
+        ) else {
+            return;
+        };
+        let end = start + point;
+
+        let folded = self.history[start..end].to_vec();
+        match self.summarize(&folded).await {
+            Ok(text) => {
+                let summary = ChatSummary {
+                    until: self.history[end - 1].id,
+                    text,
+                };
+                let chat_active = chat::ActiveModel {
+                    id: Set(self.chat.id),
+                    summary: Set(Some(summary.clone())),
+                    ..Default::default()
+                };
+                if let Err(e) = chat::Entity::update(chat_active).exec(&self.ctx.db).await {
+                    log::warn!("cannot store summary of chat {}: {}", self.chat.id, e);
+                }
+                self.summary = Some(summary.text.clone());
+                self.chat.summary = Some(summary);
+            }
+            Err(e) => log::warn!("cannot summarize chat {}: {}", self.chat.id, e),
+        }
+        self.history_skip = end;
+    }
+
+    /// Memories related to the latest user message, and what recalling
+    /// them cost.
+    async fn recall_memories(&self) -> (Vec<String>, f32) {
+        let Some(query) = self.latest_user_message() else {
+            return (Vec::new(), 0.0);
+        };
+        match self
+            .ctx
+            .memory
+            .recall(&self.ctx.db, self.user.id, query)
+            .await
+        {
+            Ok((memories, cost)) => (memories, cost as f32),
+            Err(e) => {
+                log::warn!("cannot recall memories of user {}: {}", self.user.id, e);
+                (Vec::new(), 0.0)
+            }
+        }
+    }
+
+    async fn summarize(&mut self, messages: &[message::Model]) -> Result<String> {
+        let system = self.ctx.prompt.render_summary(self.locale())?;
+        let transcript = history::transcript(self.summary.as_deref(), messages);
+
+        let mut model = self.openrouter_model();
+        if let Some(ref task_model_id) = self.model.config.task_model_id {
+            model.id.clone_from(task_model_id);
+        }
+        let option = openrouter::CompletionOption::builder()
+            .max_tokens(SUMMARY_MAX_TOKENS)
+            .temperature(SUMMARY_TEMPERATURE)
+            .build();
+
+        let result = self
+            .ctx
+            .openrouter
+            .complete(
+                vec![
+                    openrouter::Message::System(system),
+                    openrouter::Message::User(transcript),
+                ],
+                model,
+                option,
+            )
+            .await?;
+        self.update_usage(result.price as f32, result.token as i32);
+
+        let summary = result.response.trim().to_owned();
+        anyhow::ensure!(!summary.is_empty(), "empty summary");
+        Ok(summary)
     }
 
     // ------------------------------------------------------------------
@@ -228,6 +562,11 @@ impl CompletionSession {
         let (image_model_id, video_model_id) = self.media_model_ids();
         let (image_model_supported_parameters, video_model_supported_parameters) =
             self.media_model_prompt_parameters();
+        let instructions = self.custom_instructions();
+        let custom = Customization {
+            assistant_prompt: self.assistant_prompt.as_deref(),
+            instructions: &instructions,
+        };
 
         // 1. System prompt
         let system_prompt = match mode {
@@ -237,6 +576,7 @@ impl CompletionSession {
                 model_id,
                 "",
                 &model_supported_parameters,
+                &custom,
             )?,
             ModeKind::Search => ctx.prompt.render_search(
                 locale,
@@ -244,6 +584,7 @@ impl CompletionSession {
                 model_id,
                 "",
                 &model_supported_parameters,
+                &custom,
             )?,
             ModeKind::Media => ctx.prompt.render_media(
                 locale,
@@ -255,6 +596,7 @@ impl CompletionSession {
                 video_model_id,
                 &image_model_supported_parameters,
                 &video_model_supported_parameters,
+                &custom,
             )?,
             #[cfg(feature = "deep-research")]
             ModeKind::Research => ctx.prompt.render_coordinator(locale)?,
@@ -265,14 +607,22 @@ impl CompletionSession {
                 model_id,
                 "",
                 &model_supported_parameters,
+                &custom,
             )?,
         };
 
         let mut messages = vec![openrouter::Message::System(system_prompt)];
 
-        // 2. Previous messages (from DB → openrouter format)
-        let history_msgs =
-            converter::history_to_openrouter(&self.history, &ctx.blob, &self.file_mime_types);
+        // 2. Previous messages (from DB → openrouter format), the oldest
+        // ones may be replaced by their summary
+        if let Some(summary) = &self.summary {
+            messages.push(openrouter::Message::User(history::summary_message(summary)));
+        }
+        let history_msgs = converter::history_to_openrouter(
+            &self.history[self.history_skip..],
+            &ctx.blob,
+            &self.file_mime_types,
+        );
         messages.extend(history_msgs);
 
         // 3. Context injection as USER message BEFORE the last user query
@@ -294,6 +644,7 @@ impl CompletionSession {
             &time_str,
             self.chat.title.as_deref(),
             contain_dollar_sign,
+            &self.memories,
         )?;
 
         if !context_prompt.trim().is_empty() {
@@ -323,6 +674,16 @@ impl CompletionSession {
         self.user.preference.locale.as_deref().unwrap_or("en-US")
     }
 
+    /// Instructions set on the chat, otherwise those of the user.
+    pub fn custom_instructions(&self) -> CustomInstructions {
+        self.chat
+            .custom_instructions
+            .clone()
+            .or_else(|| self.user.preference.custom_instructions.clone())
+            .unwrap_or_default()
+            .normalized()
+    }
+
     pub fn latest_user_message(&self) -> Option<&str> {
         self.history.iter().rev().find_map(|m| match &m.inner {
             MessageInner::User { text, .. } => Some(text.as_str()),
@@ -447,6 +808,11 @@ impl CompletionSession {
         self.token_count += tokens;
     }
 
+    /// Cost and tokens spent so far.
+    pub fn usage(&self) -> (f32, i32) {
+        (self.cost, self.token_count)
+    }
+
     /// Drains a mapped OpenRouter token stream, publishing each token
     /// and returning `Halt` if a stop was requested.
     pub async fn put_stream<S>(&mut self, stream: S) -> Result<StreamEndReason>
@@ -491,11 +857,7 @@ impl CompletionSession {
             self.message.inner.add_reasoning_detail(rd.clone());
         }
         if !result.citations.is_empty() {
-            self.message
-                .inner
-                .add_url_citation(result.citations.clone());
-            self.publisher
-                .publish(Token::UrlCitation(result.citations.clone()));
+            self.add_citations(result.citations.clone());
         }
         for img in &result.image {
             if let Some(id) = self.store_image(img).await {
@@ -690,6 +1052,12 @@ impl CompletionSession {
         }
     }
 
+    /// Records citations on the message and streams them.
+    pub fn add_citations(&mut self, citations: Vec<UrlCitation>) {
+        self.message.inner.add_url_citation(citations.clone());
+        self.publisher.publish(Token::UrlCitation(citations));
+    }
+
     pub fn add_chunk(&mut self, chunk: AssistantChunk) {
         if let Some(existing) = self.message.inner.as_assistant() {
             existing.push(chunk);
@@ -713,6 +1081,12 @@ impl CompletionSession {
         active.inner = Set(self.message.inner.clone());
         message::Entity::update(active).exec(db).await?;
 
+        if let Err(e) = search::index(db, msg_id, self.chat.id, &self.message.inner).await {
+            log::warn!("cannot index message {}: {}", msg_id, e);
+        }
+
+        budget::record(db, self.user.id, self.cost as f64, timestamp::now()).await?;
+
         // Emit Complete token
         self.publisher.publish(Token::Complete {
             message_id: self.message.id,
@@ -1,40 +1,138 @@
-//! Normal chat mode – no tools, single completion round.
+//! Normal chat mode – remember and knowledge search tools, tool-call loop
+//! until the model answers without calling them.
 
 use anyhow::Result;
+use protocol::AssistantChunk;
 use tokio_stream::StreamExt;
 
 use crate::chat::context::StreamEndReason;
-use crate::chat::converter::openrouter_to_buffer_token;
+use crate::chat::converter::{openrouter_stream_to_assitant_chunk, openrouter_to_buffer_token};
 use crate::chat::session::CompletionSession;
+use crate::chat::token::Token;
+use crate::chat::tools::{knowledge, memory};
 use crate::chat::Context;
-use crate::openrouter;
+use crate::openrouter::{self, StreamWithOrderedTokens};
 
 pub async fn execute(ctx: &Context, session: &mut CompletionSession) -> Result<bool> {
-    let messages = session.assemble_messages(ctx, openrouter::CompletionOption::default())?;
-
-    let model = session.openrouter_model();
+    let mut messages = session.assemble_messages(ctx, openrouter::CompletionOption::default())?;
 
     let option = openrouter::CompletionOption::builder()
         .session_id(session.chat.id.to_string())
         .image_generation(true)
+        .tools(&ctx.tools.for_normal_mode())
+        .tools(&ctx.tools.for_knowledge(&session.knowledge))
         .build();
 
-    let mut stream: openrouter::StreamCompletion =
-        ctx.openrouter.stream(model, messages, option).await?;
+    loop {
+        let model = session.openrouter_model();
+        let stream: openrouter::StreamCompletion = ctx
+            .openrouter
+            .stream(model, messages.clone(), option.clone())
+            .await?;
+
+        // Wrap with ordered tokens wrapper to filter out tool tokens during streaming
+        let mut ordered_stream = StreamWithOrderedTokens::new(stream);
+
+        let halt = session
+            .put_stream((&mut ordered_stream).map(|resp| resp.map(openrouter_to_buffer_token)))
+            .await?;
+
+        let stream = ordered_stream.into_inner();
+        let mut result = stream.get_result();
+        session.update_usage(result.usage.cost as f32, result.usage.token as i32);
+
+        let tool_calls = std::mem::take(&mut result.toolcalls);
+        let assistant_text = result.get_text();
+
+        // Convert stream responses to assistant chunks and persist them
+        let chunks = openrouter_stream_to_assitant_chunk(&result.responses);
+        session.extend_chunks(chunks);
+
+        // Persist annotations / reasoning details / images
+        session.apply_stream_result(&result).await;
+
+        if matches!(halt, StreamEndReason::Halt) {
+            return Ok(true);
+        }
+        if tool_calls.is_empty() {
+            break;
+        }
+
+        for tc in &tool_calls {
+            session.add_token(Token::ToolCall {
+                name: tc.name.clone(),
+                arg: tc.args.clone(),
+            });
+        }
+
+        // Re-add assistant turn so the model sees its own tool calls
+        messages.push(openrouter::Message::Assistant {
+            content: assistant_text,
+            annotations: None,
+            reasoning_details: None,
+            files: Vec::new(),
+        });
+
+        for tc in tool_calls {
+            messages.push(openrouter::Message::ToolCall(openrouter::MessageToolCall {
+                id: tc.id.clone(),
+                name: tc.name.clone(),
+                arguments: tc.args.clone(),
+            }));
 
-    let halt = session
-        .put_stream((&mut stream).map(|resp| resp.map(openrouter_to_buffer_token)))
-        .await?;
+            session.add_chunk(AssistantChunk::ToolCall {
+                id: tc.id.clone(),
+                name: tc.name.clone(),
+                arg: tc.args.clone(),
+            });
 
-    let result = stream.get_result();
-    session.update_usage(result.usage.cost as f32, result.usage.token as i32);
+            let mut citations = Vec::new();
+            let tool_result = match tc.name.as_str() {
+                knowledge::KNOWLEDGE_SEARCH_TOOL_NAME => {
+                    let (result, found, cost) =
+                        knowledge::search(&ctx.embedder, &ctx.db, &session.knowledge, &tc.args)
+                            .await;
+                    session.update_usage(cost, 0);
+                    citations = found;
+                    result
+                }
+                memory::REMEMBER_TOOL_NAME => {
+                    let (result, cost) = memory::remember(
+                        &ctx.memory,
+                        &ctx.db,
+                        session.user.id,
+                        session.chat.id,
+                        &tc.args,
+                    )
+                    .await;
+                    session.update_usage(cost, 0);
+                    result
+                }
+                name => format!("Unknown tool: {}", name),
+            };
 
-    // Convert stream responses to assistant chunks and persist them
-    let chunks = crate::chat::converter::openrouter_stream_to_assitant_chunk(&result.responses);
-    session.extend_chunks(chunks);
+            messages.push(openrouter::Message::ToolResult(
+                openrouter::MessageToolResult {
+                    id: tc.id.clone(),
+                    content: tool_result.clone(),
+                    files: Vec::new(),
+                },
+            ));
 
-    // Persist annotations / reasoning details / images
-    session.apply_stream_result(&result).await;
+            session.add_token(Token::ToolResult {
+                content: tool_result.clone(),
+                files: Vec::new(),
+            });
+            session.add_chunk(AssistantChunk::ToolResult {
+                id: tc.id,
+                response: tool_result,
+                files: Vec::new(),
+            });
+            if !citations.is_empty() {
+                session.add_citations(citations);
+            }
+        }
+    }
 
-    Ok(matches!(halt, StreamEndReason::Halt))
+    Ok(false)
 }
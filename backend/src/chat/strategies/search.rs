@@ -10,6 +10,7 @@ use crate::chat::context::StreamEndReason;
 use crate::chat::converter::{openrouter_stream_to_assitant_chunk, openrouter_to_buffer_token};
 use crate::chat::session::CompletionSession;
 use crate::chat::token::Token;
+use crate::chat::tools::{knowledge, memory};
 use crate::chat::Context;
 use crate::openrouter::{self, StreamWithOrderedTokens};
 
@@ -17,8 +18,10 @@ pub async fn execute(ctx: &Context, session: &mut CompletionSession) -> Result<b
     let tools = ctx.tools.for_search_mode();
 
     let option = {
-        let builder =
-            openrouter::CompletionOption::builder().session_id(session.chat.id.to_string());
+        let builder = openrouter::CompletionOption::builder()
+            .session_id(session.chat.id.to_string())
+            .tools(&ctx.tools.for_memory())
+            .tools(&ctx.tools.for_knowledge(&session.knowledge));
         match ctx.openrouter.is_custom_api() {
             true => builder.tools(&tools),
             false => builder.web_search(true),
@@ -95,7 +98,30 @@ pub async fn execute(ctx: &Context, session: &mut CompletionSession) -> Result<b
                 arg: tc.args.clone(),
             });
 
-            let tool_result = execute_tool(ctx, &tc.name, &tc.args).await;
+            let mut citations = Vec::new();
+            let tool_result = match tc.name.as_str() {
+                knowledge::KNOWLEDGE_SEARCH_TOOL_NAME => {
+                    let (result, found, cost) =
+                        knowledge::search(&ctx.embedder, &ctx.db, &session.knowledge, &tc.args)
+                            .await;
+                    session.update_usage(cost, 0);
+                    citations = found;
+                    result
+                }
+                memory::REMEMBER_TOOL_NAME => {
+                    let (result, cost) = memory::remember(
+                        &ctx.memory,
+                        &ctx.db,
+                        session.user.id,
+                        session.chat.id,
+                        &tc.args,
+                    )
+                    .await;
+                    session.update_usage(cost, 0);
+                    result
+                }
+                _ => execute_tool(ctx, &tc.name, &tc.args).await,
+            };
 
             messages.push(openrouter::Message::ToolResult(
                 openrouter::MessageToolResult {
@@ -114,6 +140,9 @@ pub async fn execute(ctx: &Context, session: &mut CompletionSession) -> Result<b
                 response: tool_result,
                 files: Vec::new(),
             });
+            if !citations.is_empty() {
+                session.add_citations(citations);
+            }
         }
     }
 
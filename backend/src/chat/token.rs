@@ -57,6 +57,16 @@ pub enum Token {
         id: i32,
         user_msg_id: i32,
     },
+    BudgetWarning {
+        spent: f64,
+        limit: f64,
+    },
+    /// Token of one reply of a comparison, which share the stream of the chat
+    Compare {
+        model_id: i32,
+        message_id: i32,
+        token: Box<Token>,
+    },
 }
 
 impl Mergeable for Token {
@@ -92,6 +102,20 @@ impl Mergeable for Token {
                 s1.push_str(&s2);
                 None
             }
+            (
+                Token::Compare {
+                    message_id, token, ..
+                },
+                Token::Compare {
+                    model_id,
+                    message_id: other_id,
+                    token: other,
+                },
+            ) if *message_id == other_id => token.merge(*other).map(|rest| Token::Compare {
+                model_id,
+                message_id: other_id,
+                token: Box::new(rest),
+            }),
             (_, other) => Some(other),
         }
     }
@@ -105,6 +129,7 @@ impl Mergeable for Token {
             | Token::DeepReport(s)
             | Token::Error(s)
             | Token::DeepPlan(s) => s.len(),
+            Token::Compare { token, .. } => token.len(),
             Token::ToolResult { .. }
             | Token::Empty
             | Token::DeepStepStart(_)
@@ -115,7 +140,8 @@ impl Mergeable for Token {
             | Token::ToolCall { .. }
             | Token::DeepStepToolCall { .. }
             | Token::Image(_)
-            | Token::UrlCitation(_) => 1,
+            | Token::UrlCitation(_)
+            | Token::BudgetWarning { .. } => 1,
         }
     }
 
@@ -132,6 +158,15 @@ impl Mergeable for Token {
             Token::DeepReport(s) => s.get(r).map(|slice| Token::DeepReport(slice.to_string())),
             Token::Error(s) => s.get(r).map(|slice| Token::Error(slice.to_string())),
             Token::DeepPlan(s) => s.get(r).map(|slice| Token::DeepPlan(slice.to_string())),
+            Token::Compare {
+                model_id,
+                message_id,
+                token,
+            } => token.slice(r).map(|token| Token::Compare {
+                model_id: *model_id,
+                message_id: *message_id,
+                token: Box::new(token),
+            }),
             x if r.start == 0 => Some(x.clone()),
             _ => None,
         }
@@ -231,6 +266,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_compare_per_reply() {
+        let compare = |message_id, text: &str| Token::Compare {
+            model_id: message_id * 10,
+            message_id,
+            token: Box::new(Token::Assistant(text.to_owned())),
+        };
+
+        let mut token = compare(1, "Hel");
+        assert!(token.merge(compare(1, "lo")).is_none());
+        assert!(token.merge(compare(2, "Hi")).is_some());
+        assert_eq!(token.len(), 5);
+
+        match token.slice(1..5) {
+            Some(Token::Compare {
+                message_id: 1,
+                token,
+                ..
+            }) => assert!(matches!(*token, Token::Assistant(s) if s == "ello")),
+            _ => panic!("Expected Compare token"),
+        }
+    }
+
     #[test]
     fn test_regression_bug_report() {
         // Regression test for the exact bug in the report:
@@ -0,0 +1,109 @@
+use entity::knowledge;
+use protocol::UrlCitation;
+use sea_orm::DatabaseConnection;
+
+use crate::utils::embedding::Embedder;
+use crate::utils::knowledge as store;
+
+pub(crate) const KNOWLEDGE_SEARCH_TOOL_NAME: &str = "knowledge_search";
+
+pub(crate) fn get_knowledge_search_tool_def(
+    collections: &[knowledge::Model],
+) -> crate::openrouter::Tool {
+    let names = collections
+        .iter()
+        .map(|x| x.name.clone())
+        .collect::<Vec<_>>();
+    crate::openrouter::Tool {
+        name: KNOWLEDGE_SEARCH_TOOL_NAME.to_string(),
+        description: format!(
+            "Search the documents the user added to their knowledge bases ({}). Use it \
+             whenever the question may be answered by those documents, and cite the \
+             returned passages by their number.",
+            names.join(", ")
+        ),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to look for, phrased like the passage that would answer it"
+                },
+                "collection": {
+                    "type": "string",
+                    "enum": names,
+                    "description": "Knowledge base to search, all of them when omitted"
+                }
+            },
+            "required": ["query"]
+        }),
+    }
+}
+
+/// Run the knowledge search tool over the collections of the user,
+/// returning the tool result, a citation per passage found and what
+/// embedding the query cost.
+pub(crate) async fn search(
+    embedder: &Embedder,
+    db: &DatabaseConnection,
+    collections: &[knowledge::Model],
+    args: &str,
+) -> (String, Vec<UrlCitation>, f32) {
+    #[derive(serde::Deserialize)]
+    struct Args {
+        query: String,
+        collection: Option<String>,
+    }
+    let Ok(args) = serde_json::from_str::<Args>(args) else {
+        return (
+            format!("Invalid arguments for {}", KNOWLEDGE_SEARCH_TOOL_NAME),
+            Vec::new(),
+            0.0,
+        );
+    };
+
+    let ids = collections
+        .iter()
+        .filter(|x| args.collection.as_ref().is_none_or(|name| *name == x.name))
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        return ("Unknown knowledge base.".to_string(), Vec::new(), 0.0);
+    }
+
+    let (hits, cost) = match store::search(db, embedder, &ids, &args.query).await {
+        Ok((hits, cost)) => (hits, cost as f32),
+        Err(e) => {
+            log::warn!("Knowledge search error: {}", e);
+            return (format!("Error: {}", e), Vec::new(), 0.0);
+        }
+    };
+    if hits.is_empty() {
+        return ("No matching passages found.".to_string(), Vec::new(), cost);
+    }
+
+    let mut out = String::new();
+    let mut citations = Vec::with_capacity(hits.len());
+    for (i, hit) in hits.into_iter().enumerate() {
+        let source = match hit.page {
+            Some(page) => format!("{}, page {}", hit.file, page),
+            None => hit.file,
+        };
+        out.push_str(&format!("[{}] {}\n{}\n\n", i + 1, source, hit.content));
+
+        let url = match hit.page {
+            Some(page) => format!("knowledge:{}#page={}", hit.file_id, page),
+            None => format!("knowledge:{}", hit.file_id),
+        };
+        citations.push(UrlCitation {
+            url,
+            title: Some(source),
+            content: Some(hit.content),
+            start_index: None,
+            end_index: None,
+            favicon: None,
+        });
+    }
+
+    (out, citations, cost)
+}
@@ -0,0 +1,65 @@
+use sea_orm::DatabaseConnection;
+
+use crate::config::MAX_MEMORY_LEN;
+use crate::utils::memory::{self, MemoryStore};
+
+pub(crate) const REMEMBER_TOOL_NAME: &str = "remember_tool";
+
+pub(crate) fn get_remember_tool_def() -> crate::openrouter::Tool {
+    crate::openrouter::Tool {
+        name: REMEMBER_TOOL_NAME.to_string(),
+        description: "Save a lasting fact about the user, such as a preference, their \
+                      background or an ongoing project, so it can be recalled in later chats. \
+                      Only use it when the user asks you to remember something or shares \
+                      something clearly useful later."
+            .to_string(),
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "The fact to remember, as one short self-contained sentence"
+                }
+            },
+            "required": ["content"]
+        }),
+    }
+}
+
+/// Run the remember tool for the user, returning the tool result and what
+/// embedding the memory cost.
+pub(crate) async fn remember(
+    store: &MemoryStore,
+    db: &DatabaseConnection,
+    user_id: i32,
+    chat_id: i32,
+    args: &str,
+) -> (String, f32) {
+    #[derive(serde::Deserialize)]
+    struct Args {
+        content: String,
+    }
+    let Ok(args) = serde_json::from_str::<Args>(args) else {
+        return (format!("Invalid arguments for {}", REMEMBER_TOOL_NAME), 0.0);
+    };
+    if !memory::valid(&args.content) {
+        return (
+            format!(
+                "Memory must be non-empty and at most {} characters.",
+                MAX_MEMORY_LEN
+            ),
+            0.0,
+        );
+    }
+
+    match store
+        .remember(db, user_id, Some(chat_id), &args.content)
+        .await
+    {
+        Ok((_, cost)) => ("Remembered.".to_string(), cost as f32),
+        Err(e) => {
+            log::warn!("cannot store memory of user {}: {}", user_id, e);
+            (format!("Error: {}", e), 0.0)
+        }
+    }
+}
@@ -5,18 +5,22 @@ use std::sync::Arc;
 // TODO: make duckduckgo(web_search) tool stateful(reuse same reqwest client
 // with flyweight)
 pub(crate) mod crawl;
+pub(crate) mod knowledge;
 #[cfg(feature = "deep-research")]
 pub(crate) mod lua;
 pub(crate) mod media;
+pub(crate) mod memory;
 #[allow(unused)]
 #[cfg(feature = "deep-research")]
 pub(crate) mod runner;
 pub(crate) mod web_search;
 
 pub(crate) use crawl::{CrawlTool, get_crawl_tool_def};
+pub(crate) use knowledge::get_knowledge_search_tool_def;
 #[cfg(feature = "deep-research")]
 pub(crate) use lua::{LuaReplTool, get_lua_repl_def};
 pub(crate) use media::{get_generate_image_tool_def, get_generate_video_tool_def};
+pub(crate) use memory::get_remember_tool_def;
 pub(crate) use web_search::{WebSearchTool, get_web_search_tool_def};
 
 /// Collection of all available tools in llumen.
@@ -55,10 +59,27 @@ impl Tools {
 
     /// Returns tool definitions for normal mode.
     ///
-    /// Currently: empty (normal mode has no tools)
-    #[allow(dead_code)]
+    /// Includes: remember
     pub fn for_normal_mode(&self) -> Vec<crate::openrouter::Tool> {
-        vec![]
+        self.for_memory()
+    }
+
+    /// Returns tool definitions for user memory, offered alongside the tools
+    /// of normal and search mode.
+    pub fn for_memory(&self) -> Vec<crate::openrouter::Tool> {
+        vec![get_remember_tool_def()]
+    }
+
+    /// Returns tool definitions for the knowledge bases of the user, offered
+    /// alongside the tools of normal and search mode when there are any.
+    pub fn for_knowledge(
+        &self,
+        collections: &[entity::knowledge::Model],
+    ) -> Vec<crate::openrouter::Tool> {
+        if collections.is_empty() {
+            return vec![];
+        }
+        vec![get_knowledge_search_tool_def(collections)]
     }
 
     /// Returns tool definitions for deep research mode.
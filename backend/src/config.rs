@@ -25,6 +25,86 @@ pub const UNBOUNDED_FILE_EXPIRATION_SECS: u32 = 3600;
 // max size in bytes for re-encoded images
 pub const MAX_REENCODE_IMAGE_SIZE: usize = 32 * 1024 * 1024;
 
-// Rate limit for /auth/login: max 20 attempts per 5 minutes (global)
+// Rate limit for /auth/login: max 20 attempts per 5 minutes (per client address)
 pub const LOGIN_RATE_LIMIT_MAX: u64 = 20;
 pub const LOGIN_RATE_LIMIT_WINDOW_SECS: u64 = 300;
+
+// Failed logins allowed before each attempt has to wait twice as long as the last
+pub const LOGIN_BACKOFF_FREE_ATTEMPTS: u32 = 3;
+
+// Upper bound of the login backoff delay: 5 minutes
+pub const LOGIN_BACKOFF_MAX_SECS: u64 = 300;
+
+// Consecutive failures that lock an account, and for how long: 15 minutes
+pub const LOGIN_LOCKOUT_THRESHOLD: u32 = 10;
+pub const LOGIN_LOCKOUT_SECS: u64 = 60 * 15;
+
+// Failure counters are forgotten after this long without failures: 1 hour
+pub const LOGIN_FAILURE_RESET_SECS: u64 = 60 * 60;
+
+// Minimum interval between last_used_at updates of an API token: 1 minute
+pub const API_TOKEN_TOUCH_INTERVAL_SECS: i64 = 60;
+
+// How long a login may wait for its second factor: 5 minutes
+pub const TOTP_CHALLENGE_EXPIRATION_SECS: u64 = 60 * 5;
+
+// Number of TOTP recovery codes handed out on enrollment
+pub const TOTP_RECOVERY_CODE_COUNT: usize = 10;
+
+// How long an OIDC login may take between redirect and callback: 10 minutes
+pub const OIDC_STATE_TTL_SECS: u64 = 600;
+
+// OIDC logins started but not finished yet, the oldest is dropped beyond this
+pub const OIDC_MAX_PENDING: usize = 1024;
+
+// Allowed clock difference when checking OIDC ID token expiration
+pub const OIDC_CLOCK_SKEW_SECS: i64 = 60;
+
+// Maximum length in characters of each custom instruction field
+pub const MAX_CUSTOM_INSTRUCTIONS_LEN: usize = 4000;
+
+// Share of the context window the chat history may take, the rest is left
+// for the system prompt and the reply
+pub const HISTORY_CONTEXT_RATIO: f32 = 0.75;
+
+// Summary generation temperature and length cap
+pub const SUMMARY_TEMPERATURE: f32 = 0.2;
+pub const SUMMARY_MAX_TOKENS: i32 = 2048;
+
+// Embedding model of user memories and knowledge bases, overridden by
+// EMBEDDING_MODEL
+pub const DEFAULT_EMBEDDING_MODEL: &str = "openai/text-embedding-3-small";
+
+// Memories recalled per turn, and how similar they must be to the message
+pub const MEMORY_RECALL_LIMIT: usize = 5;
+pub const MEMORY_MIN_SIMILARITY: f32 = 0.3;
+
+// Maximum length in characters of a single memory
+pub const MAX_MEMORY_LEN: usize = 1000;
+
+// Inputs sent per embedding request
+pub const EMBEDDING_BATCH_SIZE: usize = 64;
+
+// Size in characters of the passages documents are split into, and how much
+// neighbouring passages overlap
+pub const KNOWLEDGE_CHUNK_CHARS: usize = 1500;
+pub const KNOWLEDGE_CHUNK_OVERLAP: usize = 200;
+
+// Passages returned per knowledge search, and how similar they must be to
+// the query
+pub const KNOWLEDGE_SEARCH_LIMIT: usize = 5;
+pub const KNOWLEDGE_MIN_SIMILARITY: f32 = 0.2;
+
+// Maximum length in characters of a knowledge base name
+pub const MAX_KNOWLEDGE_NAME_LEN: usize = 64;
+
+// Passages a knowledge base holds at most, searching loads all of them
+pub const MAX_KNOWLEDGE_PASSAGES: u64 = 2000;
+
+// Models compared side by side in a single turn at most
+pub const MAX_COMPARE_MODELS: usize = 4;
+
+// Chats and total uncompressed size of an export of all chats at most, the
+// archive is built in memory
+pub const MAX_EXPORT_CHATS: usize = 1000;
+pub const MAX_EXPORT_BYTES: usize = 128 * 1024 * 1024;
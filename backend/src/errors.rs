@@ -67,6 +67,19 @@ pub enum ErrorKind {
     /// Usually means the user needs to log in again.
     MalformedToken,
 
+    /// User is authenticated but lacks the role required for the operation.
+    /// Frontend should hide the related screen instead of prompting login.
+    Forbidden,
+
+    /// The account still uses a password that has to be replaced, only
+    /// changing the password is allowed.
+    /// Frontend should show the change password form.
+    PasswordChangeRequired,
+
+    /// New password is rejected by the password policy.
+    /// Frontend should show the reason next to the password field.
+    WeakPassword,
+
     /// Request body doesn't match expected schema.
     /// Indicates a client-side bug or API version mismatch.
     MalformedRequest,
@@ -80,6 +93,10 @@ pub enum ErrorKind {
     /// Frontend should show specific error message to user.
     LoginFail,
 
+    /// Too many login attempts from the client or for the account.
+    /// Frontend should show the reason, which includes when to retry.
+    RateLimited,
+
     /// Requested resource (chat, message, user, etc.) not found.
     /// May indicate:
     /// - Resource was deleted by another client
@@ -92,6 +109,10 @@ pub enum ErrorKind {
     /// Frontend should suggest retrying or checking API status.
     ApiFail,
 
+    /// The user's spending budget for the current period is used up.
+    /// Frontend should show the reason instead of retrying.
+    BudgetExceeded,
+
     /// Tool execution failed (web search, code execution, etc.).
     /// May indicate:
     /// - Network connectivity issue
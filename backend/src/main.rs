@@ -66,6 +66,11 @@ pub struct AppState {
     pub openrouter: Arc<crate::openrouter::Openrouter>,
     pub blob: Arc<BlobDB>,
     pub auth_header: Option<String>,
+    pub header_provision: utils::environment::HeaderProvision,
+    pub oidc: Option<utils::oidc::OidcClient>,
+    pub trusted_proxies: Vec<middlewares::client_ip::TrustedProxy>,
+    pub throttle: utils::login_throttle::LoginThrottle,
+    pub password_policy: utils::password_policy::PasswordPolicy,
 }
 
 /// Handles graceful shutdown signals.
@@ -194,8 +199,13 @@ async fn main() {
     );
     log::debug!("Blob DB opened");
 
+    let embedder = Arc::new(utils::embedding::Embedder::new(
+        openrouter.clone(),
+        env.embedding_model,
+    ));
+
     let chat = Arc::new(
-        Context::new(conn.clone(), openrouter.clone(), blob.clone())
+        Context::new(conn.clone(), openrouter.clone(), blob.clone(), embedder)
             .expect("Failed to create pipeline context"),
     );
     log::debug!("Chat context created");
@@ -210,6 +220,11 @@ async fn main() {
         openrouter,
         blob,
         auth_header: env.auth_header,
+        header_provision: env.header_provision,
+        oidc: env.oidc.map(utils::oidc::OidcClient::new),
+        trusted_proxies: env.trusted_proxies,
+        throttle: Default::default(),
+        password_policy: env.password_policy,
     });
 
     #[cfg(feature = "tracing")]
@@ -220,10 +235,24 @@ async fn main() {
         .nest(
             "/api",
             Router::new()
+                .nest("/assistant", routes::assistant::routes())
+                .nest("/audit", routes::audit::routes())
+                .nest("/budget", routes::budget::routes())
                 .nest("/chat", routes::chat::routes())
+                .nest("/compare", routes::compare::routes())
+                .nest("/folder", routes::folder::routes())
+                .nest("/group", routes::group::routes())
+                .nest("/knowledge", routes::knowledge::routes())
                 .nest("/user", routes::user::routes())
+                .nest("/memory", routes::memory::routes())
                 .nest("/message", routes::message::routes())
                 .nest("/model", routes::model::routes())
+                .nest("/session", routes::session::routes())
+                .nest("/share", routes::share::routes())
+                .nest("/tag", routes::tag::routes())
+                .nest("/token", routes::token::routes())
+                .nest("/totp", routes::totp::routes())
+                .nest("/usage", routes::usage::routes())
                 .layer(middlewares::compression::ZstdCompressionLayer)
                 .nest("/file", routes::file::routes())
                 .layer(middleware::from_extractor_with_state::<
@@ -231,6 +260,7 @@ async fn main() {
                     _,
                 >(state.clone()))
                 .nest("/auth", routes::auth::routes())
+                .nest("/shared", routes::share::public_routes())
                 .layer(middlewares::logger::LoggerLayer),
         )
         .fallback(routes::spa::spa_handler)
@@ -253,8 +283,11 @@ async fn main() {
     let _server_span = info_span!("server_startup", bind_addr = %env.bind_addr).entered();
 
     let tcp = TcpListener::bind(env.bind_addr).await.unwrap();
-    axum::serve(tcp, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .unwrap();
+    axum::serve(
+        tcp,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .unwrap();
 }
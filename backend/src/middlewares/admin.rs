@@ -0,0 +1,24 @@
+use axum::{
+    Json,
+    body::Body,
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use protocol::UserRole;
+
+use crate::errors::*;
+
+/// Rejects the request unless [`super::auth::Middleware`] resolved an admin.
+///
+/// Layer it with `route_layer` so unmatched routes still fall through.
+pub async fn handle(request: Request<Body>, next: Next) -> Response {
+    match request.extensions().get::<UserRole>() {
+        Some(UserRole::Admin) => next.run(request).await,
+        _ => Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: "administrator privilege required".to_owned(),
+        })
+        .into_response(),
+    }
+}
@@ -2,16 +2,26 @@ use std::sync::Arc;
 
 use axum::{
     Json,
-    extract::FromRequestParts,
+    extract::{FromRequestParts, OriginalUri},
     http::{header, request::Parts},
 };
+use entity::{api_token, prelude::*, session, user};
 use pasetors::{Local, claims::ClaimsValidationRules, local, token::UntrustedToken, version4::V4};
+use protocol::{ApiTokenScope, UserRole};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
 
-use crate::{AppState, errors::*};
+use crate::{AppState, config::API_TOKEN_TOUCH_INTERVAL_SECS, errors::*, utils::timestamp};
+
+/// Prefix of personal API tokens, in the form `llumen_<prefix>_<secret>`
+pub const API_TOKEN_PREFIX: &str = "llumen_";
 
 #[derive(Debug, Clone, Copy)]
 pub struct UserId(pub i32);
 
+/// Present only when the request is authenticated by a login session.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionId(pub i32);
+
 pub struct Middleware;
 
 impl FromRequestParts<Arc<AppState>> for Middleware {
@@ -28,29 +38,215 @@ impl FromRequestParts<Arc<AppState>> for Middleware {
             .kind(ErrorKind::Unauthorized)?;
 
         let token = token.to_str().kind(ErrorKind::MalformedToken)?;
-        let token = UntrustedToken::<Local, V4>::try_from(token).kind(ErrorKind::MalformedToken)?;
-        let validation_rules = ClaimsValidationRules::new();
-        let token = local::decrypt(&state.key, &token, &validation_rules, None, None)
-            .kind(ErrorKind::MalformedToken)?;
+        let token = token.strip_prefix("Bearer ").unwrap_or(token);
+
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|x| x.path().to_owned())
+            .unwrap_or_else(|| parts.uri.path().to_owned());
+        let path = path.strip_prefix("/api").unwrap_or(&path);
 
-        let claim = token
-            .payload_claims()
-            .map(|x| x.get_claim("uid").map(|x| x.as_i64()))
-            .flatten()
-            .flatten();
+        let user = match token.strip_prefix(API_TOKEN_PREFIX) {
+            Some(token) => api_token_auth(state, token, path).await?,
+            None => {
+                let (session, user) = session_auth(state, token).await?;
+                parts.extensions.insert(SessionId(session.id));
+                user
+            }
+        };
 
-        let user_id = claim
-            .ok_or("Missing claim")
-            .kind(ErrorKind::MalformedToken)? as i32;
+        if user.must_change_password && !PASSWORD_CHANGE_ROUTES.contains(&path) {
+            return Err(Json(Error {
+                error: ErrorKind::PasswordChangeRequired,
+                reason: "password must be changed before continuing".to_owned(),
+            }));
+        }
 
         #[cfg(feature = "tracing")]
         {
             use tracing::info;
-            info!(user_id = user_id, "authentication successful");
+            info!(user_id = user.id, "authentication successful");
         }
 
-        parts.extensions.insert(UserId(user_id));
+        parts.extensions.insert(UserId(user.id));
+        parts.extensions.insert::<UserRole>(user.role);
 
         Ok(Self)
     }
 }
+
+async fn session_auth(
+    state: &AppState,
+    token: &str,
+) -> Result<(session::Model, user::Model), AppError> {
+    let token = UntrustedToken::<Local, V4>::try_from(token).kind(ErrorKind::MalformedToken)?;
+    let validation_rules = ClaimsValidationRules::new();
+    let token = local::decrypt(&state.key, &token, &validation_rules, None, None)
+        .kind(ErrorKind::MalformedToken)?;
+
+    let claims = token
+        .payload_claims()
+        .ok_or("Missing claim")
+        .kind(ErrorKind::MalformedToken)?;
+
+    let user_id = claims
+        .get_claim("uid")
+        .and_then(|x| x.as_i64())
+        .ok_or("Missing claim")
+        .kind(ErrorKind::MalformedToken)? as i32;
+    let token_id = claims
+        .get_claim("jti")
+        .and_then(|x| x.as_str())
+        .ok_or("Missing claim")
+        .kind(ErrorKind::MalformedToken)?;
+
+    // session and role are read per request so that revocation and
+    // demotion take effect immediately
+    let now = timestamp::now();
+    let (session, user) = Session::find()
+        .filter(session::Column::TokenId.eq(token_id))
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.gt(now))
+        .find_also_related(User)
+        .one(&state.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("session has been revoked")
+        .kind(ErrorKind::Unauthorized)?;
+    let user = user
+        .ok_or("user no longer exists")
+        .kind(ErrorKind::Unauthorized)?;
+
+    Ok((session, user))
+}
+
+async fn api_token_auth(
+    state: &AppState,
+    token: &str,
+    path: &str,
+) -> Result<user::Model, AppError> {
+    let (prefix, secret) = token
+        .split_once('_')
+        .ok_or("malformed api token")
+        .kind(ErrorKind::MalformedToken)?;
+
+    let now = timestamp::now();
+    let (api_token, user) = ApiToken::find()
+        .filter(api_token::Column::Prefix.eq(prefix))
+        .find_also_related(User)
+        .one(&state.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("api token has been revoked")
+        .kind(ErrorKind::Unauthorized)?;
+    let user = user
+        .ok_or("user no longer exists")
+        .kind(ErrorKind::Unauthorized)?;
+
+    if api_token.expires_at.is_some_and(|x| x <= now) {
+        return Err(Json(Error {
+            error: ErrorKind::Unauthorized,
+            reason: "api token has expired".to_owned(),
+        }));
+    }
+    // the prefix found a single row, so this is one hash run per request
+    if !state.hasher.verify_password(&api_token.hash, secret) {
+        return Err(Json(Error {
+            error: ErrorKind::Unauthorized,
+            reason: "api token has been revoked".to_owned(),
+        }));
+    }
+
+    if !api_token.scopes.0.iter().any(|x| scope_allows(*x, path)) {
+        return Err(Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: format!("api token is not allowed to access {}", path),
+        }));
+    }
+
+    // avoid a write on every request from busy scripts
+    if api_token
+        .last_used_at
+        .is_none_or(|x| now - x >= API_TOKEN_TOUCH_INTERVAL_SECS)
+    {
+        let mut api_token = api_token.into_active_model();
+        api_token.last_used_at = ActiveValue::Set(Some(now));
+        api_token
+            .update(&state.conn)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    Ok(user)
+}
+
+/// Routes reachable while the password must be changed, enough to find out
+/// why, change it or sign out.
+///
+/// `user/update` checks that nothing but the password is changed.
+const PASSWORD_CHANGE_ROUTES: &[&str] = &[
+    "/user/read",
+    "/user/update",
+    "/session/list",
+    "/session/revoke",
+];
+
+/// Routes reachable by each [`ApiTokenScope`], anything else is denied.
+///
+/// Token and session management are never reachable by API tokens.
+pub fn scope_allows(scope: ApiTokenScope, path: &str) -> bool {
+    let allowed: &[&str] = match scope {
+        ApiTokenScope::ChatRead => &[
+            "/chat/read",
+            "/chat/paginate",
+            "/chat/search",
+            "/chat/export",
+            "/compare/list",
+            "/message/paginate",
+            "/file/read",
+            "/file/image",
+            "/model/list",
+        ],
+        ApiTokenScope::MessageCreate => &[
+            "/assistant/list",
+            "/chat/create",
+            "/chat/sse",
+            "/chat/halt",
+            "/chat/import",
+            "/compare/create",
+            "/compare/pick",
+            "/message/create",
+            "/message/queue",
+            "/message/cancel",
+            "/message/regenerate",
+            "/message/edit",
+            "/message/switch",
+            "/file/upload",
+            "/model/list",
+        ],
+    };
+
+    allowed
+        .iter()
+        .any(|x| path == *x || path.strip_prefix(*x).is_some_and(|x| x.starts_with('/')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_allows() {
+        assert!(scope_allows(ApiTokenScope::ChatRead, "/chat/read"));
+        assert!(scope_allows(ApiTokenScope::ChatRead, "/file/read/12"));
+        assert!(!scope_allows(ApiTokenScope::ChatRead, "/message/create"));
+        assert!(!scope_allows(ApiTokenScope::ChatRead, "/chat/readall"));
+        assert!(scope_allows(
+            ApiTokenScope::MessageCreate,
+            "/message/create"
+        ));
+        assert!(!scope_allows(ApiTokenScope::MessageCreate, "/token/create"));
+        assert!(!scope_allows(ApiTokenScope::MessageCreate, "/user/update"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    Json,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, request::Parts},
+};
+
+use crate::{AppState, errors::*};
+
+/// Address of the client, resolved through trusted proxies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// An address or CIDR range whose `X-Forwarded-For` header is believed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let addr = IpAddr::from_str(addr).map_err(|e| format!("{}: {}", s, e))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|x| *x <= max)
+                .ok_or_else(|| format!("{}: invalid prefix length", s))?,
+            None => max,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Resolve the client address from the socket peer and `X-Forwarded-For`.
+///
+/// The header is only read when the peer is a trusted proxy. It is walked from
+/// the right, skipping further trusted proxies, so a client cannot spoof its
+/// address by sending its own header.
+pub fn resolve(peer: IpAddr, headers: &HeaderMap, trusted: &[TrustedProxy]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|x| x.contains(ip));
+    if !is_trusted(peer) {
+        return peer.to_canonical();
+    }
+
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|x| x.to_str().ok())
+        .flat_map(|x| x.split(','))
+        .filter_map(|x| IpAddr::from_str(x.trim()).ok())
+        .collect::<Vec<_>>();
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted(**ip))
+        .or(forwarded.first())
+        .copied()
+        .unwrap_or(peer)
+        .to_canonical()
+}
+
+impl FromRequestParts<Arc<AppState>> for ClientIp {
+    type Rejection = Json<Error>;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|x| x.0.ip())
+            .ok_or("cannot determine client address")
+            .kind(ErrorKind::Internal)?;
+
+        Ok(Self(resolve(peer, &parts.headers, &state.trusted_proxies)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_trusted_proxy_contains() {
+        let net: TrustedProxy = "172.16.0.0/12".parse().unwrap();
+        assert!(net.contains(ip("172.18.0.3")));
+        assert!(!net.contains(ip("172.32.0.1")));
+        assert!(net.contains(ip("::ffff:172.18.0.3")));
+
+        let single: TrustedProxy = "::1".parse().unwrap();
+        assert!(single.contains(ip("::1")));
+        assert!(!single.contains(ip("::2")));
+
+        assert!("10.0.0.0/33".parse::<TrustedProxy>().is_err());
+        assert!("proxy".parse::<TrustedProxy>().is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let trusted = vec!["10.0.0.0/8".parse().unwrap()];
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "6.6.6.6, 1.2.3.4, 10.0.0.2".parse().unwrap(),
+        );
+
+        // untrusted peer, header ignored
+        assert_eq!(resolve(ip("5.5.5.5"), &headers, &trusted), ip("5.5.5.5"));
+        // trusted peer, rightmost untrusted hop wins over spoofed entries
+        assert_eq!(resolve(ip("10.0.0.1"), &headers, &trusted), ip("1.2.3.4"));
+        // trusted peer without header
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &HeaderMap::new(), &trusted),
+            ip("10.0.0.1")
+        );
+    }
+}
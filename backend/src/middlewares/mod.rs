@@ -1,4 +1,5 @@
+pub mod admin;
 pub mod auth;
+pub mod client_ip;
 pub mod compression;
 pub mod logger;
-pub mod rate_limit;
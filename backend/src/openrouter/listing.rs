@@ -20,6 +20,7 @@ pub(super) struct ModelCaps {
     pub audio: bool,
     pub reasoning: bool,
     pub support_native_ocr: bool,
+    pub context_length: Option<u32>,
 }
 
 impl From<ModelCaps> for Capability {
@@ -79,6 +80,7 @@ impl From<&raw::Model> for ModelCaps {
                 .architecture
                 .input_modalities
                 .contains(&raw::Modality::File),
+            context_length: model.context_length,
         }
     }
 }
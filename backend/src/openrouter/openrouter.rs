@@ -144,6 +144,16 @@ impl Openrouter {
         self.chat.get_capability(&self.listing, model).await
     }
 
+    /// Returns the context window of the given model in tokens, if listed.
+    pub async fn get_context_length(&self, model_id: &str) -> Option<u32> {
+        if !self.is_custom_api {
+            let _ = self.listing.ensure(model_id).await;
+        }
+
+        let model_id = model_id.split(':').next().unwrap_or(model_id);
+        self.listing.get(model_id).await?.context_length
+    }
+
     /// Streams a chat completion with incremental deltas.
     pub async fn stream(
         &self,
@@ -267,16 +277,22 @@ impl Openrouter {
         let mut result: raw::EmbeddingResponse = res.json().await.map_err(Error::Http)?;
         result.data.sort_by(|a, b| a.index.cmp(&b.index));
 
+        let price = result
+            .usage
+            .map(|usage| {
+                usage
+                    .cost_details
+                    .and_then(|details| details.upstream_inference_cost)
+                    .unwrap_or(usage.cost)
+            })
+            .unwrap_or(result.price);
         let response = result
             .data
             .into_iter()
             .map(|embedding| embedding.embedding)
             .collect();
 
-        Ok(Embedding {
-            price: 0.0,
-            response,
-        })
+        Ok(Embedding { price, response })
     }
 
     fn embedding_body(req: raw::EmbeddingBatchReq) -> (Option<usize>, reqwest::Body) {
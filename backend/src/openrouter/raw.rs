@@ -210,6 +210,8 @@ pub struct Model {
     pub supported_parameters: Vec<SupportedParams>,
     #[serde(default)]
     pub architecture: Architecture,
+    #[serde(default)]
+    pub context_length: Option<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -867,6 +869,7 @@ pub struct EmbeddingResult {
 
 #[derive(Deserialize)]
 pub struct EmbeddingResponse {
+    #[serde(default)]
     pub price: f64,
     pub data: Vec<EmbeddingResult>,
     pub usage: Option<Usage>,
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{assistant, prelude::*};
+use protocol::{AssistantFiles, UserRole};
+use sea_orm::{ActiveValue::Set, EntityTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::AssistantReqFile;
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{chat::ChatMode, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AssistantCreateReq {
+    pub name: String,
+    /// Added to the system prompt of chats created from the assistant
+    pub prompt: String,
+    /// Default model of new chats
+    pub model_id: Option<i32>,
+    /// Default mode of new chats
+    pub mode: ChatMode,
+    /// Copied into every chat created from the assistant
+    pub files: Vec<AssistantReqFile>,
+    /// Let every user see and use the assistant
+    pub shared: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AssistantCreateResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
+    Json(req): Json<AssistantCreateReq>,
+) -> JsonResult<AssistantCreateResp> {
+    super::check(&app, user_id, role, &req.name, req.model_id).await?;
+    let files = super::claim_files(&app, user_id, req.files).await?;
+
+    let id = Assistant::insert(assistant::ActiveModel {
+        owner_id: Set(user_id),
+        name: Set(req.name.trim().to_owned()),
+        prompt: Set(req.prompt),
+        model_id: Set(req.model_id),
+        mode: Set(req.mode.into()),
+        files: Set(AssistantFiles(files)),
+        shared: Set(req.shared),
+        created_at: Set(timestamp::now()),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    Ok(Json(AssistantCreateResp { id }))
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AssistantDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AssistantDeleteResp {
+    pub deleted: bool,
+}
+
+/// Chats created from the assistant are kept and continue without its prompt.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<AssistantDeleteReq>,
+) -> JsonResult<AssistantDeleteResp> {
+    let assistant = super::owned(&app, user_id, req.id).await?;
+    let files = assistant.files.0.iter().map(|x| x.id).collect();
+
+    assistant
+        .delete(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Chat::update_many()
+        .col_expr(chat::Column::AssistantId, Expr::value(Option::<i32>::None))
+        .filter(chat::Column::AssistantId.eq(req.id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    super::release_files(&app, files).await?;
+
+    Ok(Json(AssistantDeleteResp { deleted: true }))
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{assistant, prelude::*};
+use protocol::FileMetadata;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::chat::ChatMode};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AssistantListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AssistantListResp {
+    pub list: Vec<AssistantList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AssistantList {
+    pub id: i32,
+    pub name: String,
+    pub prompt: String,
+    pub model_id: Option<i32>,
+    pub mode: ChatMode,
+    pub files: Vec<FileMetadata>,
+    pub shared: bool,
+    /// Whether the user may edit or delete the assistant
+    pub owned: bool,
+}
+
+/// List the user's own assistants and those shared by others.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<AssistantListReq>,
+) -> JsonResult<AssistantListResp> {
+    let list = Assistant::find()
+        .filter(
+            Condition::any()
+                .add(assistant::Column::OwnerId.eq(user_id))
+                .add(assistant::Column::Shared.eq(true)),
+        )
+        .order_by_asc(assistant::Column::Name)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .map(|x| AssistantList {
+            owned: x.owner_id == user_id,
+            id: x.id,
+            name: x.name,
+            prompt: x.prompt,
+            model_id: x.model_id,
+            mode: x.mode.into(),
+            files: x.files.0,
+            shared: x.shared,
+        })
+        .collect();
+
+    Ok(Json(AssistantListResp { list }))
+}
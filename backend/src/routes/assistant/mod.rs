@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, routing::post};
+use entity::{assistant, file, prelude::*};
+use protocol::{FileKind, FileMetadata, UserRole};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, sea_query::Expr};
+use serde::Deserialize;
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, utils::model_access};
+
+mod create;
+mod delete;
+mod list;
+mod write;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/create", post(create::route))
+        .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+        .route("/write", post(write::route))
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AssistantReqFile {
+    /// Id returned by `/file/upload`
+    pub id: i32,
+    pub name: String,
+}
+
+/// Load an assistant `user_id` may edit.
+async fn owned(app: &AppState, user_id: i32, id: i32) -> Result<assistant::Model, AppError> {
+    Assistant::find_by_id(id)
+        .filter(assistant::Column::OwnerId.eq(user_id))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "assistant not found".to_owned(),
+            })
+        })
+}
+
+/// Reject an empty name or a model the user cannot use.
+async fn check(
+    app: &AppState,
+    user_id: i32,
+    role: UserRole,
+    name: &str,
+    model_id: Option<i32>,
+) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "assistant name must not be empty".to_owned(),
+        }));
+    }
+    if let Some(model_id) = model_id {
+        let access = model_access::load(&app.conn, user_id, role)
+            .await
+            .kind(ErrorKind::Internal)?;
+        if !access.permits(model_id) {
+            return Err(Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "model not found".to_owned(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Keep uploaded files past the upload expiry, as starter files.
+///
+/// Files of other users are left out.
+async fn claim_files(
+    app: &AppState,
+    user_id: i32,
+    files: Vec<AssistantReqFile>,
+) -> Result<Vec<FileMetadata>, AppError> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let owned = File::find()
+        .filter(file::Column::Id.is_in(files.iter().map(|x| x.id)))
+        .filter(file::Column::OwnerId.eq(user_id))
+        .filter(file::Column::ChatId.is_null())
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+
+    File::update_many()
+        .col_expr(file::Column::ValidUntil, Expr::value(Option::<u32>::None))
+        .filter(file::Column::Id.is_in(owned.clone()))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(files
+        .into_iter()
+        .filter(|x| owned.contains(&x.id))
+        .map(|x| FileMetadata {
+            name: x.name,
+            id: x.id,
+            kind: FileKind::User,
+            dimensions: None,
+        })
+        .collect())
+}
+
+/// Remove starter files no longer used by the assistant.
+async fn release_files(app: &AppState, ids: Vec<i32>) -> Result<(), AppError> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    File::delete_many()
+        .filter(file::Column::Id.is_in(ids.clone()))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    for id in ids {
+        if let Err(e) = app.blob.delete(id) {
+            log::warn!("cannot remove starter file {}: {}", id, e);
+        }
+    }
+    Ok(())
+}
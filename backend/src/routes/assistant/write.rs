@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use protocol::{AssistantFiles, UserRole};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::AssistantReqFile;
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::chat::ChatMode};
+
+/// Omitted fields are left unchanged.
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AssistantWriteReq {
+    pub id: i32,
+    pub name: Option<String>,
+    pub prompt: Option<String>,
+    /// Replaces the default model, chats then pick one themselves when
+    /// `clear_model` is set
+    pub model_id: Option<i32>,
+    pub clear_model: Option<bool>,
+    pub mode: Option<ChatMode>,
+    /// Replaces all starter files, listed files that are already starter
+    /// files of the assistant are kept
+    pub files: Option<Vec<AssistantReqFile>>,
+    pub shared: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AssistantWriteResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
+    Json(req): Json<AssistantWriteReq>,
+) -> JsonResult<AssistantWriteResp> {
+    let assistant = super::owned(&app, user_id, req.id).await?;
+    let name = req.name.unwrap_or_else(|| assistant.name.clone());
+    super::check(&app, user_id, role, &name, req.model_id).await?;
+
+    let mut release = Vec::new();
+    let files = match req.files {
+        Some(files) => {
+            let (kept, uploaded): (Vec<_>, Vec<_>) = files
+                .into_iter()
+                .partition(|x| assistant.files.0.iter().any(|f| f.id == x.id));
+            let mut files = assistant
+                .files
+                .0
+                .iter()
+                .filter(|f| kept.iter().any(|x| x.id == f.id))
+                .cloned()
+                .collect::<Vec<_>>();
+            release = assistant
+                .files
+                .0
+                .iter()
+                .filter(|f| !files.iter().any(|x| x.id == f.id))
+                .map(|x| x.id)
+                .collect();
+            files.extend(super::claim_files(&app, user_id, uploaded).await?);
+            Some(files)
+        }
+        None => None,
+    };
+
+    let mut active_model = assistant.into_active_model();
+    active_model.name = Set(name.trim().to_owned());
+    if let Some(prompt) = req.prompt {
+        active_model.prompt = Set(prompt);
+    }
+    if req.clear_model.unwrap_or(false) {
+        active_model.model_id = Set(None);
+    } else if let Some(model_id) = req.model_id {
+        active_model.model_id = Set(Some(model_id));
+    }
+    if let Some(mode) = req.mode {
+        active_model.mode = Set(mode.into());
+    }
+    if let Some(files) = files {
+        active_model.files = Set(AssistantFiles(files));
+    }
+    if let Some(shared) = req.shared {
+        active_model.shared = Set(shared);
+    }
+    active_model
+        .update(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    super::release_files(&app, release).await?;
+
+    Ok(Json(AssistantWriteResp { id: req.id }))
+}
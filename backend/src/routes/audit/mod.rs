@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{Router, middleware, routing::post};
+
+use crate::{AppState, middlewares::admin};
+
+mod paginate;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/paginate", post(paginate::route))
+        .route_layer(middleware::from_fn(admin::handle))
+}
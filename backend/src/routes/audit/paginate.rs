@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{audit_log, prelude::*};
+use protocol::{AuditAction, AuditTarget};
+use sea_orm::{Iterable, QueryOrder, QuerySelect, prelude::*};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState, config::MAX_PAGINATE_LIMIT, errors::*, middlewares::auth::UserId, utils::timestamp,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct AuditPaginateReq {
+    /// Default to the beginning
+    /// For Gt => minimum id
+    /// For Lt => maximum id
+    pub id: Option<i32>,
+    pub order: AuditPaginateReqOrder,
+    pub limit: Option<u32>,
+    /// Only entries performed by this user
+    pub actor_id: Option<i32>,
+    /// Only entries about this target, requires `target_kind`
+    pub target_id: Option<i32>,
+    /// Only entries about this kind of target
+    pub target_kind: Option<AuditTarget>,
+    pub action: Option<AuditAction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum AuditPaginateReqOrder {
+    /// greater than
+    Gt,
+    /// less than
+    Lt,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AuditPaginateResp {
+    pub list: Vec<AuditPaginateRespList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct AuditPaginateRespList {
+    pub id: i32,
+    pub action: AuditAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_kind: Option<AuditTarget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub created_at: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(_)): Extension<UserId>,
+    Json(req): Json<AuditPaginateReq>,
+) -> JsonResult<AuditPaginateResp> {
+    let mut q = AuditLog::find().limit(
+        req.limit
+            .map(|x| x.min(MAX_PAGINATE_LIMIT))
+            .unwrap_or(MAX_PAGINATE_LIMIT) as u64,
+    );
+    if let Some(actor_id) = req.actor_id {
+        q = q.filter(audit_log::Column::ActorId.eq(actor_id));
+    }
+    if req.target_id.is_some() && req.target_kind.is_none() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "target_id requires target_kind".to_owned(),
+        }));
+    }
+    if let Some(target_id) = req.target_id {
+        q = q.filter(audit_log::Column::TargetId.eq(target_id));
+    }
+    if let Some(kind) = req.target_kind {
+        // the kind of target follows from the action
+        let actions = AuditAction::iter().filter(|x| x.target() == Some(kind));
+        q = q.filter(audit_log::Column::Action.is_in(actions));
+    }
+    if let Some(action) = req.action {
+        q = q.filter(audit_log::Column::Action.eq(action));
+    }
+    let q = match (req.order, req.id) {
+        (AuditPaginateReqOrder::Gt, None) => q.order_by_asc(audit_log::Column::Id),
+        (AuditPaginateReqOrder::Gt, Some(id)) => q
+            .filter(audit_log::Column::Id.gt(id))
+            .order_by_asc(audit_log::Column::Id),
+        (AuditPaginateReqOrder::Lt, None) => q.order_by_desc(audit_log::Column::Id),
+        (AuditPaginateReqOrder::Lt, Some(id)) => q
+            .filter(audit_log::Column::Id.lt(id))
+            .order_by_desc(audit_log::Column::Id),
+    };
+
+    let list = q
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .map(|x| AuditPaginateRespList {
+            id: x.id,
+            action: x.action,
+            actor_id: x.actor_id,
+            target_id: x.target_id,
+            target_kind: x.target_id.and(x.action.target()),
+            detail: x.detail,
+            ip: x.ip,
+            created_at: timestamp::format(x.created_at),
+        })
+        .collect();
+    Ok(Json(AuditPaginateResp { list }))
+}
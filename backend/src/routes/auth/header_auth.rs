@@ -1,13 +1,13 @@
 use std::sync::Arc;
 
 use axum::{Json, extract::State, http::HeaderMap};
-use entity::{prelude::*, user};
-use sea_orm::prelude::*;
+use protocol::UserRole;
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
 use serde::Serialize;
 use typeshare::typeshare;
 
 use super::helper;
-use crate::{AppState, errors::*};
+use crate::{AppState, errors::*, middlewares::client_ip::ClientIp};
 
 #[derive(Debug, Clone, Serialize)]
 #[typeshare]
@@ -18,30 +18,94 @@ pub struct HeaderAuthResp {
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
     headers: HeaderMap,
 ) -> JsonResult<HeaderAuthResp> {
     let header = app.auth_header.as_deref();
     let username = header.and_then(|x| headers.get(x).and_then(|x| x.to_str().ok()));
 
-    if username.is_none() {
+    let Some(username) = username else {
         return Ok(Json(HeaderAuthResp {
             token: None,
             exp: None,
         }));
-    }
+    };
+
+    let provision = &app.header_provision;
+    let model = helper::find_or_create_user(&app, username, provision.auto_create).await?;
 
-    let model = User::find()
-        .filter(user::Column::Name.eq(username.unwrap()))
-        .one(&app.conn)
-        .await
-        .kind(ErrorKind::Internal)?
-        .ok_or("User not found")
-        .kind(ErrorKind::ResourceNotFound)?;
+    let read_header = |name: &Option<String>| {
+        name.as_deref()
+            .and_then(|x| headers.get(x))
+            .and_then(|x| x.to_str().ok())
+            .map(str::trim)
+    };
 
-    let helper::Token { token, exp } = helper::new_token(&app, model.id)?;
+    // keep display name and role in sync with the identity provider
+    let display_name = read_header(&provision.display_name_header)
+        .filter(|x| !x.is_empty())
+        .map(ToOwned::to_owned);
+    let role = match read_header(&provision.groups_header) {
+        Some(groups) if !provision.admin_groups.is_empty() => {
+            Some(resolve_role(groups, &provision.admin_groups))
+        }
+        _ => None,
+    };
+
+    let user_id = model.id;
+    let display_name = display_name.filter(|x| model.display_name.as_ref() != Some(x));
+    let role = role.filter(|x| *x != model.role);
+    if display_name.is_some() || role.is_some() {
+        let mut active_model = model.into_active_model();
+        if let Some(display_name) = display_name {
+            active_model.display_name = ActiveValue::Set(Some(display_name));
+        }
+        if let Some(role) = role {
+            log::info!(
+                "user({}) role is set to {:?} by trusted header",
+                user_id,
+                role
+            );
+            active_model.role = ActiveValue::Set(role);
+        }
+        active_model
+            .update(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    helper::login_succeeded(&app, ip, user_id, "trusted header").await;
+    let helper::Token { token, exp } = helper::new_token(&app, user_id, &headers).await?;
 
     Ok(Json(HeaderAuthResp {
         token: Some(token),
         exp: Some(exp),
     }))
 }
+
+/// Map a comma separated group header to a role.
+fn resolve_role(groups: &str, admin_groups: &[String]) -> UserRole {
+    let is_admin = groups
+        .split(',')
+        .map(str::trim)
+        .any(|group| admin_groups.iter().any(|x| x == group));
+
+    match is_admin {
+        true => UserRole::Admin,
+        false => UserRole::Member,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_role() {
+        let admin_groups = vec!["llumen-admin".to_owned(), "ops".to_owned()];
+        assert_eq!(resolve_role("dev, ops", &admin_groups), UserRole::Admin);
+        assert_eq!(resolve_role("llumen-admin", &admin_groups), UserRole::Admin);
+        assert_eq!(resolve_role("dev,llumen", &admin_groups), UserRole::Member);
+        assert_eq!(resolve_role("", &admin_groups), UserRole::Member);
+    }
+}
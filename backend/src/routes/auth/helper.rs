@@ -1,16 +1,178 @@
 use crate::{
-    config::TOKEN_EXPIRATION_SECS,
+    config::{TOKEN_EXPIRATION_SECS, TOTP_CHALLENGE_EXPIRATION_SECS},
     AppState,
-    errors::{AppError, ErrorKind, WithKind},
+    errors::{AppError, Error, ErrorKind, WithKind},
+    utils::{audit::Audit, login_throttle::Account, random, timestamp},
 };
-use pasetors::{claims::Claims, local};
-use std::time::Duration;
+use axum::http::{HeaderMap, header};
+use entity::{oidc_identity, prelude::*, session, user};
+use pasetors::{
+    Local,
+    claims::{Claims, ClaimsValidationRules},
+    local,
+    token::UntrustedToken,
+    version4::V4,
+};
+use protocol::AuditAction;
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
+use std::{net::IpAddr, time::Duration};
 
 pub struct Token {
     pub token: String,
     pub exp: String,
 }
-pub fn new_token(app: &AppState, user_id: i32) -> Result<Token, AppError> {
+
+/// Issue a token for a fresh session of `user_id`.
+pub async fn new_token(
+    app: &AppState,
+    user_id: i32,
+    headers: &HeaderMap,
+) -> Result<Token, AppError> {
+    let now = timestamp::now();
+
+    // opportunistically drop expired sessions of this user
+    Session::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.lte(now))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let token_id = random::url_safe(18);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|x| x.to_str().ok())
+        .map(|x| x.chars().take(256).collect::<String>());
+
+    Session::insert(session::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        token_id: ActiveValue::Set(token_id.clone()),
+        user_agent: ActiveValue::Set(user_agent),
+        created_at: ActiveValue::Set(now),
+        expires_at: ActiveValue::Set(now + TOKEN_EXPIRATION_SECS as i64),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    sign_token(app, user_id, &token_id)
+}
+
+/// Find a user by name for trusted header logins, creating a member on first sight when
+/// `auto_create` is set.
+///
+/// Created users get a random password nobody knows, so they can only sign in
+/// through SSO until an admin sets one.
+pub async fn find_or_create_user(
+    app: &AppState,
+    username: &str,
+    auto_create: bool,
+) -> Result<user::Model, AppError> {
+    let model = User::find()
+        .filter(user::Column::Name.eq(username))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    match model {
+        Some(model) => Ok(model),
+        None if auto_create => {
+            let password_hash = app.hasher.hash_password(&random::url_safe(32));
+            let model = user::ActiveModel {
+                name: ActiveValue::Set(username.to_owned()),
+                password: ActiveValue::Set(password_hash),
+                ..Default::default()
+            }
+            .insert(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?;
+
+            log::info!("user({}) is created by sso login", model.id);
+            Ok(model)
+        }
+        None => Err(axum::Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "User not found".to_owned(),
+        })),
+    }
+}
+
+/// Find the user linked to an OIDC identity, creating a member on first sight
+/// when `auto_create` is set.
+///
+/// Identities are matched by `iss` and `sub` only. An existing account is never
+/// adopted by name, since users can usually choose their username claim at the
+/// provider.
+pub async fn find_or_create_oidc_user(
+    app: &AppState,
+    issuer: &str,
+    subject: &str,
+    username: &str,
+    auto_create: bool,
+) -> Result<user::Model, AppError> {
+    let linked = User::find()
+        .inner_join(OidcIdentity)
+        .filter(oidc_identity::Column::Issuer.eq(issuer))
+        .filter(oidc_identity::Column::Subject.eq(subject))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    if let Some(model) = linked {
+        return Ok(model);
+    }
+    if !auto_create {
+        return Err(axum::Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "User not found".to_owned(),
+        }));
+    }
+
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let taken = User::find()
+        .filter(user::Column::Name.eq(username))
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .is_some();
+    if taken {
+        return Err(axum::Json(Error {
+            error: ErrorKind::LoginFail,
+            reason: "username is taken by another account".to_owned(),
+        }));
+    }
+
+    let password_hash = app.hasher.hash_password(&random::url_safe(32));
+    let model = user::ActiveModel {
+        name: ActiveValue::Set(username.to_owned()),
+        password: ActiveValue::Set(password_hash),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    OidcIdentity::insert(oidc_identity::ActiveModel {
+        user_id: ActiveValue::Set(model.id),
+        issuer: ActiveValue::Set(issuer.to_owned()),
+        subject: ActiveValue::Set(subject.to_owned()),
+        created_at: ActiveValue::Set(timestamp::now()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    log::info!("user({}) is created by oidc login", model.id);
+    Ok(model)
+}
+
+/// Sign a token for an existing session, used by both login and renew.
+pub fn sign_token(app: &AppState, user_id: i32, token_id: &str) -> Result<Token, AppError> {
     let mut claim = Claims::new().kind(ErrorKind::Internal)?;
 
     let expiration = Duration::from_secs(TOKEN_EXPIRATION_SECS);
@@ -18,6 +180,8 @@ pub fn new_token(app: &AppState, user_id: i32) -> Result<Token, AppError> {
         .set_expires_in(&expiration)
         .kind(ErrorKind::Internal)?;
 
+    claim.token_identifier(token_id).kind(ErrorKind::Internal)?;
+
     // safety:
     // "uid" is not reserve
     claim.add_additional("uid", user_id).unwrap();
@@ -30,3 +194,97 @@ pub fn new_token(app: &AppState, user_id: i32) -> Result<Token, AppError> {
 
     Ok(Token { token, exp })
 }
+
+/// Issue a short-lived token proving the password step of `user_id` passed.
+///
+/// It carries no "jti", so the auth middleware never accepts it as a session.
+pub fn new_challenge(app: &AppState, user_id: i32) -> Result<String, AppError> {
+    let mut claim = Claims::new().kind(ErrorKind::Internal)?;
+
+    let expiration = Duration::from_secs(TOTP_CHALLENGE_EXPIRATION_SECS);
+    claim
+        .set_expires_in(&expiration)
+        .kind(ErrorKind::Internal)?;
+
+    // safety:
+    // "uid" and "purpose" are not reserve
+    claim.add_additional("uid", user_id).unwrap();
+    claim.add_additional("purpose", "totp").unwrap();
+
+    local::encrypt(&app.key, &claim, None, None).kind(ErrorKind::Internal)
+}
+
+/// Check a token from [`new_challenge`], returning its user id.
+pub fn verify_challenge(app: &AppState, challenge: &str) -> Result<i32, AppError> {
+    let token =
+        UntrustedToken::<Local, V4>::try_from(challenge).kind(ErrorKind::MalformedRequest)?;
+    let token = local::decrypt(&app.key, &token, &ClaimsValidationRules::new(), None, None)
+        .kind(ErrorKind::LoginFail)?;
+    let claims = token
+        .payload_claims()
+        .ok_or("Cannot get claims")
+        .kind(ErrorKind::MalformedRequest)?;
+
+    if claims.get_claim("purpose").and_then(|x| x.as_str()) != Some("totp") {
+        return Err(axum::Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "not a login challenge".to_owned(),
+        }));
+    }
+
+    claims
+        .get_claim("uid")
+        .and_then(|x| x.as_i64())
+        .map(|x| x as i32)
+        .ok_or("Cannot get user id")
+        .kind(ErrorKind::MalformedRequest)
+}
+
+/// Throttle and audit a failed login attempt.
+///
+/// `account` is the id of the user the attempt targeted, if it exists.
+/// Otherwise `username` is throttled the same, unless empty.
+pub async fn login_failed(
+    app: &AppState,
+    ip: IpAddr,
+    username: &str,
+    account: Option<i32>,
+    method: &str,
+) {
+    let throttled = match account {
+        Some(id) => Some(Account::User(id, username)),
+        None if !username.is_empty() => Some(Account::Unknown(username)),
+        None => None,
+    };
+    let locked = app.throttle.failure(ip, throttled).await;
+
+    let mut audit = Audit::new(AuditAction::LoginFail)
+        .detail(format!("{} {}", method, username))
+        .ip(ip);
+    if let Some(account) = account {
+        audit = audit.target(account);
+    }
+    audit.record(&app.conn).await;
+
+    if locked {
+        let mut audit = Audit::new(AuditAction::AccountLocked)
+            .detail(username)
+            .ip(ip);
+        if let Some(account) = account {
+            audit = audit.target(account);
+        }
+        audit.record(&app.conn).await;
+    }
+}
+
+/// Reset throttling and audit a successful login.
+pub async fn login_succeeded(app: &AppState, ip: IpAddr, user_id: i32, method: &str) {
+    app.throttle.success(ip, user_id).await;
+
+    Audit::new(AuditAction::LoginSuccess)
+        .actor(user_id)
+        .detail(method)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+}
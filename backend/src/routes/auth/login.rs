@@ -1,13 +1,13 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State};
+use axum::{Json, extract::State, http::HeaderMap};
 use entity::{prelude::*, user};
 use sea_orm::prelude::*;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use super::helper;
-use crate::{AppState, errors::*};
+use crate::{AppState, errors::*, middlewares::client_ip::ClientIp, utils::login_throttle::Account};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -18,31 +18,65 @@ pub struct LoginReq {
 
 #[derive(Debug, Serialize)]
 #[typeshare]
-pub struct LoginResp {
+#[serde(tag = "t", content = "c", rename_all = "snake_case")]
+pub enum LoginResp {
+    Token(LoginToken),
+    /// A second factor is required, pass it to `/auth/totp` together with the code
+    Challenge(String),
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct LoginToken {
     pub token: String,
     pub exp: String,
 }
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(req): Json<LoginReq>,
 ) -> JsonResult<LoginResp> {
     let model = User::find()
-        .filter(user::Column::Name.eq(req.username))
+        .filter(user::Column::Name.eq(req.username.as_str()))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    // unknown usernames are locked alike, not to tell which exist
+    let account = match &model {
+        Some(model) => Account::User(model.id, &req.username),
+        None => Account::Unknown(&req.username),
+    };
+    app.throttle
+        .check(ip, Some(account))
+        .await
+        .kind(ErrorKind::RateLimited)?;
+
+    let model = match model {
+        Some(model) if app.hasher.verify_password(&model.password, &req.password) => model,
+        model => {
+            helper::login_failed(&app, ip, &req.username, model.map(|x| x.id), "password").await;
+            return Err(Json(Error {
+                error: ErrorKind::LoginFail,
+                reason: "".to_owned(),
+            }));
+        }
+    };
+
+    let second_factor = Totp::find_by_id(model.id)
         .one(&app.conn)
         .await
         .kind(ErrorKind::Internal)?
-        .ok_or("")
-        .kind(ErrorKind::LoginFail)?;
-
-    if !app.hasher.verify_password(&model.password, &req.password) {
-        return Err(Json(Error {
-            error: ErrorKind::LoginFail,
-            reason: "".to_owned(),
-        }));
+        .is_some_and(|x| x.enabled);
+    if second_factor {
+        let challenge = helper::new_challenge(&app, model.id)?;
+        return Ok(Json(LoginResp::Challenge(challenge)));
     }
 
-    let helper::Token { token, exp } = helper::new_token(&app, model.id)?;
+    helper::login_succeeded(&app, ip, model.id, "password").await;
+    let helper::Token { token, exp } = helper::new_token(&app, model.id, &headers).await?;
 
-    Ok(Json(LoginResp { token, exp }))
+    Ok(Json(LoginResp::Token(LoginToken { token, exp })))
 }
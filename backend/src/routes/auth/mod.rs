@@ -1,20 +1,22 @@
 use std::sync::Arc;
 
-use axum::{Router, middleware, routing::post};
+use axum::{Router, routing::post};
 
 use crate::AppState;
 
 mod header_auth;
 mod helper;
 mod login;
+mod oidc;
 mod renew;
+mod totp;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
-        .route(
-            "/login",
-            post(login::route).layer(middleware::from_fn(crate::middlewares::rate_limit::handle)),
-        )
+        .route("/login", post(login::route))
+        .route("/totp", post(totp::route))
         .route("/renew", post(renew::route))
         .route("/header", post(header_auth::route))
+        .route("/oidc/start", post(oidc::start))
+        .route("/oidc/callback", post(oidc::callback))
 }
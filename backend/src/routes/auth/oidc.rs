@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::HeaderMap};
+use entity::{prelude::*, user};
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::helper;
+use crate::{AppState, errors::*, middlewares::client_ip::ClientIp, utils::oidc::OidcClient};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct OidcStartReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct OidcStartResp {
+    /// Provider url to redirect to, none if OIDC is not configured
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct OidcCallbackReq {
+    /// `code` query parameter of the redirect uri
+    pub code: String,
+    /// `state` query parameter of the redirect uri
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+#[serde(tag = "t", content = "c", rename_all = "snake_case")]
+pub enum OidcCallbackResp {
+    Token(OidcCallbackToken),
+    /// A second factor is required, pass it to `/auth/totp` together with the code
+    Challenge(String),
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct OidcCallbackToken {
+    pub token: String,
+    pub exp: String,
+}
+
+pub async fn start(
+    State(app): State<Arc<AppState>>,
+    Json(_): Json<OidcStartReq>,
+) -> JsonResult<OidcStartResp> {
+    let Some(oidc) = app.oidc.as_ref() else {
+        return Ok(Json(OidcStartResp { url: None }));
+    };
+
+    let url = oidc.authorize_url().await.kind(ErrorKind::ApiFail)?;
+
+    Ok(Json(OidcStartResp { url: Some(url) }))
+}
+
+pub async fn callback(
+    State(app): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(req): Json<OidcCallbackReq>,
+) -> JsonResult<OidcCallbackResp> {
+    let oidc = app
+        .oidc
+        .as_ref()
+        .ok_or("OIDC is not configured")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    app.throttle
+        .check(ip, None)
+        .await
+        .kind(ErrorKind::RateLimited)?;
+
+    let model = match identify(&app, oidc, &req).await {
+        Ok(model) => model,
+        Err((Json(e), username)) => {
+            if !matches!(e.error, ErrorKind::Internal) {
+                helper::login_failed(&app, ip, &username, None, "oidc").await;
+            }
+            return Err(Json(e));
+        }
+    };
+
+    let second_factor = Totp::find_by_id(model.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .is_some_and(|x| x.enabled);
+    if second_factor {
+        let challenge = helper::new_challenge(&app, model.id)?;
+        return Ok(Json(OidcCallbackResp::Challenge(challenge)));
+    }
+
+    helper::login_succeeded(&app, ip, model.id, "oidc").await;
+    let helper::Token { token, exp } = helper::new_token(&app, model.id, &headers).await?;
+
+    Ok(Json(OidcCallbackResp::Token(OidcCallbackToken {
+        token,
+        exp,
+    })))
+}
+
+/// Verify the redirect and find the user of the identity, creating it when
+/// allowed. Failures come with the username claim, empty if the provider
+/// vouched for none.
+async fn identify(
+    app: &AppState,
+    oidc: &OidcClient,
+    req: &OidcCallbackReq,
+) -> Result<user::Model, (AppError, String)> {
+    let claims = oidc
+        .exchange(&req.code, &req.state)
+        .await
+        .kind(ErrorKind::LoginFail)
+        .map_err(|e| (e, String::new()))?;
+    let username = oidc
+        .username(&claims)
+        .ok_or_else(|| format!("missing claim {}", oidc.config.username_claim))
+        .kind(ErrorKind::LoginFail)
+        .map_err(|e| (e, String::new()))?;
+
+    let (issuer, subject) = oidc
+        .identity(&claims)
+        .ok_or("missing claim sub")
+        .kind(ErrorKind::LoginFail)
+        .map_err(|e| (e, username.clone()))?;
+
+    helper::find_or_create_oidc_user(app, &issuer, &subject, &username, oidc.config.auto_create)
+        .await
+        .map_err(|e| (e, username))
+}
@@ -1,17 +1,14 @@
 use std::sync::Arc;
 
 use axum::{Json, extract::State};
-use pasetors::{
-    Local,
-    claims::{Claims, ClaimsValidationRules},
-    local,
-    token::UntrustedToken,
-    version4::V4,
-};
+use entity::{prelude::*, session};
+use pasetors::{Local, claims::ClaimsValidationRules, local, token::UntrustedToken, version4::V4};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{config::TOKEN_EXPIRATION_SECS, AppState, errors::*};
+use super::helper;
+use crate::{config::TOKEN_EXPIRATION_SECS, AppState, errors::*, utils::timestamp};
 
 #[derive(Debug, Clone, Deserialize)]
 #[typeshare]
@@ -34,30 +31,38 @@ pub async fn route(
 
     let token = local::decrypt(&app.key, &token, &ClaimsValidationRules::new(), None, None)
         .kind(ErrorKind::MalformedRequest)?;
-    let claim = token
+    let claims = token
         .payload_claims()
-        .map(|x| x.get_claim("uid").map(|x| x.as_u64()))
-        .flatten()
-        .flatten();
+        .ok_or("Cannot get claims")
+        .kind(ErrorKind::MalformedRequest)?;
 
-    let user_id = claim
+    let user_id = claims
+        .get_claim("uid")
+        .and_then(|x| x.as_i64())
         .ok_or("Cannot get user id")
+        .kind(ErrorKind::MalformedRequest)? as i32;
+    let token_id = claims
+        .get_claim("jti")
+        .and_then(|x| x.as_str())
+        .ok_or("Cannot get session id")
         .kind(ErrorKind::MalformedRequest)?;
-    let mut claim = Claims::new().kind(ErrorKind::Internal)?;
-    let expiration = std::time::Duration::from_secs(TOKEN_EXPIRATION_SECS);
-    claim
-        .set_expires_in(&expiration)
-        .kind(ErrorKind::Internal)?;
 
-    // safety:
-    // "uid" is not reserve
-    claim.add_additional("uid", user_id).unwrap();
+    let now = timestamp::now();
+    let session = Session::find()
+        .filter(session::Column::TokenId.eq(token_id))
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.gt(now))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("session has been revoked")
+        .kind(ErrorKind::Unauthorized)?;
 
-    // safety:
-    // "exp" must exists
-    let exp = claim.get_claim("exp").unwrap().as_str().unwrap().to_owned();
+    let mut session = session.into_active_model();
+    session.expires_at = ActiveValue::Set(now + TOKEN_EXPIRATION_SECS as i64);
+    session.update(&app.conn).await.kind(ErrorKind::Internal)?;
 
-    let token = local::encrypt(&app.key, &claim, None, None).kind(ErrorKind::Internal)?;
+    let helper::Token { token, exp } = helper::sign_token(&app, user_id, token_id)?;
 
     Ok(Json(RenewResp { token, exp }))
 }
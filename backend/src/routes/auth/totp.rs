@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::HeaderMap};
+use entity::prelude::*;
+use sea_orm::{EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::helper;
+use crate::{
+    AppState, errors::*, middlewares::client_ip::ClientIp, routes::totp::check_second_factor,
+    utils::login_throttle::Account,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TotpLoginReq {
+    /// Challenge returned by `/auth/login`
+    pub challenge: String,
+    /// Current code or a recovery code
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TotpLoginResp {
+    pub token: String,
+    pub exp: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(req): Json<TotpLoginReq>,
+) -> JsonResult<TotpLoginResp> {
+    let user_id = helper::verify_challenge(&app, &req.challenge)?;
+
+    let name = User::find_by_id(user_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .map(|x| x.name)
+        .unwrap_or_default();
+
+    app.throttle
+        .check(ip, Some(Account::User(user_id, &name)))
+        .await
+        .kind(ErrorKind::RateLimited)?;
+
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let model = Totp::find_by_id(user_id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.enabled)
+        .ok_or("two-factor authentication is not enabled")
+        .kind(ErrorKind::LoginFail)?;
+
+    if !check_second_factor(&app, &txn, model, &req.code).await? {
+        txn.rollback().await.kind(ErrorKind::Internal)?;
+        helper::login_failed(&app, ip, &name, Some(user_id), "totp").await;
+        return Err(Json(Error {
+            error: ErrorKind::LoginFail,
+            reason: "invalid code".to_owned(),
+        }));
+    }
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+    helper::login_succeeded(&app, ip, user_id, "totp").await;
+
+    let helper::Token { token, exp } = helper::new_token(&app, user_id, &headers).await?;
+
+    Ok(Json(TotpLoginResp { token, exp }))
+}
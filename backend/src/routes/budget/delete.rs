@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use protocol::AuditAction;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct BudgetDeleteReq {
+    pub user_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct BudgetDeleteResp {
+    pub deleted: bool,
+}
+
+/// Remove the budget, leaving the user unlimited. Spending history is kept.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<BudgetDeleteReq>,
+) -> JsonResult<BudgetDeleteResp> {
+    let res = Budget::delete_by_id(req.user_id)
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    log::info!("budget of user({}) is removed by {}", req.user_id, user_id);
+    if res.rows_affected == 1 {
+        Audit::new(AuditAction::BudgetDelete)
+            .actor(user_id)
+            .target(req.user_id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(BudgetDeleteResp {
+        deleted: res.rows_affected == 1,
+    }))
+}
@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use axum::{Router, middleware, routing::post};
+
+use crate::{AppState, middlewares::admin};
+
+mod delete;
+mod read;
+mod write;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/write", post(write::route))
+        .route("/delete", post(delete::route))
+        .route_layer(middleware::from_fn(admin::handle))
+        .route("/read", post(read::route))
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use protocol::{BudgetPeriod, UserRole};
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{budget, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct BudgetReadReq {
+    /// If omit will use the current user instead
+    pub user_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct BudgetReadResp {
+    /// None if the user has no budget
+    pub budget: Option<BudgetReadRespBudget>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct BudgetReadRespBudget {
+    pub limit: f64,
+    pub warn: Option<f64>,
+    pub period: BudgetPeriod,
+    pub window_days: i32,
+    /// Spent in the current period
+    pub spent: f64,
+    /// Start of the current period
+    pub since: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
+    Json(req): Json<BudgetReadReq>,
+) -> JsonResult<BudgetReadResp> {
+    let target_id = req.user_id.unwrap_or(user_id);
+
+    if target_id != user_id && role != UserRole::Admin {
+        return Err(Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: "only administrators can read budgets of other users".to_owned(),
+        }));
+    }
+
+    let Some(model) = Budget::find_by_id(target_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+    else {
+        return Ok(Json(BudgetReadResp { budget: None }));
+    };
+    let status = budget::status(&app.conn, target_id, timestamp::now())
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    Ok(Json(BudgetReadResp {
+        budget: Some(BudgetReadRespBudget {
+            limit: model.limit,
+            warn: model.warn,
+            period: model.period,
+            window_days: model.window_days,
+            spent: status.spent,
+            since: timestamp::format(status.since),
+        }),
+    }))
+}
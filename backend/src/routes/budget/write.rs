@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{budget, prelude::*};
+use protocol::{AuditAction, BudgetPeriod};
+use sea_orm::{ActiveValue, EntityTrait, sea_query::OnConflict};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct BudgetWriteReq {
+    pub user_id: i32,
+    /// Hard limit in USD, completions are refused once reached
+    pub limit: f64,
+    /// Soft limit in USD, a warning is streamed once reached
+    pub warn: Option<f64>,
+    pub period: BudgetPeriod,
+    /// Length of a rolling period, defaults to 30
+    pub window_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct BudgetWriteResp {
+    pub user_id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<BudgetWriteReq>,
+) -> JsonResult<BudgetWriteResp> {
+    let window_days = req.window_days.unwrap_or(30);
+    let negative = |x: f64| x.is_nan() || x < 0.0;
+    if negative(req.limit) || req.warn.is_some_and(negative) || window_days < 1 {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "limits must not be negative and window must be at least one day".to_owned(),
+        }));
+    }
+
+    User::find_by_id(req.user_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    let model = budget::ActiveModel {
+        user_id: ActiveValue::Set(req.user_id),
+        limit: ActiveValue::Set(req.limit),
+        warn: ActiveValue::Set(req.warn),
+        period: ActiveValue::Set(req.period),
+        window_days: ActiveValue::Set(window_days),
+    };
+    Budget::insert(model)
+        .on_conflict(
+            OnConflict::column(budget::Column::UserId)
+                .update_columns([
+                    budget::Column::Limit,
+                    budget::Column::Warn,
+                    budget::Column::Period,
+                    budget::Column::WindowDays,
+                ])
+                .to_owned(),
+        )
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    log::info!("budget of user({}) is set by {}", req.user_id, user_id);
+    Audit::new(AuditAction::BudgetUpdate)
+        .actor(user_id)
+        .target(req.user_id)
+        .detail(format!("{:?} {}", req.period, req.limit))
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(BudgetWriteResp {
+        user_id: req.user_id,
+    }))
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, chat_tag, folder, prelude::*, tag};
+use protocol::AuditAction;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, sea_query::OnConflict,
+};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+// keeps the `IN (...)` lists well below the SQLite variable limit
+const MAX_BULK_CHATS: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatBulkReq {
+    pub ids: Vec<i32>,
+    pub action: ChatBulkAction,
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+#[serde(tag = "t", content = "c", rename_all = "snake_case")]
+pub enum ChatBulkAction {
+    Move(ChatBulkActionMove),
+    /// Tag id to add
+    Tag(i32),
+    /// Tag id to remove
+    Untag(i32),
+    Pin,
+    Unpin,
+    Archive,
+    Unarchive,
+    Delete,
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatBulkActionMove {
+    /// Out of any folder when omitted
+    pub folder_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatBulkResp {
+    /// Number of chats changed, chats of other users are ignored
+    pub affected: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ChatBulkReq>,
+) -> JsonResult<ChatBulkResp> {
+    if req.ids.len() > MAX_BULK_CHATS {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: format!("at most {} chats at once", MAX_BULK_CHATS),
+        }));
+    }
+
+    let ids = Chat::find()
+        .select_only()
+        .column(chat::Column::Id)
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .filter(chat::Column::Id.is_in(req.ids))
+        .into_tuple::<i32>()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if ids.is_empty() {
+        return Ok(Json(ChatBulkResp { affected: 0 }));
+    }
+
+    let update = |column: chat::Column, value: sea_orm::Value| {
+        Chat::update_many()
+            .col_expr(column, sea_orm::sea_query::Expr::value(value))
+            .filter(chat::Column::Id.is_in(ids.clone()))
+    };
+
+    let affected = match req.action {
+        ChatBulkAction::Move(ChatBulkActionMove { folder_id }) => {
+            if let Some(folder_id) = folder_id {
+                owned(
+                    &app,
+                    Folder::find_by_id(folder_id).filter(folder::Column::OwnerId.eq(user_id)),
+                )
+                .await?;
+            }
+            update(chat::Column::FolderId, folder_id.into())
+                .exec(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .rows_affected
+        }
+        ChatBulkAction::Tag(tag_id) => {
+            owned(
+                &app,
+                Tag::find_by_id(tag_id).filter(tag::Column::OwnerId.eq(user_id)),
+            )
+            .await?;
+            ChatTag::insert_many(ids.iter().map(|&chat_id| chat_tag::ActiveModel {
+                chat_id: Set(chat_id),
+                tag_id: Set(tag_id),
+            }))
+            .on_conflict(
+                OnConflict::columns([chat_tag::Column::ChatId, chat_tag::Column::TagId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?
+        }
+        ChatBulkAction::Untag(tag_id) => {
+            ChatTag::delete_many()
+                .filter(chat_tag::Column::TagId.eq(tag_id))
+                .filter(chat_tag::Column::ChatId.is_in(ids.clone()))
+                .exec(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .rows_affected
+        }
+        ChatBulkAction::Pin | ChatBulkAction::Unpin => {
+            let pinned = matches!(req.action, ChatBulkAction::Pin);
+            update(chat::Column::Pinned, pinned.into())
+                .exec(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .rows_affected
+        }
+        ChatBulkAction::Archive | ChatBulkAction::Unarchive => {
+            let archived = matches!(req.action, ChatBulkAction::Archive);
+            update(chat::Column::Archived, archived.into())
+                .exec(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .rows_affected
+        }
+        ChatBulkAction::Delete => {
+            let affected = Chat::delete_many()
+                .filter(chat::Column::Id.is_in(ids.clone()))
+                .exec(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .rows_affected;
+            for &id in &ids {
+                app.chat.queue.clear(id);
+                Audit::new(AuditAction::ChatDelete)
+                    .actor(user_id)
+                    .target(id)
+                    .ip(ip)
+                    .record(&app.conn)
+                    .await;
+            }
+            affected
+        }
+    };
+
+    Ok(Json(ChatBulkResp {
+        affected: affected as u32,
+    }))
+}
+
+/// Fail unless `query` finds a row, which carries the ownership check.
+async fn owned<E: EntityTrait>(app: &AppState, query: sea_orm::Select<E>) -> Result<(), AppError> {
+    match query.one(&app.conn).await.kind(ErrorKind::Internal)? {
+        Some(_) => Ok(()),
+        None => Err(Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "folder or tag not found".to_owned(),
+        })),
+    }
+}
@@ -1,29 +1,43 @@
 use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
-use entity::{chat, prelude::*};
+use entity::{assistant, chat, file, prelude::*};
+use protocol::{FileMetadata, ModeKind, UserRole};
 use sea_orm::{ActiveValue::Set, EntityTrait};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId, utils::chat::ChatMode};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{chat::ChatMode, model_access},
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
 pub struct ChatCreateReq {
-    pub model_id: i32,
-    pub mode: ChatMode,
+    /// Defaults to the model of the assistant
+    pub model_id: Option<i32>,
+    /// Defaults to the mode of the assistant, or normal mode
+    pub mode: Option<ChatMode>,
+    /// Create the chat from an assistant of the user or a shared one
+    pub assistant_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
 #[typeshare]
 pub struct ChatCreateResp {
     pub id: i32,
+    /// Starter files of the assistant, copied into the chat so they can be
+    /// attached to the first message
+    pub files: Vec<FileMetadata>,
 }
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
     Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
     Json(req): Json<ChatCreateReq>,
 ) -> JsonResult<ChatCreateResp> {
     #[cfg(feature = "tracing")]
@@ -32,11 +46,54 @@ pub async fn route(
         info!(user_id = user_id, mode = ?req.mode, "creating chat");
     }
 
+    let assistant = match req.assistant_id {
+        Some(id) => Some(
+            Assistant::find_by_id(id)
+                .one(&app.conn)
+                .await
+                .kind(ErrorKind::Internal)?
+                .filter(|x| x.shared || x.owner_id == user_id)
+                .ok_or_else(|| {
+                    Json(Error {
+                        error: ErrorKind::ResourceNotFound,
+                        reason: "assistant not found".to_owned(),
+                    })
+                })?,
+        ),
+        None => None,
+    };
+
+    let model_id = req
+        .model_id
+        .or_else(|| assistant.as_ref().and_then(|x| x.model_id))
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::MalformedRequest,
+                reason: "model_id is required".to_owned(),
+            })
+        })?;
+    let mode = req
+        .mode
+        .map(ModeKind::from)
+        .or_else(|| assistant.as_ref().map(|x| x.mode))
+        .unwrap_or(ModeKind::Normal);
+
+    let access = model_access::load(&app.conn, user_id, role)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if !access.permits(model_id) {
+        return Err(Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "model not found".to_owned(),
+        }));
+    }
+
     let chat_id = Chat::insert(chat::ActiveModel {
         owner_id: Set(user_id),
-        model_id: Set(Some(req.model_id)),
+        model_id: Set(Some(model_id)),
         title: Set(None),
-        mode: Set(req.mode.into()),
+        mode: Set(mode),
+        assistant_id: Set(assistant.as_ref().map(|x| x.id)),
         ..Default::default()
     })
     .exec(&app.conn)
@@ -44,5 +101,59 @@ pub async fn route(
     .kind(ErrorKind::Internal)?
     .last_insert_id;
 
-    Ok(Json(ChatCreateResp { id: chat_id }))
+    let files = match assistant {
+        Some(assistant) => copy_files(&app, user_id, chat_id, assistant).await?,
+        None => Vec::new(),
+    };
+
+    Ok(Json(ChatCreateResp { id: chat_id, files }))
+}
+
+/// Copy the starter files of an assistant into a new chat, so that deleting
+/// the chat or the assistant leaves the other intact.
+async fn copy_files(
+    app: &AppState,
+    user_id: i32,
+    chat_id: i32,
+    assistant: assistant::Model,
+) -> Result<Vec<FileMetadata>, AppError> {
+    let mut files = Vec::with_capacity(assistant.files.0.len());
+    for metadata in assistant.files.0 {
+        let Some(source) = File::find_by_id(metadata.id)
+            .one(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?
+        else {
+            continue;
+        };
+        let Some(data) = app.blob.get_vectored(source.id).await else {
+            log::warn!(
+                "starter file {} of assistant {} is missing",
+                source.id,
+                assistant.id
+            );
+            continue;
+        };
+
+        let id = File::insert(file::ActiveModel {
+            chat_id: Set(Some(chat_id)),
+            owner_id: Set(Some(user_id)),
+            mime_type: Set(source.mime_type),
+            valid_until: Set(None),
+            ..Default::default()
+        })
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .last_insert_id;
+
+        let size = data.len();
+        app.blob
+            .insert(id, size, tokio_stream::iter(vec![bytes::Bytes::from(data)]))
+            .await
+            .kind(ErrorKind::Internal)?;
+
+        files.push(FileMetadata { id, ..metadata });
+    }
+    Ok(files)
 }
@@ -2,11 +2,17 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::chat;
+use protocol::AuditAction;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -23,6 +29,7 @@ pub struct ChatDeleteResp {
 pub async fn route(
     State(app): State<Arc<AppState>>,
     Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<ChatDeleteReq>,
 ) -> JsonResult<ChatDeleteResp> {
     let result = chat::Entity::delete_by_id(req.id)
@@ -32,6 +39,15 @@ pub async fn route(
         .kind(ErrorKind::Internal)?;
 
     let deleted = result.rows_affected > 0;
+    if deleted {
+        app.chat.queue.clear(req.id);
+        Audit::new(AuditAction::ChatDelete)
+            .actor(user_id)
+            .target(req.id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
 
     Ok(Json(ChatDeleteResp { deleted }))
 }
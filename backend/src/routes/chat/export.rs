@@ -0,0 +1,230 @@
+use std::{collections::HashMap, io::Write, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use entity::{chat, file, message, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QueryTrait};
+use serde::Deserialize;
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    config::{MAX_EXPORT_BYTES, MAX_EXPORT_CHATS},
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{branch, export},
+};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum ChatExportFormat {
+    /// Active branch as Markdown
+    Markdown,
+    /// Every message of every branch, lossless
+    Json,
+    /// Active branch as a single page with images inlined
+    Html,
+}
+
+impl ChatExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ChatExportFormat::Markdown => "md",
+            ChatExportFormat::Json => "json",
+            ChatExportFormat::Html => "html",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ChatExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ChatExportFormat::Json => "application/json",
+            ChatExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatExportReq {
+    /// Export every chat of the user as a zip when omitted, refused past
+    /// 1000 chats or 128 MiB of content
+    pub id: Option<i32>,
+    pub format: ChatExportFormat,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ChatExportReq>,
+) -> Result<Response, AppError> {
+    let chats = Chat::find()
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .apply_if(req.id, |q, id| q.filter(chat::Column::Id.eq(id)))
+        .order_by_asc(chat::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let mut headers = HeaderMap::new();
+    let (name, body) = match req.id {
+        Some(_) => {
+            let chat = chats.into_iter().next().ok_or_else(|| {
+                Json(Error {
+                    error: ErrorKind::ResourceNotFound,
+                    reason: "chat not found".to_owned(),
+                })
+            })?;
+            let name = format!("chat-{}.{}", chat.id, req.format.extension());
+            let body = render(&app, user_id, &chat, req.format).await?;
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(req.format.content_type()),
+            );
+            (name, body)
+        }
+        None => {
+            if chats.len() > MAX_EXPORT_CHATS {
+                return Err(too_large());
+            }
+            // only the archive and the chat at hand are held in memory
+            let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            let mut size = 0;
+            for chat in &chats {
+                let content = render(&app, user_id, chat, req.format).await?;
+                size += content.len();
+                if size > MAX_EXPORT_BYTES {
+                    return Err(too_large());
+                }
+                let name = export::file_name(chat, req.format.extension());
+                add_file(&mut writer, name, &content).kind(ErrorKind::Internal)?;
+            }
+            let body = writer.finish().kind(ErrorKind::Internal)?.into_inner();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/zip"),
+            );
+            ("llumen-chats.zip".to_owned(), body)
+        }
+    };
+
+    // names are ascii, titles only appear inside the archive
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", name))
+            .kind(ErrorKind::Internal)?,
+    );
+
+    Ok((headers, body).into_response())
+}
+
+async fn render(
+    app: &AppState,
+    user_id: i32,
+    chat: &chat::Model,
+    format: ChatExportFormat,
+) -> Result<Vec<u8>, AppError> {
+    let messages = Message::find()
+        .filter(message::Column::ChatId.eq(chat.id))
+        .order_by_asc(message::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    if let ChatExportFormat::Json = format {
+        return export::json(chat, &messages)
+            .map(String::into_bytes)
+            .kind(ErrorKind::Internal);
+    }
+
+    let nodes = messages
+        .iter()
+        .map(|x| (x.id, x.parent_id))
+        .collect::<Vec<_>>();
+    let path = branch::leaf(chat, &nodes)
+        .map(|leaf| branch::active_path(&nodes, leaf))
+        .unwrap_or_default();
+    let messages = messages
+        .into_iter()
+        .filter(|x| path.contains(&x.id))
+        .collect::<Vec<_>>();
+
+    let content = match format {
+        ChatExportFormat::Html => {
+            let images = images(
+                app,
+                user_id,
+                chat.id,
+                export::file_ids(messages.iter().map(|x| &x.inner)),
+            )
+            .await?;
+            export::html(chat, &messages, &images)
+        }
+        _ => export::markdown(chat, &messages),
+    };
+    Ok(content.into_bytes())
+}
+
+/// Data URIs of the images among `ids` the user can see.
+async fn images(
+    app: &AppState,
+    user_id: i32,
+    chat_id: i32,
+    ids: Vec<i32>,
+) -> Result<HashMap<i32, String>, AppError> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let files = File::find()
+        .filter(file::Column::Id.is_in(ids))
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let mut images = HashMap::new();
+    for file in files {
+        let visible = file.owner_id == Some(user_id)
+            || (file.owner_id.is_none() && file.chat_id == Some(chat_id));
+        let Some(mime) = file.mime_type.filter(|x| x.starts_with("image/")) else {
+            continue;
+        };
+        if !visible {
+            continue;
+        }
+        if let Some(reader) = app.blob.get(file.id) {
+            let data = STANDARD.encode(reader.as_ref());
+            images.insert(file.id, format!("data:{};base64,{}", mime, data));
+        }
+    }
+    Ok(images)
+}
+
+fn too_large() -> AppError {
+    Json(Error {
+        error: ErrorKind::MalformedRequest,
+        reason: format!(
+            "too much to export at once (at most {} chats, {} MiB), export chats one by one",
+            MAX_EXPORT_CHATS,
+            MAX_EXPORT_BYTES / 1024 / 1024
+        ),
+    })
+}
+
+fn add_file<W: Write + std::io::Seek>(
+    writer: &mut zip::ZipWriter<W>,
+    name: String,
+    content: &[u8],
+) -> zip::result::ZipResult<()> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    writer.start_file(name, options)?;
+    writer.write_all(content)?;
+    Ok(())
+}
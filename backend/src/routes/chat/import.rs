@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, file, message, prelude::*};
+use protocol::{AssistantChunk, FileKind, FileMetadata, MessageInner, ModeKind};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, DatabaseTransaction, EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{
+        branch,
+        import::{self, ImportSource, ImportedChat, ImportedContent},
+        search, timestamp,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatImportReq {
+    /// Export uploaded with `/file/upload`, it is removed after the import
+    pub file_id: i32,
+    /// Detected from the content when omitted
+    pub source: Option<ImportSource>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatImportResp {
+    pub chat_ids: Vec<i32>,
+    /// What could not be imported, one line per item
+    pub skipped: Vec<String>,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ChatImportReq>,
+) -> JsonResult<ChatImportResp> {
+    let upload = File::find_by_id(req.file_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.owner_id == Some(user_id) && x.chat_id.is_none())
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "file not found".to_owned(),
+            })
+        })?;
+    let data = app.blob.get_vectored(upload.id).await.ok_or_else(|| {
+        Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "file not found".to_owned(),
+        })
+    })?;
+
+    let value =
+        serde_json::from_slice::<serde_json::Value>(&data).kind(ErrorKind::MalformedRequest)?;
+    let source = req
+        .source
+        .or_else(|| import::detect(&value))
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::MalformedRequest,
+                reason: "unrecognized export format".to_owned(),
+            })
+        })?;
+    let import = import::parse(source, value);
+
+    let mut chat_ids = Vec::with_capacity(import.chats.len());
+    let mut blobs = Vec::new();
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+    for imported in import.chats {
+        let chat_id = insert_chat(&txn, user_id, imported, &mut blobs).await?;
+        chat_ids.push(chat_id);
+    }
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    for (id, data) in blobs {
+        let size = data.len();
+        app.blob
+            .insert(id, size, tokio_stream::iter(vec![bytes::Bytes::from(data)]))
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    // the export has served its purpose
+    File::delete_by_id(upload.id)
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if let Err(e) = app.blob.delete(upload.id) {
+        log::warn!("cannot remove imported file {}: {}", upload.id, e);
+    }
+
+    Ok(Json(ChatImportResp {
+        chat_ids,
+        skipped: import.skipped,
+    }))
+}
+
+/// Insert a chat with its messages, images are queued in `blobs` until the
+/// transaction is committed.
+async fn insert_chat(
+    txn: &DatabaseTransaction,
+    user_id: i32,
+    imported: ImportedChat,
+    blobs: &mut Vec<(i32, Vec<u8>)>,
+) -> Result<i32, AppError> {
+    let chat = chat::ActiveModel {
+        owner_id: Set(user_id),
+        model_id: Set(None),
+        title: Set(imported.title),
+        mode: Set(ModeKind::Normal),
+        ..Default::default()
+    }
+    .insert(txn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    let now = timestamp::now();
+    let mut ids: Vec<i32> = Vec::with_capacity(imported.messages.len());
+    for message in imported.messages {
+        // user attachments belong to the user, replies to the chat
+        let owner_id = match message.content {
+            ImportedContent::User(_) => Some(user_id),
+            ImportedContent::Assistant(_) => None,
+        };
+        let mut files = Vec::with_capacity(message.images.len());
+        for image in message.images {
+            let id = File::insert(file::ActiveModel {
+                chat_id: Set(Some(chat.id)),
+                owner_id: Set(owner_id),
+                mime_type: Set(Some(image.mime_type)),
+                valid_until: Set(None),
+                ..Default::default()
+            })
+            .exec(txn)
+            .await
+            .kind(ErrorKind::Internal)?
+            .last_insert_id;
+            blobs.push((id, image.data));
+            files.push(id);
+        }
+
+        let inner = match message.content {
+            ImportedContent::User(text) => MessageInner::User {
+                text,
+                files: files
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, id)| FileMetadata {
+                        name: format!("image-{}", i + 1),
+                        id,
+                        kind: FileKind::Image,
+                        dimensions: None,
+                    })
+                    .collect(),
+            },
+            ImportedContent::Assistant(mut chunks) => {
+                chunks.extend(files.into_iter().map(AssistantChunk::Image));
+                MessageInner::Assistant(chunks)
+            }
+        };
+
+        let model = message::ActiveModel {
+            chat_id: Set(chat.id),
+            inner: Set(inner),
+            created_at: Set(message.created_at.unwrap_or(now)),
+            parent_id: Set(message.parent.map(|x| ids[x])),
+            ..Default::default()
+        }
+        .insert(txn)
+        .await
+        .kind(ErrorKind::Internal)?;
+        ids.push(model.id);
+
+        if let Err(e) = search::index(txn, model.id, chat.id, &model.inner).await {
+            log::warn!("cannot index message {}: {}", model.id, e);
+        }
+    }
+
+    branch::set_leaf(txn, chat.id, imported.leaf.map(|x| ids[x]))
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(chat.id)
+}
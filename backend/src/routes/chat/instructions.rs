@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::chat;
+use protocol::CustomInstructions;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::chat as chat_utils};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatInstructionsReq {
+    pub chat_id: i32,
+    /// Replaces the instructions of the user for this chat, blank fields
+    /// leave them out. The chat follows the user again when omitted.
+    pub custom_instructions: Option<CustomInstructions>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatInstructionsResp {
+    pub wrote: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ChatInstructionsReq>,
+) -> JsonResult<ChatInstructionsResp> {
+    let instructions = req.custom_instructions.map(CustomInstructions::normalized);
+    if instructions
+        .as_ref()
+        .is_some_and(|x| !chat_utils::instructions_fit(x))
+    {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "custom instructions are too long".to_owned(),
+        }));
+    }
+
+    let res = chat::Entity::update_many()
+        .col_expr(chat::Column::CustomInstructions, Expr::value(instructions))
+        .filter(chat::Column::Id.eq(req.chat_id))
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(ChatInstructionsResp {
+        wrote: res.rows_affected > 0,
+    }))
+}
@@ -1,14 +1,21 @@
+mod bulk;
 mod create;
 mod delete;
+mod export;
 mod halt;
+mod import;
+mod instructions;
 mod paginate;
 mod read;
+mod search;
 pub(crate) mod sse;
 mod write;
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use axum::{Router, routing::post};
+use entity::{chat_tag, prelude::*};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
 
 use crate::AppState;
 
@@ -16,9 +23,33 @@ pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/sse", post(sse::route))
         .route("/delete", post(delete::route))
+        .route("/bulk", post(bulk::route))
         .route("/paginate", post(paginate::route))
         .route("/read", post(read::route))
+        .route("/search", post(search::route))
+        .route("/export", post(export::route))
         .route("/create", post(create::route))
+        .route("/import", post(import::route))
         .route("/halt", post(halt::route))
         .route("/write", post(write::route))
+        .route("/instructions", post(instructions::route))
+}
+
+/// Tag ids of each chat in `chat_ids`, untagged chats are left out.
+async fn tag_ids<C: ConnectionTrait>(
+    conn: &C,
+    chat_ids: Vec<i32>,
+) -> Result<HashMap<i32, Vec<i32>>, DbErr> {
+    let mut tags: HashMap<i32, Vec<i32>> = HashMap::new();
+    if chat_ids.is_empty() {
+        return Ok(tags);
+    }
+    for row in ChatTag::find()
+        .filter(chat_tag::Column::ChatId.is_in(chat_ids))
+        .all(conn)
+        .await?
+    {
+        tags.entry(row.chat_id).or_default().push(row.tag_id);
+    }
+    Ok(tags)
 }
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
-use entity::{chat, prelude::*};
-use sea_orm::{QueryOrder, QuerySelect, prelude::*};
+use entity::{chat, chat_tag, prelude::*};
+use sea_orm::{QueryOrder, QuerySelect, QueryTrait, Select, prelude::*, sea_query::Query};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
@@ -25,6 +25,8 @@ pub struct ChatPaginateReqLimit {
     pub id: Option<i32>,
     pub order: ChatPaginateReqOrder,
     pub limit: Option<u32>,
+    #[serde(default)]
+    pub filter: ChatPaginateReqFilter,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +36,22 @@ pub struct ChatPaginateReqLimit {
 pub struct ChatPaginateReqRange {
     pub upper: i32,
     pub lower: i32,
+    #[serde(default)]
+    pub filter: ChatPaginateReqFilter,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[typeshare]
+pub struct ChatPaginateReqFilter {
+    /// Only chats in this folder
+    pub folder_id: Option<i32>,
+    /// Only chats carrying this tag
+    pub tag_id: Option<i32>,
+    /// Only pinned (true) or unpinned (false) chats
+    pub pinned: Option<bool>,
+    /// List archived chats instead of the others
+    #[serde(default)]
+    pub archived: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +78,11 @@ pub struct ChatPaginateRespList {
     pub model_id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+    pub pinned: bool,
+    pub archived: bool,
 }
 
 pub async fn route(
@@ -69,14 +92,12 @@ pub async fn route(
 ) -> JsonResult<ChatPaginateResp> {
     let q = match req {
         ChatPaginateReq::Limit(limit) => {
-            let q = Chat::find()
-                .filter(chat::Column::OwnerId.eq(user_id))
-                .limit(
-                    limit
-                        .limit
-                        .map(|x| x.min(MAX_PAGINATE_LIMIT))
-                        .unwrap_or(MAX_PAGINATE_LIMIT) as u64,
-                );
+            let q = filtered(user_id, limit.filter).limit(
+                limit
+                    .limit
+                    .map(|x| x.min(MAX_PAGINATE_LIMIT))
+                    .unwrap_or(MAX_PAGINATE_LIMIT) as u64,
+            );
             let q = match (limit.order, limit.id) {
                 (ChatPaginateReqOrder::Gt, None) => q.order_by_asc(chat::Column::Id),
                 (ChatPaginateReqOrder::Gt, Some(id)) => q
@@ -89,23 +110,52 @@ pub async fn route(
             };
             q
         }
-        ChatPaginateReq::Range(range) => Chat::find()
-            .filter(chat::Column::OwnerId.eq(user_id))
+        ChatPaginateReq::Range(range) => filtered(user_id, range.filter)
             .filter(chat::Column::Id.gt(range.lower))
             .filter(chat::Column::Id.lt(range.upper))
             .limit(MAX_PAGINATE_LIMIT as u64),
     };
 
-    let list = q
-        .all(&app.conn)
+    let chats = q.all(&app.conn).await.kind(ErrorKind::Internal)?;
+    let mut tags = super::tag_ids(&app.conn, chats.iter().map(|x| x.id).collect())
         .await
-        .kind(ErrorKind::Internal)?
+        .kind(ErrorKind::Internal)?;
+
+    let list = chats
         .into_iter()
         .map(|x| ChatPaginateRespList {
+            tag_ids: tags.remove(&x.id).unwrap_or_default(),
             id: x.id,
             model_id: x.model_id,
             title: x.title,
+            folder_id: x.folder_id,
+            pinned: x.pinned,
+            archived: x.archived,
         })
         .collect();
     Ok(Json(ChatPaginateResp { list }))
 }
+
+/// Chats of `user_id` matching `filter`.
+fn filtered(user_id: i32, filter: ChatPaginateReqFilter) -> Select<chat::Entity> {
+    Chat::find()
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .filter(chat::Column::Archived.eq(filter.archived))
+        .apply_if(filter.folder_id, |q, id| {
+            q.filter(chat::Column::FolderId.eq(id))
+        })
+        .apply_if(filter.pinned, |q, pinned| {
+            q.filter(chat::Column::Pinned.eq(pinned))
+        })
+        .apply_if(filter.tag_id, |q, id| {
+            q.filter(
+                chat::Column::Id.in_subquery(
+                    Query::select()
+                        .column(chat_tag::Column::ChatId)
+                        .from(ChatTag)
+                        .and_where(chat_tag::Column::TagId.eq(id))
+                        .to_owned(),
+                ),
+            )
+        })
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::{chat, model};
+use protocol::CustomInstructions;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
@@ -22,6 +23,17 @@ pub struct ChatReadResp {
     pub model_id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<i32>,
+    pub tag_ids: Vec<i32>,
+    pub pinned: bool,
+    pub archived: bool,
+    /// Overrides the custom instructions of the user when present
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_instructions: Option<CustomInstructions>,
+    /// Assistant the chat was created from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assistant_id: Option<i32>,
 }
 
 pub async fn route(
@@ -43,11 +55,24 @@ pub async fn route(
         .kind(ErrorKind::Internal)?;
 
     match res {
-        Some((chat, model)) => Ok(Json(ChatReadResp {
-            model_id: model.map(|x| x.id),
-            mode: chat.mode.into(),
-            title: chat.title,
-        })),
+        Some((chat, model)) => {
+            let tag_ids = super::tag_ids(&app.conn, vec![chat.id])
+                .await
+                .kind(ErrorKind::Internal)?
+                .remove(&chat.id)
+                .unwrap_or_default();
+            Ok(Json(ChatReadResp {
+                model_id: model.map(|x| x.id),
+                mode: chat.mode.into(),
+                title: chat.title,
+                folder_id: chat.folder_id,
+                tag_ids,
+                pinned: chat.pinned,
+                archived: chat.archived,
+                custom_instructions: chat.custom_instructions,
+                assistant_id: chat.assistant_id,
+            }))
+        }
         None => {
             return Err(Json(Error {
                 error: ErrorKind::ResourceNotFound,
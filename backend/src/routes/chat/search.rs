@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use sea_orm::{ConnectionTrait, FromQueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    config::MAX_PAGINATE_LIMIT,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::search::{self, MATCH_END, MATCH_START},
+};
+
+// matching messages looked at, spread over at most `limit` chats
+const MAX_SEARCH_MESSAGES: u32 = 200;
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ChatSearchReq {
+    pub query: String,
+    /// Maximum number of chats, default to 50
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatSearchResp {
+    /// Best match first
+    pub list: Vec<ChatSearchRespList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatSearchRespList {
+    pub id: i32,
+    pub title: Option<String>,
+    pub matches: Vec<ChatSearchRespMatch>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ChatSearchRespMatch {
+    /// May sit on an inactive branch, `/message/switch` to it before jumping
+    pub message_id: i32,
+    /// Escaped HTML, matched words are wrapped in `<mark>`
+    pub snippet: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SearchRow {
+    chat_id: i32,
+    title: Option<String>,
+    message_id: i32,
+    snippet: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ChatSearchReq>,
+) -> JsonResult<ChatSearchResp> {
+    let Some(query) = search::match_query(&req.query) else {
+        return Ok(Json(ChatSearchResp { list: Vec::new() }));
+    };
+    let limit = req
+        .limit
+        .unwrap_or(MAX_PAGINATE_LIMIT)
+        .min(MAX_PAGINATE_LIMIT) as usize;
+
+    let rows = SearchRow::find_by_statement(Statement::from_sql_and_values(
+        app.conn.get_database_backend(),
+        "SELECT message.chat_id AS chat_id, chat.title AS title, message.id AS message_id, \
+         snippet(message_fts, 0, ?, ?, '…', 12) AS snippet \
+         FROM message_fts \
+         JOIN message ON message.id = message_fts.rowid \
+         JOIN chat ON chat.id = message.chat_id \
+         WHERE message_fts MATCH ? AND chat.owner_id = ? \
+         ORDER BY rank LIMIT ?",
+        [
+            MATCH_START.into(),
+            MATCH_END.into(),
+            query.into(),
+            user_id.into(),
+            MAX_SEARCH_MESSAGES.into(),
+        ],
+    ))
+    .all(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    // group by chat, chats keep the rank of their best message
+    let mut list: Vec<ChatSearchRespList> = Vec::new();
+    for row in rows {
+        let matched = ChatSearchRespMatch {
+            message_id: row.message_id,
+            snippet: search::highlight(&row.snippet),
+        };
+        match list.iter_mut().find(|x| x.id == row.chat_id) {
+            Some(chat) => chat.matches.push(matched),
+            None if list.len() < limit => list.push(ChatSearchRespList {
+                id: row.chat_id,
+                title: row.title,
+                matches: vec![matched],
+            }),
+            None => {}
+        }
+    }
+
+    Ok(Json(ChatSearchResp { list }))
+}
@@ -10,16 +10,21 @@ use axum::{
 };
 use entity::prelude::*;
 use futures_util::stream;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
-use tokio_stream::{Stream, StreamExt};
+use tokio_stream::{Stream, StreamExt, wrappers::BroadcastStream};
 use typeshare::typeshare;
 
 use crate::{
     AppState,
-    chat::{Cursor, converter::token_to_sse},
+    chat::{
+        Cursor,
+        converter::token_to_sse,
+        queue::{Change, Queued},
+    },
     errors::*,
     middlewares::auth::UserId,
+    utils::{branch, chat::ChatMode},
 };
 
 #[derive(Debug, Deserialize)]
@@ -83,6 +88,17 @@ pub struct SseReq {
 ///   assistant message.
 /// - `Title(String)`: an updated or generated title for the chat.
 /// - `Error(String)`: an error message to surface to the client.
+/// - `BudgetWarning(SseRespBudgetWarning)`: the user is close to their spending
+///   budget, sent right after `Start`.
+/// - `Queue(Vec<SseRespQueued>)`: messages waiting for the streaming reply,
+///   next first. Sent on subscribing when any are waiting, and whenever the
+///   queue changes; an empty list means the queue drained.
+/// - `QueueError(SseRespQueueError)`: a queued message was refused when its
+///   turn came, for instance once the budget is used up. It is gone from the
+///   queue.
+/// - `Compare(SseRespCompare)`: an event of one reply of a comparison, the
+///   replies of all compared models stream side by side. The wrapped event
+///   follows the rules above, per reply.
 ///
 /// Important: the client should treat text-bearing variants (`Token`,
 /// `Reasoning`, `DeepPlan`, `DeepStepToken`, `DeepStepReasoning`, `DeepReport`)
@@ -111,6 +127,10 @@ pub enum SseResp {
     DeepReport(String),
     Image(i32),
     UrlCitation(Vec<protocol::UrlCitation>),
+    BudgetWarning(SseRespBudgetWarning),
+    Queue(Vec<SseRespQueued>),
+    QueueError(SseRespQueueError),
+    Compare(SseRespCompare),
 }
 
 #[derive(Debug, Serialize)]
@@ -137,6 +157,65 @@ pub struct SseRespMessageComplete {
     pub version: i32,
 }
 
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SseRespBudgetWarning {
+    /// Spent in the current budget period, in USD
+    pub spent: f64,
+    pub limit: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SseRespCompare {
+    pub model_id: i32,
+    /// Id of the reply the event belongs to
+    pub id: i32,
+    pub event: Box<SseResp>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SseRespQueued {
+    pub id: i32,
+    pub model_id: i32,
+    pub mode: ChatMode,
+    pub text: String,
+    pub files: Vec<protocol::FileMetadata>,
+}
+
+impl From<Queued> for SseRespQueued {
+    fn from(value: Queued) -> Self {
+        Self {
+            id: value.id,
+            model_id: value.model_id,
+            mode: value.mode,
+            text: value.text,
+            files: value.files,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SseRespQueueError {
+    /// Id of the refused message in the queue
+    pub id: i32,
+    pub reason: String,
+}
+
+/// Current queue of the chat as an event.
+pub fn queue_event(app: &AppState, chat_id: i32) -> SseResp {
+    SseResp::Queue(
+        app.chat
+            .queue
+            .list(chat_id)
+            .into_iter()
+            .map(SseRespQueued::from)
+            .collect(),
+    )
+}
+
 #[derive(Debug, Serialize)]
 #[typeshare]
 pub struct SseStart {
@@ -175,11 +254,16 @@ pub async fn route(
         }));
     }
 
-    // last non-empty message
+    // last non-empty message of the active branch
+    let nodes = branch::nodes(&app.conn, req.id)
+        .await
+        .kind(ErrorKind::Internal)?;
+    let path = branch::leaf(&res, &nodes)
+        .map(|leaf| branch::active_path(&nodes, leaf))
+        .unwrap_or_default();
     let last_msg = Message::find()
-        .filter(entity::message::Column::ChatId.eq(req.id))
+        .filter(entity::message::Column::Id.is_in(path.into_iter().rev().take(2)))
         .order_by_desc(entity::message::Column::Id)
-        .limit(2)
         .all(&app.conn)
         .await
         .kind(ErrorKind::Internal)?
@@ -205,12 +289,40 @@ pub async fn route(
         })
         .unwrap_or(None);
 
+    let queued = match app.chat.queue.list(req.id).is_empty() {
+        true => None,
+        false => Some(Ok(Event::default()
+            .json_data(queue_event(&app, req.id))
+            .unwrap())),
+    };
+
     let stream = pipeline.clone().subscribe(req.id, cursor);
 
-    let st = stream::iter(initial_event).chain(stream.filter_map(|token| {
-        let event = token_to_sse(token)?;
+    // a lagged receiver missed changes, the current queue covers them
+    let chat_id = req.id;
+    let queue = BroadcastStream::new(app.chat.queue.subscribe()).filter_map(move |changed| {
+        let event = match changed {
+            Ok(Change::Updated(id)) if id != chat_id => return None,
+            Ok(Change::Refused {
+                chat_id: id,
+                id: queued_id,
+                reason,
+            }) if id == chat_id => SseResp::QueueError(SseRespQueueError {
+                id: queued_id,
+                reason,
+            }),
+            Ok(Change::Refused { .. }) => return None,
+            _ => queue_event(&app, chat_id),
+        };
         Some(Ok(Event::default().json_data(event).unwrap()))
-    }));
+    });
+
+    let st = stream::iter(initial_event.into_iter().chain(queued))
+        .chain(stream.filter_map(|token| {
+            let event = token_to_sse(token)?;
+            Some(Ok(Event::default().json_data(event).unwrap()))
+        }))
+        .merge(queue);
 
     Ok(Sse::new(st).keep_alive(KeepAlive::default().interval(Duration::from_secs(30))))
 }
@@ -13,6 +13,7 @@ use crate::{AppState, errors::*, middlewares::auth::UserId};
 pub struct ChatUpdateReq {
     pub chat_id: i32,
     pub title: Option<String>,
+    pub pinned: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -34,14 +35,19 @@ pub async fn route(
 
     // TODO: sync Mode with remote
 
-    if req.title.is_none() {
+    if req.title.is_none() && req.pinned.is_none() {
         return Ok(Json(ChatUpdateResp { wrote: false }));
     }
 
-    let title = req.title.unwrap();
+    let mut update = chat::Entity::update_many();
+    if let Some(title) = req.title {
+        update = update.col_expr(chat::Column::Title, title.into());
+    }
+    if let Some(pinned) = req.pinned {
+        update = update.col_expr(chat::Column::Pinned, pinned.into());
+    }
 
-    let res = chat::Entity::update_many()
-        .col_expr(chat::Column::Title, title.into())
+    let res = update
         .filter(
             chat::Column::Id
                 .eq(req.chat_id)
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    config::MAX_COMPARE_MODELS,
+    errors::{Error, ErrorKind, JsonResult, WithKind},
+    middlewares::auth::UserId,
+    routes::message::create::{
+        MessageCreateReqFile, attach_files, insert_user_message, owned_chat,
+    },
+    utils::{budget::BudgetExceeded, chat::ChatMode, model_access::ModelNotAllowed},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct CompareCreateReq {
+    pub chat_id: i32,
+    /// Models to answer side by side, the first one's reply is active until
+    /// a winner is picked
+    pub model_ids: Vec<i32>,
+    pub mode: ChatMode,
+    pub text: String,
+    pub files: Vec<MessageCreateReqFile>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct CompareCreateResp {
+    pub id: i32,
+    pub user_id: i32,
+    /// Replies in the order of the requested models
+    pub replies: Vec<CompareCreateReply>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct CompareCreateReply {
+    /// Id of the reply message
+    pub id: i32,
+    pub model_id: i32,
+}
+
+/// Send a message answered by several models side by side.
+///
+/// The replies stream over the SSE of the chat as `compare` events, each
+/// tagged with its model and reply.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<CompareCreateReq>,
+) -> JsonResult<CompareCreateResp> {
+    let mut distinct = req.model_ids.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() != req.model_ids.len()
+        || !(2..=MAX_COMPARE_MODELS).contains(&req.model_ids.len())
+    {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: format!(
+                "compare between 2 and {} distinct models",
+                MAX_COMPARE_MODELS
+            ),
+        }));
+    }
+
+    let chat = owned_chat(&app, user_id, req.chat_id).await?;
+
+    // a comparison is not queued, it would hold up the messages behind it
+    if app.chat.is_streaming(chat.id) || !app.chat.queue.list(chat.id).is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "a reply is still streaming in this chat".to_owned(),
+        }));
+    }
+
+    let files = attach_files(&app, user_id, chat.id, req.files).await?;
+
+    // continue the active branch
+    let user_msg = insert_user_message(&app, &chat, chat.leaf_id, req.text, files).await?;
+
+    let (comparison, replies) = match app
+        .chat
+        .compare(
+            user_id,
+            &req.model_ids,
+            req.mode,
+            &user_msg,
+            chat.leaf_id,
+            true,
+        )
+        .await
+    {
+        Ok(started) => started,
+        Err(e)
+            if e.downcast_ref::<BudgetExceeded>().is_some()
+                || e.downcast_ref::<ModelNotAllowed>().is_some() =>
+        {
+            let error = match e.downcast_ref::<BudgetExceeded>() {
+                Some(_) => ErrorKind::BudgetExceeded,
+                None => ErrorKind::ResourceNotFound,
+            };
+            return Err(Json(Error {
+                error,
+                reason: e.to_string(),
+            }));
+        }
+        Err(e) => return Err(e).kind(ErrorKind::ResourceNotFound),
+    };
+
+    Ok(Json(CompareCreateResp {
+        id: comparison.id,
+        user_id: user_msg.id,
+        replies: replies
+            .into_iter()
+            .zip(req.model_ids)
+            .map(|(reply, model_id)| CompareCreateReply {
+                id: reply.message_id,
+                model_id,
+            })
+            .collect(),
+    }))
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::{comparison, comparison_reply, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState, errors::*, middlewares::auth::UserId, routes::message::create::owned_chat,
+    utils::timestamp,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct CompareListReq {
+    pub chat_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct CompareListResp {
+    pub list: Vec<CompareList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct CompareList {
+    pub id: i32,
+    pub user_message_id: i32,
+    /// Reply picked as the canonical branch
+    pub winner_id: Option<i32>,
+    pub replies: Vec<CompareListReply>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct CompareListReply {
+    pub message_id: i32,
+    /// None once the model is deleted
+    pub model_id: Option<i32>,
+    /// In USD
+    pub cost: f32,
+    pub token_count: i32,
+    /// Time taken to generate the reply, none while it streams
+    pub latency_ms: Option<i32>,
+}
+
+/// List the comparisons of a chat, oldest first, with the cost and latency of
+/// each model.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<CompareListReq>,
+) -> JsonResult<CompareListResp> {
+    let chat = owned_chat(&app, user_id, req.chat_id).await?;
+
+    let comparisons = Comparison::find()
+        .filter(comparison::Column::ChatId.eq(chat.id))
+        .order_by_asc(comparison::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let mut replies: HashMap<i32, Vec<CompareListReply>> = HashMap::new();
+    for reply in ComparisonReply::find()
+        .filter(comparison_reply::Column::ComparisonId.is_in(comparisons.iter().map(|x| x.id)))
+        .order_by_asc(comparison_reply::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+    {
+        replies
+            .entry(reply.comparison_id)
+            .or_default()
+            .push(CompareListReply {
+                message_id: reply.message_id,
+                model_id: reply.model_id,
+                cost: reply.cost,
+                token_count: reply.token_count,
+                latency_ms: reply.latency_ms.map(|x| x as i32),
+            });
+    }
+
+    let list = comparisons
+        .into_iter()
+        .map(|x| CompareList {
+            replies: replies.remove(&x.id).unwrap_or_default(),
+            id: x.id,
+            user_message_id: x.user_message_id,
+            winner_id: x.winner_id,
+            created_at: timestamp::format(x.created_at),
+        })
+        .collect();
+
+    Ok(Json(CompareListResp { list }))
+}
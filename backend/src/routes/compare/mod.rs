@@ -0,0 +1,16 @@
+mod create;
+mod list;
+mod pick;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use crate::AppState;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/create", post(create::route))
+        .route("/list", post(list::route))
+        .route("/pick", post(pick::route))
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{comparison, comparison_reply, prelude::*};
+use sea_orm::{ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState, errors::*, middlewares::auth::UserId, routes::message::create::owned_chat,
+    utils::branch,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ComparePickReq {
+    pub id: i32,
+    /// Id of the winning reply
+    pub message_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ComparePickResp {
+    /// Last message of the now active branch
+    pub leaf_id: i32,
+}
+
+/// Pick the winning reply of a comparison and make its branch active.
+///
+/// The other replies stay around as sibling branches.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ComparePickReq>,
+) -> JsonResult<ComparePickResp> {
+    let not_found = || {
+        Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "comparison not found".to_owned(),
+        })
+    };
+
+    let comparison = Comparison::find_by_id(req.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(not_found)?;
+    let chat = owned_chat(&app, user_id, comparison.chat_id)
+        .await
+        .map_err(|_| not_found())?;
+
+    ComparisonReply::find()
+        .filter(comparison_reply::Column::ComparisonId.eq(comparison.id))
+        .filter(comparison_reply::Column::MessageId.eq(req.message_id))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("reply is not part of the comparison")
+        .kind(ErrorKind::MalformedRequest)?;
+
+    Comparison::update(comparison::ActiveModel {
+        id: Set(comparison.id),
+        winner_id: Set(Some(req.message_id)),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    let nodes = branch::nodes(&app.conn, chat.id)
+        .await
+        .kind(ErrorKind::Internal)?;
+    let leaf_id = branch::newest_leaf(&nodes, req.message_id);
+    branch::set_leaf(&app.conn, chat.id, Some(leaf_id))
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(ComparePickResp { leaf_id }))
+}
@@ -9,7 +9,7 @@ use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use crate::AppState;
 use crate::errors::{AppError, Error, ErrorKind, WithKind};
 use crate::middlewares::auth::UserId;
-use crate::utils::blob::MmapStream;
+use crate::utils::blob::{MmapStream, Reader};
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
@@ -55,14 +55,19 @@ pub async fn route(
         reason: "File data not found".to_owned(),
     }))?;
 
+    Ok(respond(file.mime_type.as_deref(), reader))
+}
+
+/// Stream a stored file as the response body.
+pub(crate) fn respond(mime_type: Option<&str>, reader: Reader) -> Response {
     let content_length = reader.as_ref().len();
 
     let mut headers = axum::http::HeaderMap::new();
 
-    if let Some(mime) = file.mime_type {
+    if let Some(mime) = mime_type {
         headers.insert(
             axum::http::header::CONTENT_TYPE,
-            axum::http::HeaderValue::from_str(mime.as_str()).unwrap(),
+            axum::http::HeaderValue::from_str(mime).unwrap(),
         );
     }
 
@@ -74,5 +79,5 @@ pub async fn route(
     let stream: MmapStream = reader.into();
     let body = axum::body::Body::from_stream(stream);
 
-    Ok((headers, body).into_response())
+    (headers, body).into_response()
 }
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{folder, prelude::*};
+use sea_orm::{ActiveValue, EntityTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct FolderCreateReq {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct FolderCreateResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<FolderCreateReq>,
+) -> JsonResult<FolderCreateResp> {
+    let name = super::check_name(&app.conn, user_id, None, &req.name).await?;
+
+    let id = Folder::insert(folder::ActiveModel {
+        owner_id: ActiveValue::Set(user_id),
+        name: ActiveValue::Set(name),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    Ok(Json(FolderCreateResp { id }))
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, folder, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, TransactionTrait, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct FolderDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct FolderDeleteResp {
+    pub deleted: bool,
+}
+
+/// Chats of the folder are kept and moved out of it.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<FolderDeleteReq>,
+) -> JsonResult<FolderDeleteResp> {
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let res = Folder::delete_many()
+        .filter(folder::Column::Id.eq(req.id))
+        .filter(folder::Column::OwnerId.eq(user_id))
+        .exec(&txn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let deleted = res.rows_affected > 0;
+    if deleted {
+        Chat::update_many()
+            .col_expr(chat::Column::FolderId, Expr::value(Option::<i32>::None))
+            .filter(chat::Column::FolderId.eq(req.id))
+            .exec(&txn)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    Ok(Json(FolderDeleteResp { deleted }))
+}
@@ -0,0 +1,66 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, folder, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct FolderListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct FolderListResp {
+    pub list: Vec<FolderList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct FolderList {
+    pub id: i32,
+    pub name: String,
+    /// Number of chats in the folder, archived ones included
+    pub chat_count: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<FolderListReq>,
+) -> JsonResult<FolderListResp> {
+    let folders = Folder::find()
+        .filter(folder::Column::OwnerId.eq(user_id))
+        .order_by_asc(folder::Column::Name)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let counts: HashMap<i32, i64> = Chat::find()
+        .select_only()
+        .column(chat::Column::FolderId)
+        .column_as(chat::Column::Id.count(), "count")
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .filter(chat::Column::FolderId.is_not_null())
+        .group_by(chat::Column::FolderId)
+        .into_tuple::<(i32, i64)>()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .collect();
+
+    let list = folders
+        .into_iter()
+        .map(|x| FolderList {
+            chat_count: counts.get(&x.id).copied().unwrap_or_default() as u32,
+            id: x.id,
+            name: x.name,
+        })
+        .collect();
+
+    Ok(Json(FolderListResp { list }))
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, routing::post};
+use entity::{folder, prelude::*};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+use crate::{AppState, errors::*};
+
+mod create;
+mod delete;
+mod list;
+mod write;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/create", post(create::route))
+        .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+        .route("/write", post(write::route))
+}
+
+/// Trim `name` and make sure no other folder of the user has it.
+async fn check_name<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    id: Option<i32>,
+    name: &str,
+) -> Result<String, AppError> {
+    let name = name.trim().to_owned();
+    let mut query = Folder::find()
+        .filter(folder::Column::OwnerId.eq(user_id))
+        .filter(folder::Column::Name.eq(&name));
+    if let Some(id) = id {
+        query = query.filter(folder::Column::Id.ne(id));
+    }
+    let taken = query.one(conn).await.kind(ErrorKind::Internal)?.is_some();
+    if name.is_empty() || taken {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "folder name is empty or taken".to_owned(),
+        }));
+    }
+    Ok(name)
+}
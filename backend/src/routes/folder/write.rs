@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{folder, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct FolderWriteReq {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct FolderWriteResp {
+    pub wrote: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<FolderWriteReq>,
+) -> JsonResult<FolderWriteResp> {
+    let name = super::check_name(&app.conn, user_id, Some(req.id), &req.name).await?;
+
+    let res = Folder::update_many()
+        .col_expr(folder::Column::Name, name.into())
+        .filter(folder::Column::Id.eq(req.id))
+        .filter(folder::Column::OwnerId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(FolderWriteResp {
+        wrote: res.rows_affected > 0,
+    }))
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{prelude::*, user_group};
+use protocol::AuditAction;
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct GroupCreateReq {
+    pub name: String,
+    /// User ids
+    pub members: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct GroupCreateResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<GroupCreateReq>,
+) -> JsonResult<GroupCreateResp> {
+    let name = req.name.trim().to_owned();
+    if name.is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "group name must not be empty".to_owned(),
+        }));
+    }
+
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let taken = UserGroup::find()
+        .filter(user_group::Column::Name.eq(&name))
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .is_some();
+    if taken {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "group name is taken".to_owned(),
+        }));
+    }
+
+    let id = UserGroup::insert(user_group::ActiveModel {
+        name: ActiveValue::Set(name.clone()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    super::set_members(&txn, id, req.members)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    Audit::new(AuditAction::GroupCreate)
+        .actor(user_id)
+        .target(id)
+        .detail(name)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(GroupCreateResp { id }))
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use protocol::AuditAction;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct GroupDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct GroupDeleteResp {
+    pub deleted: bool,
+}
+
+/// Members and model allowlist entries of the group are removed with it.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<GroupDeleteReq>,
+) -> JsonResult<GroupDeleteResp> {
+    let res = UserGroup::delete_by_id(req.id)
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let deleted = res.rows_affected == 1;
+    if deleted {
+        Audit::new(AuditAction::GroupDelete)
+            .actor(user_id)
+            .target(req.id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(GroupDeleteResp { deleted }))
+}
@@ -0,0 +1,62 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct GroupListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct GroupListResp {
+    pub list: Vec<GroupList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct GroupList {
+    pub id: i32,
+    pub name: String,
+    /// User ids
+    pub members: Vec<i32>,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(_)): Extension<UserId>,
+    Json(_): Json<GroupListReq>,
+) -> JsonResult<GroupListResp> {
+    let groups = UserGroup::find()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let mut members: HashMap<i32, Vec<i32>> = HashMap::new();
+    for member in UserGroupMember::find()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+    {
+        members
+            .entry(member.group_id)
+            .or_default()
+            .push(member.user_id);
+    }
+
+    let list = groups
+        .into_iter()
+        .map(|x| GroupList {
+            members: members.remove(&x.id).unwrap_or_default(),
+            id: x.id,
+            name: x.name,
+        })
+        .collect();
+
+    Ok(Json(GroupListResp { list }))
+}
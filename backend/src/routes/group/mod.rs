@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use axum::{Router, middleware, routing::post};
+use entity::{prelude::*, user, user_group_member};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect};
+
+use crate::{AppState, middlewares::admin};
+
+mod create;
+mod delete;
+mod list;
+mod write;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/create", post(create::route))
+        .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+        .route("/write", post(write::route))
+        .route_layer(middleware::from_fn(admin::handle))
+}
+
+/// Replace the members of `group_id`, ignoring ids of missing users.
+async fn set_members<C: ConnectionTrait>(
+    conn: &C,
+    group_id: i32,
+    members: Vec<i32>,
+) -> Result<(), DbErr> {
+    UserGroupMember::delete_many()
+        .filter(user_group_member::Column::GroupId.eq(group_id))
+        .exec(conn)
+        .await?;
+
+    let members = User::find()
+        .select_only()
+        .column(user::Column::Id)
+        .filter(user::Column::Id.is_in(members))
+        .into_tuple::<i32>()
+        .all(conn)
+        .await?;
+    if members.is_empty() {
+        return Ok(());
+    }
+
+    UserGroupMember::insert_many(members.into_iter().map(|user_id| {
+        user_group_member::ActiveModel {
+            group_id: sea_orm::ActiveValue::Set(group_id),
+            user_id: sea_orm::ActiveValue::Set(user_id),
+        }
+    }))
+    .exec(conn)
+    .await?;
+    Ok(())
+}
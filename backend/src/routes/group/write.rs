@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{prelude::*, user_group};
+use protocol::AuditAction;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct GroupWriteReq {
+    pub id: i32,
+    pub name: Option<String>,
+    /// Replaces all members when present
+    pub members: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct GroupWriteResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<GroupWriteReq>,
+) -> JsonResult<GroupWriteResp> {
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let group = UserGroup::find_by_id(req.id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    if let Some(name) = req.name {
+        let name = name.trim().to_owned();
+        let taken = UserGroup::find()
+            .filter(user_group::Column::Name.eq(&name))
+            .filter(user_group::Column::Id.ne(req.id))
+            .one(&txn)
+            .await
+            .kind(ErrorKind::Internal)?
+            .is_some();
+        if name.is_empty() || taken {
+            return Err(Json(Error {
+                error: ErrorKind::MalformedRequest,
+                reason: "group name is empty or taken".to_owned(),
+            }));
+        }
+
+        let mut active_model = group.into_active_model();
+        active_model.name = ActiveValue::Set(name);
+        active_model.update(&txn).await.kind(ErrorKind::Internal)?;
+    }
+
+    if let Some(members) = req.members {
+        super::set_members(&txn, req.id, members)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    Audit::new(AuditAction::GroupUpdate)
+        .actor(user_id)
+        .target(req.id)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(GroupWriteResp { id: req.id }))
+}
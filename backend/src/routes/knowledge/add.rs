@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{file, knowledge_chunk, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    config::MAX_KNOWLEDGE_PASSAGES,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{budget, knowledge, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeAddReq {
+    pub knowledge_id: i32,
+    /// Id returned by `/file/upload`
+    pub file_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeAddResp {
+    pub id: i32,
+    /// Number of passages the document was split into
+    pub passages: u32,
+}
+
+/// Add an uploaded document to a knowledge base.
+///
+/// The text of the document is extracted and embedded, the upload itself is
+/// removed afterwards. Embedding counts toward the budget of the user.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<KnowledgeAddReq>,
+) -> JsonResult<KnowledgeAddResp> {
+    super::owned(&app.conn, user_id, req.knowledge_id).await?;
+
+    let upload = File::find_by_id(req.file_id)
+        .filter(file::Column::OwnerId.eq(user_id))
+        .filter(file::Column::ChatId.is_null())
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    let data = match upload {
+        Some(_) => app.blob.get_vectored(req.file_id).await,
+        None => None,
+    };
+    let Some(data) = data else {
+        return Err(Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "file not found".to_owned(),
+        }));
+    };
+
+    let pages = tokio::task::spawn_blocking(move || knowledge::extract(&data))
+        .await
+        .kind(ErrorKind::Internal)?
+        .kind(ErrorKind::MalformedRequest)?;
+    let passages = knowledge::chunk(pages);
+    if passages.is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "no text found in the document".to_owned(),
+        }));
+    }
+    let count = passages.len() as u32;
+
+    let stored = KnowledgeChunk::find()
+        .filter(knowledge_chunk::Column::KnowledgeId.eq(req.knowledge_id))
+        .count(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if stored + count as u64 > MAX_KNOWLEDGE_PASSAGES {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: format!(
+                "a knowledge base holds at most {} passages, this document has {} and {} are stored",
+                MAX_KNOWLEDGE_PASSAGES, count, stored
+            ),
+        }));
+    }
+
+    if let Some(exceeded) = budget::exceeded(&app.conn, user_id, timestamp::now())
+        .await
+        .kind(ErrorKind::Internal)?
+    {
+        return Err(Json(Error {
+            error: ErrorKind::BudgetExceeded,
+            reason: exceeded.to_string(),
+        }));
+    }
+
+    let name = req.name.trim();
+    let (model, cost) = knowledge::ingest(
+        &app.conn,
+        &app.chat.embedder,
+        req.knowledge_id,
+        if name.is_empty() { "document" } else { name },
+        passages,
+    )
+    .await
+    .kind(ErrorKind::ApiFail)?;
+    budget::record(&app.conn, user_id, cost, timestamp::now())
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    File::delete_by_id(req.file_id)
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if let Err(e) = app.blob.delete(req.file_id) {
+        log::warn!("cannot remove uploaded document {}: {}", req.file_id, e);
+    }
+
+    Ok(Json(KnowledgeAddResp {
+        id: model.id,
+        passages: count,
+    }))
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{knowledge, prelude::*};
+use sea_orm::{ActiveValue, EntityTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeCreateReq {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeCreateResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<KnowledgeCreateReq>,
+) -> JsonResult<KnowledgeCreateResp> {
+    let name = super::check_name(&app.conn, user_id, None, &req.name).await?;
+
+    let id = Knowledge::insert(knowledge::ActiveModel {
+        owner_id: ActiveValue::Set(user_id),
+        name: ActiveValue::Set(name),
+        created_at: ActiveValue::Set(timestamp::now()),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    Ok(Json(KnowledgeCreateResp { id }))
+}
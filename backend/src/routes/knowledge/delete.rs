@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{knowledge, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeDeleteResp {
+    pub deleted: bool,
+}
+
+/// Documents of the knowledge base and their passages are deleted with it.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<KnowledgeDeleteReq>,
+) -> JsonResult<KnowledgeDeleteResp> {
+    let res = Knowledge::delete_many()
+        .filter(knowledge::Column::Id.eq(req.id))
+        .filter(knowledge::Column::OwnerId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(KnowledgeDeleteResp {
+        deleted: res.rows_affected > 0,
+    }))
+}
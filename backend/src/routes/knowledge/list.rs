@@ -0,0 +1,80 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::{knowledge, knowledge_file, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeListResp {
+    pub list: Vec<KnowledgeList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeList {
+    pub id: i32,
+    pub name: String,
+    pub files: Vec<KnowledgeListFile>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeListFile {
+    pub id: i32,
+    pub name: String,
+    pub created_at: String,
+}
+
+/// List the knowledge bases of the user by name, with their documents.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<KnowledgeListReq>,
+) -> JsonResult<KnowledgeListResp> {
+    let knowledges = Knowledge::find()
+        .filter(knowledge::Column::OwnerId.eq(user_id))
+        .order_by_asc(knowledge::Column::Name)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let mut files: HashMap<i32, Vec<KnowledgeListFile>> = HashMap::new();
+    for file in KnowledgeFile::find()
+        .filter(knowledge_file::Column::KnowledgeId.is_in(knowledges.iter().map(|x| x.id)))
+        .order_by_asc(knowledge_file::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+    {
+        files
+            .entry(file.knowledge_id)
+            .or_default()
+            .push(KnowledgeListFile {
+                id: file.id,
+                name: file.name,
+                created_at: timestamp::format(file.created_at),
+            });
+    }
+
+    let list = knowledges
+        .into_iter()
+        .map(|x| KnowledgeList {
+            files: files.remove(&x.id).unwrap_or_default(),
+            id: x.id,
+            name: x.name,
+            created_at: timestamp::format(x.created_at),
+        })
+        .collect();
+
+    Ok(Json(KnowledgeListResp { list }))
+}
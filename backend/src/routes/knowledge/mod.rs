@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{Json, Router, routing::post};
+use entity::{knowledge, prelude::*};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+use crate::{AppState, config::MAX_KNOWLEDGE_NAME_LEN, errors::*};
+
+mod add;
+mod create;
+mod delete;
+mod list;
+mod remove;
+mod write;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/add", post(add::route))
+        .route("/create", post(create::route))
+        .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+        .route("/remove", post(remove::route))
+        .route("/write", post(write::route))
+}
+
+/// Load a knowledge base of the user.
+async fn owned<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    id: i32,
+) -> Result<knowledge::Model, AppError> {
+    Knowledge::find_by_id(id)
+        .filter(knowledge::Column::OwnerId.eq(user_id))
+        .one(conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "knowledge base not found".to_owned(),
+            })
+        })
+}
+
+/// Trim `name` and make sure no other knowledge base of the user has it.
+async fn check_name<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    id: Option<i32>,
+    name: &str,
+) -> Result<String, AppError> {
+    let name = name.trim().to_owned();
+    if name.is_empty() || name.chars().count() > MAX_KNOWLEDGE_NAME_LEN {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "knowledge base name is empty or too long".to_owned(),
+        }));
+    }
+
+    let mut query = Knowledge::find()
+        .filter(knowledge::Column::OwnerId.eq(user_id))
+        .filter(knowledge::Column::Name.eq(&name));
+    if let Some(id) = id {
+        query = query.filter(knowledge::Column::Id.ne(id));
+    }
+    if query.one(conn).await.kind(ErrorKind::Internal)?.is_some() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "knowledge base name is taken".to_owned(),
+        }));
+    }
+    Ok(name)
+}
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+/// Remove a document from its knowledge base.
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeRemoveReq {
+    /// Id of the document, as listed by `/knowledge/list`
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeRemoveResp {
+    pub removed: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<KnowledgeRemoveReq>,
+) -> JsonResult<KnowledgeRemoveResp> {
+    let Some(file) = KnowledgeFile::find_by_id(req.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+    else {
+        return Ok(Json(KnowledgeRemoveResp { removed: false }));
+    };
+    super::owned(&app.conn, user_id, file.knowledge_id).await?;
+
+    let res = KnowledgeFile::delete_by_id(file.id)
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(KnowledgeRemoveResp {
+        removed: res.rows_affected > 0,
+    }))
+}
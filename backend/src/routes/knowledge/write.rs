@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{knowledge, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct KnowledgeWriteReq {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct KnowledgeWriteResp {
+    pub wrote: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<KnowledgeWriteReq>,
+) -> JsonResult<KnowledgeWriteResp> {
+    let name = super::check_name(&app.conn, user_id, Some(req.id), &req.name).await?;
+
+    let res = Knowledge::update_many()
+        .col_expr(knowledge::Column::Name, name.into())
+        .filter(knowledge::Column::Id.eq(req.id))
+        .filter(knowledge::Column::OwnerId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(KnowledgeWriteResp {
+        wrote: res.rows_affected > 0,
+    }))
+}
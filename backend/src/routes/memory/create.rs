@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{budget, memory, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MemoryCreateReq {
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MemoryCreateResp {
+    /// Id of the existing memory when the same content is already stored
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MemoryCreateReq>,
+) -> JsonResult<MemoryCreateResp> {
+    if !memory::valid(&req.content) {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "memory is empty or too long".to_owned(),
+        }));
+    }
+
+    // embedding is paid for like replies
+    if let Some(exceeded) = budget::exceeded(&app.conn, user_id, timestamp::now())
+        .await
+        .kind(ErrorKind::Internal)?
+    {
+        return Err(Json(Error {
+            error: ErrorKind::BudgetExceeded,
+            reason: exceeded.to_string(),
+        }));
+    }
+
+    let (memory, cost) = app
+        .chat
+        .memory
+        .remember(&app.conn, user_id, None, &req.content)
+        .await
+        .kind(ErrorKind::ApiFail)?;
+    budget::record(&app.conn, user_id, cost, timestamp::now())
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(MemoryCreateResp { id: memory.id }))
+}
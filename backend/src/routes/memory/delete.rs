@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{memory, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+/// Delete the given memories, or all memories of the user when `ids` is
+/// omitted.
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MemoryDeleteReq {
+    pub ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MemoryDeleteResp {
+    pub deleted: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MemoryDeleteReq>,
+) -> JsonResult<MemoryDeleteResp> {
+    let mut query = Memory::delete_many().filter(memory::Column::OwnerId.eq(user_id));
+    if let Some(ids) = req.ids {
+        query = query.filter(memory::Column::Id.is_in(ids));
+    }
+    let res = query.exec(&app.conn).await.kind(ErrorKind::Internal)?;
+
+    Ok(Json(MemoryDeleteResp {
+        deleted: res.rows_affected as u32,
+    }))
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{memory, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MemoryListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MemoryListResp {
+    pub list: Vec<MemoryList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MemoryList {
+    pub id: i32,
+    pub content: String,
+    /// Chat the model remembered it in, absent when added by the user or
+    /// when the chat is deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<i32>,
+    pub created_at: String,
+}
+
+/// List the memories of the user, newest first.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<MemoryListReq>,
+) -> JsonResult<MemoryListResp> {
+    let list = Memory::find()
+        .filter(memory::Column::OwnerId.eq(user_id))
+        .order_by_desc(memory::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .map(|x| MemoryList {
+            id: x.id,
+            content: x.content,
+            chat_id: x.chat_id,
+            created_at: timestamp::format(x.created_at),
+        })
+        .collect();
+
+    Ok(Json(MemoryListResp { list }))
+}
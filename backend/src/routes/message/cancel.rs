@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::create::owned_chat;
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+/// Remove a message from the queue of a chat before its turn comes.
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MessageCancelReq {
+    pub chat_id: i32,
+    /// Id returned in the `queued` response of `/message/create`
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MessageCancelResp {
+    /// False when the message already started or was cancelled before
+    pub cancelled: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MessageCancelReq>,
+) -> JsonResult<MessageCancelResp> {
+    let chat = owned_chat(&app, user_id, req.chat_id).await?;
+
+    let cancelled = app.chat.queue.cancel(chat.id, req.id).is_some();
+
+    Ok(Json(MessageCancelResp { cancelled }))
+}
@@ -2,16 +2,21 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::file::{Column as FileColumn, Entity as File};
-use protocol::{FileMetadata, MessageInner};
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter};
+use entity::{chat, message};
+use protocol::FileMetadata;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::{
     AppState,
-    errors::{ErrorKind, JsonResult, WithKind},
+    chat::{
+        ChatBusy,
+        queue::{Admission, Queued},
+    },
+    errors::{AppError, Error, ErrorKind, JsonResult, WithKind},
     middlewares::auth::UserId,
-    utils::chat::ChatMode,
+    utils::{budget::BudgetExceeded, chat::ChatMode},
 };
 
 #[derive(Debug, Deserialize)]
@@ -33,7 +38,18 @@ pub struct MessageCreateReq {
 
 #[derive(Debug, Serialize)]
 #[typeshare]
-pub struct MessageCreateResp {
+#[serde(tag = "t", content = "c", rename_all = "snake_case")]
+pub enum MessageCreateResp {
+    Created(MessageCreated),
+    /// Id in the queue of the chat, the message waits for the streaming reply
+    /// and is answered after the messages queued before it
+    Queued(i32),
+}
+
+/// Ids of the reply and the user message.
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MessageCreated {
     pub id: i32,
     pub user_id: i32,
 }
@@ -43,16 +59,109 @@ pub async fn route(
     Extension(UserId(user_id)): Extension<UserId>,
     Json(req): Json<MessageCreateReq>,
 ) -> JsonResult<MessageCreateResp> {
-    let file_ids: Vec<i32> = req.files.iter().map(|f| f.id).collect();
+    let chat = owned_chat(&app, user_id, req.chat_id).await?;
+    let files = attach_files(&app, user_id, req.chat_id, req.files).await?;
+
+    let message = Queued {
+        id: 0,
+        user_id,
+        model_id: req.model_id,
+        mode: req.mode,
+        text: req.text,
+        files,
+    };
+    let message = match app
+        .chat
+        .queue
+        .admit(chat.id, || app.chat.is_streaming(chat.id), message)
+    {
+        Admission::Queued(id) => return Ok(Json(MessageCreateResp::Queued(id))),
+        Admission::Idle(message) => message,
+    };
+
+    let resp = answer(&app, message, chat.id).await;
+    // messages sent meanwhile waited for this one
+    app.chat.release(chat.id).await;
+    resp
+}
+
+/// Answer a message admitted to the idle chat. Should an edit or a
+/// regeneration have taken the chat meanwhile, the message is queued after
+/// all.
+async fn answer(
+    app: &Arc<AppState>,
+    message: Queued,
+    chat_id: i32,
+) -> JsonResult<MessageCreateResp> {
+    // a reply may have ended since the chat was loaded, continue from it
+    let chat = owned_chat(app, message.user_id, chat_id).await?;
+    let user_msg = insert_user_message(
+        app,
+        &chat,
+        chat.leaf_id,
+        message.text.clone(),
+        message.files.clone(),
+    )
+    .await?;
+
+    match app
+        .chat
+        .reply(
+            message.user_id,
+            message.model_id,
+            message.mode,
+            &user_msg,
+            chat.leaf_id,
+            true,
+        )
+        .await
+    {
+        Ok(id) => Ok(Json(MessageCreateResp::Created(MessageCreated {
+            user_id: user_msg.id,
+            id,
+        }))),
+        Err(e) if e.downcast_ref::<ChatBusy>().is_some() => {
+            let id = message.id;
+            app.chat.queue.push_front(chat.id, message);
+            Ok(Json(MessageCreateResp::Queued(id)))
+        }
+        Err(e) => Err(reply_error(e)),
+    }
+}
+
+/// Load a chat of `user_id`, hiding chats of other users.
+pub(crate) async fn owned_chat(
+    app: &AppState,
+    user_id: i32,
+    chat_id: i32,
+) -> Result<chat::Model, AppError> {
+    chat::Entity::find_by_id(chat_id)
+        .one(&app.conn)
+        .await
+        .raw_kind(ErrorKind::Internal)?
+        .filter(|x| x.owner_id == user_id)
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "chat not found".to_owned(),
+            })
+        })
+}
+
+/// Move uploaded files into the chat, so they outlive the upload expiry.
+pub(crate) async fn attach_files(
+    app: &AppState,
+    user_id: i32,
+    chat_id: i32,
+    files: Vec<MessageCreateReqFile>,
+) -> Result<Vec<FileMetadata>, AppError> {
+    let file_ids: Vec<i32> = files.iter().map(|f| f.id).collect();
 
     if !file_ids.is_empty() {
         File::update_many()
             .filter(FileColumn::Id.is_in(file_ids.clone()))
             .filter(FileColumn::OwnerId.eq(user_id))
-            .col_expr(
-                FileColumn::ChatId,
-                sea_orm::sea_query::Expr::value(req.chat_id),
-            )
+            .col_expr(FileColumn::ChatId, sea_orm::sea_query::Expr::value(chat_id))
             .col_expr(
                 FileColumn::ValidUntil,
                 sea_orm::sea_query::Expr::value(Option::<i32>::None),
@@ -62,8 +171,7 @@ pub async fn route(
             .raw_kind(ErrorKind::Internal)?;
     }
 
-    let files = req
-        .files
+    Ok(files
         .into_iter()
         .map(|f| FileMetadata {
             name: f.name,
@@ -71,38 +179,57 @@ pub async fn route(
             kind: protocol::FileKind::User,
             dimensions: None,
         })
-        .collect::<Vec<_>>();
-
-    let user_msg = entity::message::ActiveModel {
-        chat_id: Set(req.chat_id),
-        inner: Set(MessageInner::User {
-            text: req.text,
-            files,
-        }),
-        ..Default::default()
-    }
-    .insert(&app.conn)
-    .await
-    .raw_kind(ErrorKind::Internal)?;
+        .collect())
+}
 
-    let session = app
-        .chat
-        .get_session(user_id, req.chat_id, req.model_id, req.mode.into())
+/// Insert a user message below `parent_id` and make it the active branch.
+pub(crate) async fn insert_user_message(
+    app: &AppState,
+    chat: &chat::Model,
+    parent_id: Option<i32>,
+    text: String,
+    files: Vec<FileMetadata>,
+) -> Result<message::Model, AppError> {
+    app.chat
+        .insert_user_message(chat.id, parent_id, text, files)
         .await
-        .kind(ErrorKind::ResourceNotFound)?;
-
-    let id = session.message.id;
-    let strategy = req.mode.into();
+        .kind(ErrorKind::Internal)
+}
 
-    let processor = app.chat.clone();
-    tokio::spawn(async move {
-        if let Err(e) = processor.process(strategy, session).await {
-            log::error!("Failed to process message: {:?}", e);
-        }
-    });
+/// Start the assistant reply to `user_msg`, which ends the active branch.
+///
+/// When the reply cannot be started, the branch active before is restored
+/// and a `user_msg` created for this request is removed again.
+pub(super) async fn reply(
+    app: &Arc<AppState>,
+    user_id: i32,
+    model_id: i32,
+    mode: ChatMode,
+    user_msg: message::Model,
+    previous_leaf: Option<i32>,
+    created: bool,
+) -> JsonResult<MessageCreated> {
+    let id = app
+        .chat
+        .reply(user_id, model_id, mode, &user_msg, previous_leaf, created)
+        .await
+        .map_err(reply_error)?;
 
-    Ok(Json(MessageCreateResp {
+    Ok(Json(MessageCreated {
         user_id: user_msg.id,
         id,
     }))
 }
+
+/// Error of a reply that could not be started.
+fn reply_error(e: anyhow::Error) -> AppError {
+    let error = match e.downcast_ref::<BudgetExceeded>() {
+        Some(_) => ErrorKind::BudgetExceeded,
+        // a restricted model reads like a missing one
+        None => ErrorKind::ResourceNotFound,
+    };
+    Json(Error {
+        error,
+        reason: e.to_string(),
+    })
+}
@@ -2,11 +2,11 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::{chat, message};
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::branch};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -37,20 +37,42 @@ pub async fn route(
             })
         })?;
 
-    if chat.map(|m| m.owner_id) != Some(user_id) {
+    if chat.as_ref().map(|m| m.owner_id) != Some(user_id) {
         return Err(Json(Error {
             error: ErrorKind::ResourceNotFound,
             reason: "message not found".to_owned(),
         }));
     }
 
+    // the message takes every reply below it along
+    let nodes = branch::nodes(&app.conn, message.chat_id)
+        .await
+        .raw_kind(ErrorKind::Internal)?;
+    let removed = branch::subtree(&nodes, message.id);
+
+    let txn = app.conn.begin().await.raw_kind(ErrorKind::Internal)?;
     let result = message::Entity::delete_many()
-        .filter(message::Column::ChatId.eq(message.chat_id))
-        .filter(message::Column::Id.gte(req.id))
-        .exec(&app.conn)
+        .filter(message::Column::Id.is_in(removed.clone()))
+        .exec(&txn)
         .await
         .raw_kind(ErrorKind::Internal)?;
 
+    let leaf_id = chat.and_then(|x| x.leaf_id);
+    if leaf_id.is_none_or(|x| removed.contains(&x)) {
+        let remaining = nodes
+            .into_iter()
+            .filter(|x| !removed.contains(&x.0))
+            .collect::<Vec<_>>();
+        let leaf_id = match message.parent_id {
+            Some(parent_id) => Some(branch::newest_leaf(&remaining, parent_id)),
+            None => remaining.iter().map(|x| x.0).max(),
+        };
+        branch::set_leaf(&txn, message.chat_id, leaf_id)
+            .await
+            .raw_kind(ErrorKind::Internal)?;
+    }
+    txn.commit().await.raw_kind(ErrorKind::Internal)?;
+
     Ok(Json(MessageDeleteResp {
         deleted: result.rows_affected > 0,
     }))
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::message;
+use protocol::MessageInner;
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use typeshare::typeshare;
+
+use super::create::{
+    MessageCreateReqFile, MessageCreated, attach_files, insert_user_message, owned_chat, reply,
+};
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::chat::ChatMode};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MessageEditReq {
+    /// User message to replace, it is kept as a sibling branch
+    pub id: i32,
+    pub model_id: i32,
+    pub mode: ChatMode,
+    pub text: String,
+    pub files: Vec<MessageCreateReqFile>,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MessageEditReq>,
+) -> JsonResult<MessageCreated> {
+    let not_found = || {
+        Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "message not found".to_owned(),
+        })
+    };
+
+    let original = message::Entity::find_by_id(req.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(not_found)?;
+    let chat = owned_chat(&app, user_id, original.chat_id)
+        .await
+        .map_err(|_| not_found())?;
+
+    if !matches!(original.inner, MessageInner::User { .. }) {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "only user messages can be edited".to_owned(),
+        }));
+    }
+
+    if app.chat.is_streaming(chat.id) {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "a reply is still streaming in this chat".to_owned(),
+        }));
+    }
+
+    let files = attach_files(&app, user_id, chat.id, req.files).await?;
+    let user_msg = insert_user_message(&app, &chat, original.parent_id, req.text, files).await?;
+
+    reply(
+        &app,
+        user_id,
+        req.model_id,
+        req.mode,
+        user_msg,
+        chat.leaf_id,
+        true,
+    )
+    .await
+}
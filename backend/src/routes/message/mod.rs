@@ -1,6 +1,11 @@
-mod create;
+mod cancel;
+pub(crate) mod create;
 mod delete;
+mod edit;
 mod paginate;
+mod queue;
+mod regenerate;
+mod switch;
 
 use std::sync::Arc;
 
@@ -10,7 +15,12 @@ use crate::AppState;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
+        .route("/cancel", post(cancel::route))
         .route("/create", post(create::route))
         .route("/delete", post(delete::route))
         .route("/paginate", post(paginate::route))
+        .route("/queue", post(queue::route))
+        .route("/regenerate", post(regenerate::route))
+        .route("/edit", post(edit::route))
+        .route("/switch", post(switch::route))
 }
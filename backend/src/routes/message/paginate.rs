@@ -8,7 +8,7 @@ use sea_orm::{QueryOrder, QuerySelect, prelude::*};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, config::MAX_PAGINATE_LIMIT, errors::*, middlewares::auth::UserId};
+use crate::{AppState, config::MAX_PAGINATE_LIMIT, errors::*, middlewares::auth::UserId, utils::branch};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -62,6 +62,8 @@ pub struct MessagePaginateRespList {
     pub token_count: i32,
     pub price: f32,
     pub inner: MessageInner,
+    /// Ids of the alternatives to this message, itself included, oldest first
+    pub siblings: Vec<i32>,
 }
 
 pub async fn route(
@@ -69,23 +71,37 @@ pub async fn route(
     Extension(UserId(user_id)): Extension<UserId>,
     Json(req): Json<MessagePaginateReq>,
 ) -> JsonResult<MessagePaginateResp> {
+    let chat_id = match &req {
+        MessagePaginateReq::Limit(limit) => limit.chat_id,
+        MessagePaginateReq::Range(range) => range.chat_id,
+    };
+    let Some(chat) = Chat::find_by_id(chat_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.owner_id == user_id)
+    else {
+        return Err(Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "".to_owned(),
+        }));
+    };
+
+    // only the active branch is listed
+    let nodes = branch::nodes(&app.conn, chat_id)
+        .await
+        .kind(ErrorKind::Internal)?;
+    let path = branch::leaf(&chat, &nodes)
+        .map(|leaf| branch::active_path(&nodes, leaf))
+        .unwrap_or_default();
+
+    let q = Message::find()
+        .filter(message::Column::ChatId.eq(chat_id))
+        .filter(message::Column::Id.is_in(path));
     let q = match req {
         MessagePaginateReq::Limit(limit) => {
-            let res = Chat::find_by_id(limit.chat_id)
-                .one(&app.conn)
-                .await
-                .kind(ErrorKind::Internal)?;
-            if res.is_none_or(|x| x.owner_id != user_id) {
-                return Err(Json(Error {
-                    error: ErrorKind::ResourceNotFound,
-                    reason: "".to_owned(),
-                }));
-            }
-
-            let q = Message::find()
-                .filter(message::Column::ChatId.eq(limit.chat_id))
-                .limit(limit.limit.unwrap_or(MAX_PAGINATE_LIMIT) as u64);
-            let q = match (limit.order, limit.id) {
+            let q = q.limit(limit.limit.unwrap_or(MAX_PAGINATE_LIMIT) as u64);
+            match (limit.order, limit.id) {
                 (MessagePaginateReqOrder::Gt, None) => q.order_by_asc(message::Column::Id),
                 (MessagePaginateReqOrder::Gt, Some(id)) => q
                     .filter(message::Column::Id.gt(id))
@@ -94,27 +110,11 @@ pub async fn route(
                 (MessagePaginateReqOrder::Lt, Some(id)) => q
                     .filter(message::Column::Id.lt(id))
                     .order_by_desc(message::Column::Id),
-            };
-            q
-        }
-        MessagePaginateReq::Range(range) => {
-            let res = Chat::find_by_id(range.chat_id)
-                .one(&app.conn)
-                .await
-                .kind(ErrorKind::Internal)?;
-            if res.is_none_or(|x| x.owner_id != user_id) {
-                return Err(Json(Error {
-                    error: ErrorKind::ResourceNotFound,
-                    reason: "".to_owned(),
-                }));
             }
-
-            let q = Message::find()
-                .filter(message::Column::ChatId.eq(range.chat_id))
-                .limit(MAX_PAGINATE_LIMIT as u64)
-                .filter(message::Column::Id.gt(range.lower).lt(range.upper));
-            q
         }
+        MessagePaginateReq::Range(range) => q
+            .limit(MAX_PAGINATE_LIMIT as u64)
+            .filter(message::Column::Id.gt(range.lower).lt(range.upper)),
     };
 
     let msgs = q.all(&app.conn).await.kind(ErrorKind::Internal)?;
@@ -137,6 +137,7 @@ pub async fn route(
                 id: msg.id,
                 token_count: msg.token_count,
                 price: msg.price,
+                siblings: branch::siblings(&nodes, msg.id),
                 inner,
             })
         })
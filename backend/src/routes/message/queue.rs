@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::create::owned_chat;
+use crate::{AppState, errors::*, middlewares::auth::UserId, routes::chat::sse::SseRespQueued};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MessageQueueReq {
+    pub chat_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MessageQueueResp {
+    pub list: Vec<SseRespQueued>,
+}
+
+/// List the messages waiting for the streaming reply of a chat, next first.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MessageQueueReq>,
+) -> JsonResult<MessageQueueResp> {
+    let chat = owned_chat(&app, user_id, req.chat_id).await?;
+
+    let list = app
+        .chat
+        .queue
+        .list(chat.id)
+        .into_iter()
+        .map(SseRespQueued::from)
+        .collect();
+
+    Ok(Json(MessageQueueResp { list }))
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::message;
+use protocol::MessageInner;
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use typeshare::typeshare;
+
+use super::create::{MessageCreated, owned_chat, reply};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{branch, chat::ChatMode},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MessageRegenerateReq {
+    /// Assistant message to answer again, it is kept as a sibling branch
+    pub id: i32,
+    pub model_id: i32,
+    pub mode: ChatMode,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MessageRegenerateReq>,
+) -> JsonResult<MessageCreated> {
+    let not_found = || {
+        Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "message not found".to_owned(),
+        })
+    };
+
+    let answer = message::Entity::find_by_id(req.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(not_found)?;
+    let chat = owned_chat(&app, user_id, answer.chat_id)
+        .await
+        .map_err(|_| not_found())?;
+
+    let prompt = match answer.parent_id {
+        Some(parent_id) => message::Entity::find_by_id(parent_id)
+            .one(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?,
+        None => None,
+    };
+    let Some(prompt) = prompt.filter(|x| matches!(x.inner, MessageInner::User { .. })) else {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "only replies to a user message can be regenerated".to_owned(),
+        }));
+    };
+
+    if app.chat.is_streaming(chat.id) {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "a reply is still streaming in this chat".to_owned(),
+        }));
+    }
+
+    // the new reply becomes the next child of the prompt
+    branch::set_leaf(&app.conn, chat.id, Some(prompt.id))
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    reply(
+        &app,
+        user_id,
+        req.model_id,
+        req.mode,
+        prompt,
+        chat.leaf_id,
+        false,
+    )
+    .await
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::message;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::create::owned_chat;
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::branch};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct MessageSwitchReq {
+    /// Any message of the branch to show, usually a sibling of the current one
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct MessageSwitchResp {
+    /// Last message of the now active branch
+    pub leaf_id: i32,
+}
+
+/// Make the branch through `id` active, following its newest replies.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<MessageSwitchReq>,
+) -> JsonResult<MessageSwitchResp> {
+    let not_found = || {
+        Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "message not found".to_owned(),
+        })
+    };
+
+    let message = message::Entity::find_by_id(req.id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(not_found)?;
+    let chat = owned_chat(&app, user_id, message.chat_id)
+        .await
+        .map_err(|_| not_found())?;
+
+    let nodes = branch::nodes(&app.conn, chat.id)
+        .await
+        .kind(ErrorKind::Internal)?;
+    let leaf_id = branch::newest_leaf(&nodes, message.id);
+    branch::set_leaf(&app.conn, chat.id, Some(leaf_id))
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(MessageSwitchResp { leaf_id }))
+}
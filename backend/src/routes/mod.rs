@@ -1,7 +1,23 @@
+pub mod assistant;
+pub mod audit;
 pub mod auth;
+pub mod budget;
 pub mod chat;
+pub mod compare;
 pub mod file;
+pub mod folder;
+pub mod group;
+pub mod knowledge;
+pub mod memory;
 pub mod message;
 pub mod model;
+pub mod session;
+pub mod share;
 pub mod spa;
+pub mod tag;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod token;
+pub mod totp;
+pub mod usage;
 pub mod user;
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{model_access, prelude::*};
+use protocol::AuditAction;
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ModelAllowReq {
+    pub id: i32,
+    /// User ids allowed to list and use the model
+    pub users: Vec<i32>,
+    /// Group ids allowed to list and use the model
+    pub groups: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ModelAllowResp {
+    /// Whether the model is now limited to the allowlist
+    pub restricted: bool,
+}
+
+/// Replace the allowlist of a model, an empty one opens it to everyone.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ModelAllowReq>,
+) -> JsonResult<ModelAllowResp> {
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    Model::find_by_id(req.id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("model not found")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    ModelAccess::delete_many()
+        .filter(model_access::Column::ModelId.eq(req.id))
+        .exec(&txn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let users = req.users.iter().map(|&x| (Some(x), None));
+    let groups = req.groups.iter().map(|&x| (None, Some(x)));
+    let rows = users
+        .chain(groups)
+        .map(|(user_id, group_id)| model_access::ActiveModel {
+            model_id: ActiveValue::Set(req.id),
+            user_id: ActiveValue::Set(user_id),
+            group_id: ActiveValue::Set(group_id),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let restricted = !rows.is_empty();
+    if restricted {
+        // unknown user or group ids fail the foreign keys
+        ModelAccess::insert_many(rows)
+            .exec(&txn)
+            .await
+            .kind(ErrorKind::MalformedRequest)?;
+    }
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    Audit::new(AuditAction::ModelAccessChange)
+        .actor(user_id)
+        .target(req.id)
+        .detail(format!("users {:?}, groups {:?}", req.users, req.groups))
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(ModelAllowResp { restricted }))
+}
@@ -2,12 +2,18 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::{model, prelude::*};
-use protocol::{ModelConfig, OcrEngine};
+use protocol::{AuditAction, ModelConfig, OcrEngine};
 use sea_orm::{ActiveValue::Set, EntityTrait};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId, openrouter, utils::model::ModelChecker};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    openrouter,
+    utils::{audit::Audit, model::ModelChecker},
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -32,7 +38,8 @@ pub struct ModelCreateResp {
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<ModelCreateReq>,
 ) -> JsonResult<ModelCreateResp> {
     let raw_config = req.config;
@@ -51,6 +58,14 @@ pub async fn route(
             .kind(ErrorKind::Internal)?
             .last_insert_id;
 
+            Audit::new(AuditAction::ModelCreate)
+                .actor(user_id)
+                .target(id)
+                .detail(config.display_name.clone())
+                .ip(ip)
+                .record(&app.conn)
+                .await;
+
             Ok(Json(ModelCreateResp {
                 id,
                 image_input: caps.image_input,
@@ -2,11 +2,17 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::model;
+use protocol::AuditAction;
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -22,7 +28,8 @@ pub struct ModelDeleteResp {
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<ModelDeleteReq>,
 ) -> JsonResult<ModelDeleteResp> {
     model::Entity::delete_by_id(req.id)
@@ -30,5 +37,12 @@ pub async fn route(
         .await
         .kind(ErrorKind::ResourceNotFound)?;
 
+    Audit::new(AuditAction::ModelDelete)
+        .actor(user_id)
+        .target(req.id)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
     Ok(Json(ModelDeleteResp { deleted: true }))
 }
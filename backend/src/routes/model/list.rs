@@ -2,12 +2,18 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::model;
-use protocol::{ModelConfig, OcrEngine};
+use protocol::{ModelConfig, OcrEngine, UserRole};
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId, openrouter, utils::model::ModelChecker};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    openrouter,
+    utils::{model::ModelChecker, model_access},
+};
 
 #[derive(Debug, Serialize)]
 #[typeshare]
@@ -36,16 +42,20 @@ pub struct ModelListReq {}
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
     Json(_): Json<ModelListReq>,
 ) -> JsonResult<ModelListResp> {
     let models = model::Entity::find()
         .all(&app.conn)
         .await
         .kind(ErrorKind::Internal)?;
+    let access = model_access::load(&app.conn, user_id, role)
+        .await
+        .kind(ErrorKind::Internal)?;
 
     let mut list = Vec::new();
-    for m in models {
+    for m in models.into_iter().filter(|x| access.permits(x.id)) {
         let config =
             <ModelConfig as ModelChecker>::from_toml(&m.config).expect("corruptted database");
 
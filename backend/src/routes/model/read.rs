@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
-use entity::model;
-use sea_orm::EntityTrait;
+use entity::{model, model_access, prelude::*};
+use protocol::UserRole;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::model_access::load};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -18,24 +19,49 @@ pub struct ModelReadReq {
 #[typeshare]
 pub struct ModelReadResp {
     raw: String,
+    /// Empty when every user can use the model, always empty for members
+    allowed_users: Vec<i32>,
+    allowed_groups: Vec<i32>,
 }
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
     Json(req): Json<ModelReadReq>,
 ) -> JsonResult<ModelReadResp> {
+    let access = load(&app.conn, user_id, role)
+        .await
+        .kind(ErrorKind::Internal)?;
+
     let model = model::Entity::find_by_id(req.id)
         .one(&app.conn)
         .await
-        .kind(ErrorKind::Internal)?;
+        .kind(ErrorKind::Internal)?
+        .filter(|x| access.permits(x.id));
 
     let model = model.ok_or_else(|| Error {
         error: ErrorKind::ResourceNotFound,
         reason: "model not found".to_owned(),
     })?;
 
+    // who else may use it is for administrators only
+    let access = match role {
+        UserRole::Admin => ModelAccess::find()
+            .filter(model_access::Column::ModelId.eq(model.id))
+            .all(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?,
+        UserRole::Member => Vec::new(),
+    };
+    let allowed_users = access.iter().filter_map(|x| x.user_id).collect();
+    let allowed_groups = access.iter().filter_map(|x| x.group_id).collect();
+
     let raw = model.config;
 
-    Ok(Json(ModelReadResp { raw }))
+    Ok(Json(ModelReadResp {
+        raw,
+        allowed_users,
+        allowed_groups,
+    }))
 }
@@ -2,12 +2,17 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::model;
-use protocol::ModelConfig;
+use protocol::{AuditAction, ModelConfig};
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId, utils::model::ModelChecker};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::{audit::Audit, model::ModelChecker},
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -25,7 +30,8 @@ pub struct ModelWriteResp {
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<ModelWriteReq>,
 ) -> JsonResult<ModelWriteResp> {
     let config = req.config;
@@ -47,6 +53,15 @@ pub async fn route(
         .kind(ErrorKind::ResourceNotFound)?;
 
     let wrote = result.rows_affected > 0;
+    if wrote {
+        Audit::new(AuditAction::ModelUpdate)
+            .actor(user_id)
+            .target(req.id)
+            .detail(display_name.clone())
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
 
     Ok(Json(ModelWriteResp {
         display_name,
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{prelude::*, session};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::{SessionId, UserId},
+    utils::timestamp,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct SessionListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SessionListResp {
+    pub list: Vec<SessionList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SessionList {
+    pub id: i32,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    /// RFC 3339 timestamp
+    pub expires_at: String,
+    pub user_agent: Option<String>,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(SessionId(session_id)): Extension<SessionId>,
+    Json(_): Json<SessionListReq>,
+) -> JsonResult<SessionListResp> {
+    let now = timestamp::now();
+    let sessions = Session::find()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.gt(now))
+        .order_by_desc(session::Column::CreatedAt)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let list = sessions
+        .into_iter()
+        .map(|m| SessionList {
+            id: m.id,
+            created_at: timestamp::format(m.created_at),
+            expires_at: timestamp::format(m.expires_at),
+            user_agent: m.user_agent,
+            current: m.id == session_id,
+        })
+        .collect();
+
+    Ok(Json(SessionListResp { list }))
+}
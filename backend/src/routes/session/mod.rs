@@ -0,0 +1,14 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use crate::AppState;
+
+mod list;
+mod revoke;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/list", post(list::route))
+        .route("/revoke", post(revoke::route))
+}
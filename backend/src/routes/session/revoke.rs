@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{prelude::*, session};
+use protocol::AuditAction;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct SessionRevokeReq {
+    /// If omit will revoke every session of the current user, including the
+    /// one making the request
+    pub id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SessionRevokeResp {
+    pub revoked: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<SessionRevokeReq>,
+) -> JsonResult<SessionRevokeResp> {
+    let mut query = Session::delete_many().filter(session::Column::UserId.eq(user_id));
+    if let Some(id) = req.id {
+        query = query.filter(session::Column::Id.eq(id));
+    }
+
+    let res = query.exec(&app.conn).await.kind(ErrorKind::Internal)?;
+
+    if res.rows_affected > 0 {
+        let audit = Audit::new(AuditAction::SessionRevoke).actor(user_id).ip(ip);
+        let audit = match req.id {
+            Some(id) => audit.target(id),
+            None => audit.detail(format!("all {} sessions", res.rows_affected)),
+        };
+        audit.record(&app.conn).await;
+    }
+
+    Ok(Json(SessionRevokeResp {
+        revoked: res.rows_affected as u32,
+    }))
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, message, prelude::*, share};
+use protocol::{AuditAction, ShareSnapshot, SharedMessage};
+use sea_orm::{ActiveValue::Set, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::{audit::Audit, branch, random, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ShareCreateReq {
+    pub chat_id: i32,
+    /// If omit the link never expires
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ShareCreateResp {
+    pub id: i32,
+    /// Part of the public link, `/api/shared/read` takes it
+    pub slug: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ShareCreateReq>,
+) -> JsonResult<ShareCreateResp> {
+    let chat = Chat::find_by_id(req.chat_id)
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "chat not found".to_owned(),
+            })
+        })?;
+
+    let messages = Message::find()
+        .filter(message::Column::ChatId.eq(chat.id))
+        .order_by_asc(message::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    // later edits and branches stay private
+    let nodes = messages
+        .iter()
+        .map(|x| (x.id, x.parent_id))
+        .collect::<Vec<_>>();
+    let path = branch::leaf(&chat, &nodes)
+        .map(|leaf| branch::active_path(&nodes, leaf))
+        .unwrap_or_default();
+    let snapshot = messages
+        .into_iter()
+        .filter(|x| path.contains(&x.id))
+        .map(|x| SharedMessage {
+            id: x.id,
+            created_at: x.created_at,
+            inner: x.inner,
+        })
+        .collect::<Vec<_>>();
+
+    if snapshot.is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "chat has no messages to share".to_owned(),
+        }));
+    }
+
+    let slug = random::url_safe(16);
+    let now = timestamp::now();
+
+    let id = Share::insert(share::ActiveModel {
+        chat_id: Set(chat.id),
+        slug: Set(slug.clone()),
+        title: Set(chat.title),
+        messages: Set(ShareSnapshot(snapshot)),
+        created_at: Set(now),
+        expires_at: Set(req
+            .expires_in_days
+            .map(|days| now + days as i64 * 60 * 60 * 24)),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    Audit::new(AuditAction::ShareCreate)
+        .actor(user_id)
+        .target(id)
+        .detail(format!("chat {}", chat.id))
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(ShareCreateResp { id, slug }))
+}
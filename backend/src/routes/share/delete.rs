@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, prelude::*};
+use protocol::AuditAction;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ShareDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ShareDeleteResp {
+    pub deleted: bool,
+}
+
+/// Revoke a share link, the link stops working right away.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ShareDeleteReq>,
+) -> JsonResult<ShareDeleteResp> {
+    let owned = Share::find_by_id(req.id)
+        .inner_join(Chat)
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .is_some();
+
+    let deleted = owned
+        && Share::delete_by_id(req.id)
+            .exec(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?
+            .rows_affected
+            > 0;
+    if deleted {
+        Audit::new(AuditAction::ShareDelete)
+            .actor(user_id)
+            .target(req.id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(ShareDeleteResp { deleted }))
+}
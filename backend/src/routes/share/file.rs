@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::Response;
+use entity::file::Entity as File;
+use sea_orm::EntityTrait;
+
+use super::find_active;
+use crate::AppState;
+use crate::errors::{AppError, Error, ErrorKind, WithKind};
+use crate::routes::file::download::respond;
+use crate::utils::export;
+
+/// Serve a file shown in a shared chat.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Path((slug, id)): Path<(String, i32)>,
+) -> Result<Response, AppError> {
+    let share = find_active(&app, &slug).await?;
+
+    // only what the snapshot shows, and only from the shared chat
+    let listed = export::file_ids(share.messages.0.iter().map(|x| &x.inner)).contains(&id);
+    let file = File::find_by_id(id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| listed && x.chat_id == Some(share.chat_id))
+        .ok_or(Json(Error {
+            error: ErrorKind::ResourceNotFound,
+            reason: "File not found".to_owned(),
+        }))?;
+
+    let reader = app.blob.get(id).ok_or(Json(Error {
+        error: ErrorKind::ResourceNotFound,
+        reason: "File data not found".to_owned(),
+    }))?;
+
+    Ok(respond(file.mime_type.as_deref(), reader))
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, prelude::*, share};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct ShareListReq {
+    /// Only links of this chat, all links of the user when omitted
+    pub chat_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ShareListResp {
+    pub list: Vec<ShareList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct ShareList {
+    pub id: i32,
+    pub chat_id: i32,
+    pub slug: String,
+    /// Title of the chat at share time
+    pub title: Option<String>,
+    /// Number of messages in the snapshot
+    pub message_count: u32,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    /// RFC 3339 timestamp
+    pub expires_at: Option<String>,
+    pub expired: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<ShareListReq>,
+) -> JsonResult<ShareListResp> {
+    let shares = Share::find()
+        .inner_join(Chat)
+        .filter(chat::Column::OwnerId.eq(user_id))
+        .apply_if(req.chat_id, |q, id| q.filter(share::Column::ChatId.eq(id)))
+        .order_by_desc(share::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let now = timestamp::now();
+    let list = shares
+        .into_iter()
+        .map(|m| ShareList {
+            id: m.id,
+            chat_id: m.chat_id,
+            slug: m.slug,
+            title: m.title,
+            message_count: m.messages.0.len() as u32,
+            created_at: timestamp::format(m.created_at),
+            expires_at: m.expires_at.map(timestamp::format),
+            expired: m.expires_at.is_some_and(|x| x <= now),
+        })
+        .collect();
+
+    Ok(Json(ShareListResp { list }))
+}
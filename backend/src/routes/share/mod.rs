@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    routing::{get, post},
+};
+use entity::{prelude::*, share};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use crate::{AppState, errors::*, utils::timestamp};
+
+mod create;
+mod delete;
+mod file;
+mod list;
+mod read;
+
+/// Managing share links of own chats.
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/create", post(create::route))
+        .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+}
+
+/// Viewing a shared chat, reachable without logging in.
+pub fn public_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/read", post(read::route))
+        .route("/file/{slug}/{id}", get(file::route))
+}
+
+/// Look up a share link that is still valid.
+async fn find_active(app: &AppState, slug: &str) -> Result<share::Model, AppError> {
+    Share::find()
+        .filter(share::Column::Slug.eq(slug))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.expires_at.is_none_or(|at| at > timestamp::now()))
+        .ok_or_else(|| {
+            Json(Error {
+                error: ErrorKind::ResourceNotFound,
+                reason: "share link not found".to_owned(),
+            })
+        })
+}
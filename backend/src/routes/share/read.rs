@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
+use protocol::MessageInner;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::find_active;
+use crate::{AppState, errors::*, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct SharedReadReq {
+    pub slug: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SharedReadResp {
+    pub title: Option<String>,
+    /// RFC 3339 timestamp of when the link was created
+    pub shared_at: String,
+    /// Oldest first, files are served by `/api/shared/file/{slug}/{id}`
+    pub messages: Vec<SharedReadRespMessage>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct SharedReadRespMessage {
+    pub id: i32,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    pub inner: MessageInner,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Json(req): Json<SharedReadReq>,
+) -> JsonResult<SharedReadResp> {
+    let share = find_active(&app, &req.slug).await?;
+
+    let messages = share
+        .messages
+        .0
+        .into_iter()
+        .map(|x| SharedReadRespMessage {
+            id: x.id,
+            created_at: timestamp::format(x.created_at),
+            inner: x.inner,
+        })
+        .collect();
+
+    Ok(Json(SharedReadResp {
+        title: share.title,
+        shared_at: timestamp::format(share.created_at),
+        messages,
+    }))
+}
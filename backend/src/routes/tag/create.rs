@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{tag, prelude::*};
+use sea_orm::{ActiveValue, EntityTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TagCreateReq {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TagCreateResp {
+    pub id: i32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<TagCreateReq>,
+) -> JsonResult<TagCreateResp> {
+    let name = super::check_name(&app.conn, user_id, None, &req.name).await?;
+
+    let id = Tag::insert(tag::ActiveModel {
+        owner_id: ActiveValue::Set(user_id),
+        name: ActiveValue::Set(name),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?
+    .last_insert_id;
+
+    Ok(Json(TagCreateResp { id }))
+}
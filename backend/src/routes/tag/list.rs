@@ -0,0 +1,65 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat_tag, prelude::*, tag};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TagListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TagListResp {
+    pub list: Vec<TagList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TagList {
+    pub id: i32,
+    pub name: String,
+    /// Number of tagged chats, archived ones included
+    pub chat_count: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<TagListReq>,
+) -> JsonResult<TagListResp> {
+    let tags = Tag::find()
+        .filter(tag::Column::OwnerId.eq(user_id))
+        .order_by_asc(tag::Column::Name)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let counts: HashMap<i32, i64> = ChatTag::find()
+        .select_only()
+        .column(chat_tag::Column::TagId)
+        .column_as(chat_tag::Column::ChatId.count(), "count")
+        .filter(chat_tag::Column::TagId.is_in(tags.iter().map(|x| x.id)))
+        .group_by(chat_tag::Column::TagId)
+        .into_tuple::<(i32, i64)>()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .collect();
+
+    let list = tags
+        .into_iter()
+        .map(|x| TagList {
+            chat_count: counts.get(&x.id).copied().unwrap_or_default() as u32,
+            id: x.id,
+            name: x.name,
+        })
+        .collect();
+
+    Ok(Json(TagListResp { list }))
+}
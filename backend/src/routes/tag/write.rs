@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{tag, prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TagWriteReq {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TagWriteResp {
+    pub wrote: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<TagWriteReq>,
+) -> JsonResult<TagWriteResp> {
+    let name = super::check_name(&app.conn, user_id, Some(req.id), &req.name).await?;
+
+    let res = Tag::update_many()
+        .col_expr(tag::Column::Name, name.into())
+        .filter(tag::Column::Id.eq(req.id))
+        .filter(tag::Column::OwnerId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    Ok(Json(TagWriteResp {
+        wrote: res.rows_affected > 0,
+    }))
+}
@@ -0,0 +1,111 @@
+//! Shared setup for route tests: an in-memory [`AppState`] and a helper that
+//! posts to a router as a given user, skipping token authentication.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    Extension, Router,
+    body::{Body, to_bytes},
+    extract::ConnectInfo,
+    http::{Request, header},
+};
+use entity::{prelude::*, user};
+use migration::MigratorTrait;
+use pasetors::keys::SymmetricKey;
+use protocol::UserRole;
+use redb::backends::InMemoryBackend;
+use sea_orm::{ActiveValue, ConnectOptions, Database, EntityTrait};
+use tower::ServiceExt;
+
+use crate::{
+    AppState,
+    chat::Context,
+    config::DEFAULT_EMBEDDING_MODEL,
+    middlewares::auth::UserId,
+    openrouter::Openrouter,
+    utils::{blob::BlobDB, embedding::Embedder, password_hash::Hasher},
+};
+
+pub async fn state() -> Arc<AppState> {
+    // every pooled connection would otherwise open its own empty database
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1).min_connections(1);
+    let conn = Database::connect(options).await.unwrap();
+    migration::Migrator::up(&conn, None).await.unwrap();
+
+    let key = Config::find_by_id("paseto_key")
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap()
+        .value;
+    let key = SymmetricKey::from(&key).unwrap();
+
+    let openrouter = Arc::new(Openrouter::new("", "http://127.0.0.1:9", false));
+    let blob = redb::Database::builder()
+        .create_with_backend(InMemoryBackend::new())
+        .unwrap();
+    let blob = Arc::new(BlobDB::new(Arc::new(blob)));
+    let embedder = Arc::new(Embedder::new(
+        openrouter.clone(),
+        DEFAULT_EMBEDDING_MODEL.to_owned(),
+    ));
+    let chat =
+        Arc::new(Context::new(conn.clone(), openrouter.clone(), blob.clone(), embedder).unwrap());
+
+    Arc::new(AppState {
+        conn,
+        key,
+        hasher: Hasher::default(),
+        chat,
+        openrouter,
+        blob,
+        auth_header: None,
+        header_provision: Default::default(),
+        oidc: None,
+        trusted_proxies: Vec::new(),
+        throttle: Default::default(),
+        password_policy: Default::default(),
+    })
+}
+
+pub async fn insert_user(app: &AppState, name: &str, role: UserRole) -> user::Model {
+    let model = user::ActiveModel {
+        name: ActiveValue::Set(name.to_owned()),
+        password: ActiveValue::Set(String::new()),
+        role: ActiveValue::Set(role),
+        ..Default::default()
+    };
+    User::insert(model)
+        .exec_with_returning(&app.conn)
+        .await
+        .unwrap()
+}
+
+/// Posts `body` to `path` as `user`, with the extensions
+/// [`crate::middlewares::auth::Middleware`] would have set, and returns the
+/// JSON response.
+pub async fn post(
+    app: &Arc<AppState>,
+    router: Router<Arc<AppState>>,
+    user: &user::Model,
+    path: &str,
+    body: serde_json::Value,
+) -> serde_json::Value {
+    let router = router
+        .layer(Extension(UserId(user.id)))
+        .layer(Extension(user.role))
+        .layer(Extension(ConnectInfo(SocketAddr::from((
+            [127, 0, 0, 1],
+            0,
+        )))))
+        .with_state(app.clone());
+
+    let request = Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{api_token, prelude::*};
+use protocol::{ApiTokenScope, ApiTokenScopes, AuditAction};
+use sea_orm::{ActiveValue, EntityTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{
+        auth::{API_TOKEN_PREFIX, UserId},
+        client_ip::ClientIp,
+    },
+    utils::{audit::Audit, random, timestamp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TokenCreateReq {
+    pub name: String,
+    pub scopes: Vec<ApiTokenScope>,
+    /// If omit the token never expires
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TokenCreateResp {
+    pub id: i32,
+    /// Plain token, only returned once
+    pub token: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<TokenCreateReq>,
+) -> JsonResult<TokenCreateResp> {
+    if req.scopes.is_empty() {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "api token requires at least one scope".to_owned(),
+        }));
+    }
+
+    let prefix = random::hex(6);
+    let secret = random::url_safe(24);
+    let now = timestamp::now();
+
+    let res = ApiToken::insert(api_token::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        name: ActiveValue::Set(req.name.clone()),
+        prefix: ActiveValue::Set(prefix.clone()),
+        hash: ActiveValue::Set(app.hasher.hash_password(&secret)),
+        scopes: ActiveValue::Set(ApiTokenScopes(req.scopes)),
+        created_at: ActiveValue::Set(now),
+        last_used_at: ActiveValue::Set(None),
+        expires_at: ActiveValue::Set(
+            req.expires_in_days
+                .map(|days| now + days as i64 * 60 * 60 * 24),
+        ),
+        ..Default::default()
+    })
+    .exec(&app.conn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    Audit::new(AuditAction::ApiTokenCreate)
+        .actor(user_id)
+        .target(res.last_insert_id)
+        .detail(req.name)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
+    Ok(Json(TokenCreateResp {
+        id: res.last_insert_id,
+        token: format!("{}{}_{}", API_TOKEN_PREFIX, prefix, secret),
+    }))
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{api_token, prelude::*};
+use protocol::AuditAction;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TokenDeleteReq {
+    pub id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TokenDeleteResp {
+    pub deleted: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<TokenDeleteReq>,
+) -> JsonResult<TokenDeleteResp> {
+    let res = ApiToken::delete_by_id(req.id)
+        .filter(api_token::Column::UserId.eq(user_id))
+        .exec(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    if res.rows_affected > 0 {
+        Audit::new(AuditAction::ApiTokenDelete)
+            .actor(user_id)
+            .target(req.id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(TokenDeleteResp {
+        deleted: res.rows_affected > 0,
+    }))
+}
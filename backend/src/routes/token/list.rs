@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{api_token, prelude::*};
+use protocol::ApiTokenScope;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TokenListReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TokenListResp {
+    pub list: Vec<TokenList>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TokenList {
+    pub id: i32,
+    pub name: String,
+    /// Public part of the token, for telling tokens apart
+    pub prefix: String,
+    pub scopes: Vec<ApiTokenScope>,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    /// RFC 3339 timestamp
+    pub last_used_at: Option<String>,
+    /// RFC 3339 timestamp
+    pub expires_at: Option<String>,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<TokenListReq>,
+) -> JsonResult<TokenListResp> {
+    let tokens = ApiToken::find()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .order_by_desc(api_token::Column::Id)
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let list = tokens
+        .into_iter()
+        .map(|m| TokenList {
+            id: m.id,
+            name: m.name,
+            prefix: m.prefix,
+            scopes: m.scopes.0,
+            created_at: timestamp::format(m.created_at),
+            last_used_at: m.last_used_at.map(timestamp::format),
+            expires_at: m.expires_at.map(timestamp::format),
+        })
+        .collect();
+
+    Ok(Json(TokenListResp { list }))
+}
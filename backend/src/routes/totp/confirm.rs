@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use protocol::RecoveryCodes;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    config::TOTP_RECOVERY_CODE_COUNT,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{random, timestamp, totp as otp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TotpConfirmReq {
+    /// Current code from the authenticator app
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TotpConfirmResp {
+    /// One-time recovery codes, only returned once
+    pub recovery_codes: Vec<String>,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<TotpConfirmReq>,
+) -> JsonResult<TotpConfirmResp> {
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let model = Totp::find_by_id(user_id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| !x.enabled)
+        .ok_or("no pending two-factor enrollment")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    let step = otp::verify(&model.secret, &req.code, timestamp::now())
+        .ok_or("invalid code")
+        .kind(ErrorKind::MalformedRequest)?;
+
+    let recovery_codes = (0..TOTP_RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code = random::hex(5);
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect::<Vec<_>>();
+    let hashes = recovery_codes
+        .iter()
+        .map(|x| otp::recovery_code_hash(app.key.as_bytes(), x))
+        .collect();
+
+    let mut active_model = model.into_active_model();
+    active_model.enabled = ActiveValue::Set(true);
+    active_model.last_used_step = ActiveValue::Set(step);
+    active_model.recovery_codes = ActiveValue::Set(RecoveryCodes(hashes));
+    active_model.update(&txn).await.kind(ErrorKind::Internal)?;
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    log::info!("user({}) enabled two-factor authentication", user_id);
+
+    Ok(Json(TotpConfirmResp { recovery_codes }))
+}
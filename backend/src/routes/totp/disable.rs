@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use protocol::{AuditAction, UserRole};
+use sea_orm::{EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::check_second_factor;
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TotpDisableReq {
+    /// If omit will use the current user instead, administrators can reset
+    /// other users without a code
+    pub user_id: Option<i32>,
+    /// Current code or a recovery code, required for the current user
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TotpDisableResp {
+    pub disabled: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<TotpDisableReq>,
+) -> JsonResult<TotpDisableResp> {
+    let target_id = req.user_id.unwrap_or(user_id);
+
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    if target_id != user_id {
+        if role != UserRole::Admin {
+            return Err(Json(Error {
+                error: ErrorKind::Forbidden,
+                reason: "only administrators can reset other users".to_owned(),
+            }));
+        }
+    } else if let Some(model) = Totp::find_by_id(user_id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.enabled)
+    {
+        let code = req.code.as_deref().unwrap_or_default();
+        if !check_second_factor(&app, &txn, model, code).await? {
+            return Err(Json(Error {
+                error: ErrorKind::MalformedRequest,
+                reason: "invalid code".to_owned(),
+            }));
+        }
+    }
+
+    let res = Totp::delete_by_id(target_id)
+        .exec(&txn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    log::info!(
+        "user({}) two-factor authentication is disabled by {}",
+        target_id,
+        user_id
+    );
+    if res.rows_affected > 0 {
+        Audit::new(AuditAction::TotpDisable)
+            .actor(user_id)
+            .target(target_id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(TotpDisableResp {
+        disabled: res.rows_affected > 0,
+    }))
+}
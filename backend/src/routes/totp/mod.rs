@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+use entity::totp;
+use protocol::RecoveryCodes;
+use sea_orm::{ActiveModelTrait, ActiveValue, ConnectionTrait, IntoActiveModel};
+
+use crate::{
+    AppState,
+    errors::*,
+    utils::{timestamp, totp as otp},
+};
+
+mod confirm;
+mod disable;
+mod setup;
+mod status;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/setup", post(setup::route))
+        .route("/confirm", post(confirm::route))
+        .route("/disable", post(disable::route))
+        .route("/status", post(status::route))
+}
+
+/// Check a TOTP code or an unused recovery code against an enrolled secret.
+///
+/// The accepted time step, or the consumed recovery code, is persisted so that
+/// neither can be used twice.
+pub(crate) async fn check_second_factor<C: ConnectionTrait>(
+    app: &AppState,
+    conn: &C,
+    model: totp::Model,
+    code: &str,
+) -> Result<bool, AppError> {
+    if let Some(step) = otp::verify(&model.secret, code, timestamp::now()) {
+        if step <= model.last_used_step {
+            return Ok(false);
+        }
+        let mut active_model = model.into_active_model();
+        active_model.last_used_step = ActiveValue::Set(step);
+        active_model.update(conn).await.kind(ErrorKind::Internal)?;
+        return Ok(true);
+    }
+
+    let hash = otp::recovery_code_hash(app.key.as_bytes(), code);
+    let Some(index) = model.recovery_codes.0.iter().position(|x| *x == hash) else {
+        return Ok(false);
+    };
+
+    let mut codes = model.recovery_codes.0.clone();
+    codes.remove(index);
+    let mut active_model = model.into_active_model();
+    active_model.recovery_codes = ActiveValue::Set(RecoveryCodes(codes));
+    active_model.update(conn).await.kind(ErrorKind::Internal)?;
+
+    Ok(true)
+}
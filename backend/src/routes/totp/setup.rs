@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{prelude::*, totp};
+use sea_orm::{ActiveValue, EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{timestamp, totp as otp},
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TotpSetupReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TotpSetupResp {
+    /// Base32 secret, for manual entry
+    pub secret: String,
+    /// `otpauth://` uri, usually shown as a QR code
+    pub uri: String,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<TotpSetupReq>,
+) -> JsonResult<TotpSetupResp> {
+    let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
+
+    let user = User::find_by_id(user_id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .ok_or("")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    let existing = Totp::find_by_id(user_id)
+        .one(&txn)
+        .await
+        .kind(ErrorKind::Internal)?;
+    if existing.as_ref().is_some_and(|x| x.enabled) {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "two-factor authentication is already enabled".to_owned(),
+        }));
+    }
+    if existing.is_some() {
+        // restart an enrollment that was never confirmed
+        Totp::delete_by_id(user_id)
+            .exec(&txn)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    let secret = otp::generate_secret();
+    Totp::insert(totp::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        secret: ActiveValue::Set(secret.clone()),
+        enabled: ActiveValue::Set(false),
+        recovery_codes: ActiveValue::Set(Default::default()),
+        last_used_step: ActiveValue::Set(0),
+        created_at: ActiveValue::Set(timestamp::now()),
+    })
+    .exec(&txn)
+    .await
+    .kind(ErrorKind::Internal)?;
+
+    txn.commit().await.kind(ErrorKind::Internal)?;
+
+    let uri = otp::provisioning_uri(&secret, "llumen", &user.name);
+
+    Ok(Json(TotpSetupResp { secret, uri }))
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::prelude::*;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct TotpStatusReq {}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct TotpStatusResp {
+    pub enabled: bool,
+    pub recovery_codes_left: u32,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(_): Json<TotpStatusReq>,
+) -> JsonResult<TotpStatusResp> {
+    let model = Totp::find_by_id(user_id)
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .filter(|x| x.enabled);
+
+    Ok(Json(TotpStatusResp {
+        enabled: model.is_some(),
+        recovery_codes_left: model.map(|x| x.recovery_codes.0.len() as u32).unwrap_or(0),
+    }))
+}
@@ -0,0 +1,72 @@
+use std::{borrow::Cow, fmt::Write, sync::Arc};
+
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+
+use super::query::{UsageQueryReq, aggregate};
+use crate::{AppState, errors::*, middlewares::auth::UserId};
+
+/// Same report as `/usage/query`, as a CSV download.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(_)): Extension<UserId>,
+    Json(req): Json<UsageQueryReq>,
+) -> Result<Response, AppError> {
+    let list = aggregate(&app, req).await?;
+
+    let mut body =
+        String::from("period,user_id,username,model_id,model,cost,token_count,message_count\n");
+    for row in list {
+        let id = |x: Option<i32>| x.map(|x| x.to_string()).unwrap_or_default();
+        writeln!(
+            body,
+            "{},{},{},{},{},{},{},{}",
+            escape(row.period.as_deref().unwrap_or_default()),
+            id(row.user_id),
+            escape(row.username.as_deref().unwrap_or_default()),
+            id(row.model_id),
+            escape(row.model_name.as_deref().unwrap_or_default()),
+            row.cost,
+            row.token_count,
+            row.message_count
+        )
+        .kind(ErrorKind::Internal)?;
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/csv; charset=utf-8"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"usage.csv\""),
+    );
+
+    Ok((headers, body).into_response())
+}
+
+/// Quote a CSV field when needed (RFC 4180).
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("alice"), "alice");
+        assert_eq!(escape("Kimi, K3"), "\"Kimi, K3\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+use axum::{Router, middleware, routing::post};
+
+use crate::{AppState, middlewares::admin};
+
+mod csv;
+mod query;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/query", post(query::route))
+        .route("/csv", post(csv::route))
+        .route_layer(middleware::from_fn(admin::handle))
+}
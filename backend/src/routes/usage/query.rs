@@ -0,0 +1,196 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{Extension, Json, extract::State};
+use entity::{chat, message, prelude::*};
+use protocol::ModelConfig;
+use sea_orm::{
+    ConnectionTrait, EntityTrait, FromQueryResult, QuerySelect,
+    sea_query::{Alias, Expr, Order, Query, SimpleExpr},
+};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::auth::UserId,
+    utils::{model::ModelChecker, timestamp},
+};
+
+// range used when the request leaves `since` out: 30 days
+const DEFAULT_RANGE_SECS: i64 = 60 * 60 * 24 * 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    User,
+    /// Model currently selected by the chat
+    Model,
+    Day,
+    /// Weeks start on Monday
+    Week,
+}
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct UsageQueryReq {
+    /// RFC 3339, inclusive, default to 30 days before `until`
+    pub since: Option<String>,
+    /// RFC 3339, exclusive, default to now
+    pub until: Option<String>,
+    /// Empty for a single total row
+    pub group_by: Vec<UsageGroupBy>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct UsageQueryResp {
+    pub list: Vec<UsageQueryRespRow>,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct UsageQueryRespRow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// First day (YYYY-MM-DD, UTC) of the day or week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    pub cost: f64,
+    pub token_count: i32,
+    pub message_count: i32,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct UsageRow {
+    user_id: Option<i32>,
+    model_id: Option<i32>,
+    period: Option<String>,
+    cost: f64,
+    token_count: i64,
+    message_count: i64,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(_)): Extension<UserId>,
+    Json(req): Json<UsageQueryReq>,
+) -> JsonResult<UsageQueryResp> {
+    let list = aggregate(&app, req).await?;
+    Ok(Json(UsageQueryResp { list }))
+}
+
+/// Sum cost, tokens and messages over the requested range and groups.
+pub(super) async fn aggregate(
+    app: &AppState,
+    req: UsageQueryReq,
+) -> Result<Vec<UsageQueryRespRow>, AppError> {
+    let parse = |x: Option<String>| match x {
+        Some(x) => timestamp::parse(&x)
+            .map(Some)
+            .ok_or_else(|| format!("invalid timestamp {}", x)),
+        None => Ok(None),
+    };
+    let until = parse(req.until)
+        .kind(ErrorKind::MalformedRequest)?
+        .unwrap_or_else(timestamp::now);
+    let since = parse(req.since)
+        .kind(ErrorKind::MalformedRequest)?
+        .unwrap_or(until - DEFAULT_RANGE_SECS);
+
+    let group_by = |x| req.group_by.contains(&x);
+    let user = group_by(UsageGroupBy::User)
+        .then(|| SimpleExpr::from(Expr::col((chat::Entity, chat::Column::OwnerId))));
+    let model = group_by(UsageGroupBy::Model)
+        .then(|| SimpleExpr::from(Expr::col((chat::Entity, chat::Column::ModelId))));
+    let period = if group_by(UsageGroupBy::Week) {
+        // move to the coming sunday, then back to its monday
+        Some(Expr::cust(
+            r#"date("message"."created_at", 'unixepoch', 'weekday 0', '-6 days')"#,
+        ))
+    } else if group_by(UsageGroupBy::Day) {
+        Some(Expr::cust(r#"date("message"."created_at", 'unixepoch')"#))
+    } else {
+        None
+    };
+
+    let created_at = Expr::col((message::Entity, message::Column::CreatedAt));
+    let null = || Expr::cust("NULL");
+    let mut query = Query::select();
+    query
+        .from(message::Entity)
+        .inner_join(
+            chat::Entity,
+            Expr::col((chat::Entity, chat::Column::Id))
+                .equals((message::Entity, message::Column::ChatId)),
+        )
+        .expr_as(user.clone().unwrap_or_else(null), Alias::new("user_id"))
+        .expr_as(model.clone().unwrap_or_else(null), Alias::new("model_id"))
+        .expr_as(period.clone().unwrap_or_else(null), Alias::new("period"))
+        .expr_as(
+            Expr::cust(r#"COALESCE(SUM("message"."price"), 0.0)"#),
+            Alias::new("cost"),
+        )
+        .expr_as(
+            Expr::cust(r#"COALESCE(SUM("message"."token_count"), 0)"#),
+            Alias::new("token_count"),
+        )
+        .expr_as(
+            Expr::col((message::Entity, message::Column::Id)).count(),
+            Alias::new("message_count"),
+        )
+        .and_where(created_at.clone().gte(since))
+        .and_where(created_at.lt(until))
+        .add_group_by([user, model, period].into_iter().flatten())
+        .order_by(Alias::new("period"), Order::Asc)
+        .order_by(Alias::new("cost"), Order::Desc);
+
+    let backend = app.conn.get_database_backend();
+    let rows = UsageRow::find_by_statement(backend.build(&query))
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    let usernames = User::find()
+        .select_only()
+        .columns([entity::user::Column::Id, entity::user::Column::Name])
+        .into_tuple::<(i32, String)>()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let model_names = Model::find()
+        .all(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?
+        .into_iter()
+        .filter_map(|x| {
+            let config = <ModelConfig as ModelChecker>::from_toml(&x.config).ok()?;
+            Some((x.id, config.display_name))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let list = rows
+        .into_iter()
+        .map(|x| UsageQueryRespRow {
+            username: x.user_id.and_then(|id| usernames.get(&id).cloned()),
+            user_id: x.user_id,
+            model_name: x.model_id.and_then(|id| model_names.get(&id).cloned()),
+            model_id: x.model_id,
+            period: x.period,
+            cost: x.cost,
+            token_count: x.token_count.min(i32::MAX as i64) as i32,
+            message_count: x.message_count.min(i32::MAX as i64) as i32,
+        })
+        .collect();
+
+    Ok(list)
+}
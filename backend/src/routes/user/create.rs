@@ -2,17 +2,25 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::{prelude::*, user};
+use protocol::{AuditAction, UserRole};
 use sea_orm::{ActiveValue, EntityTrait};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
 pub struct UserCreateReq {
     pub username: String,
     pub password: String,
+    /// Defaults to member
+    pub role: Option<UserRole>,
 }
 
 #[derive(Debug, Serialize)]
@@ -23,13 +31,19 @@ pub struct UserCreateResp {
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
-    Extension(UserId(_)): Extension<UserId>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<UserCreateReq>,
 ) -> JsonResult<UserCreateResp> {
+    app.password_policy
+        .check(&req.username, &req.password)
+        .kind(ErrorKind::WeakPassword)?;
+
     let password_hash = app.hasher.hash_password(&req.password);
     let new_user = user::ActiveModel {
-        name: ActiveValue::Set(req.username),
+        name: ActiveValue::Set(req.username.clone()),
         password: ActiveValue::Set(password_hash),
+        role: ActiveValue::Set(req.role.unwrap_or_default()),
         ..Default::default()
     };
 
@@ -38,6 +52,14 @@ pub async fn route(
         .await
         .kind(ErrorKind::Internal)?;
 
+    Audit::new(AuditAction::UserCreate)
+        .actor(user_id)
+        .target(new_user.last_insert_id)
+        .detail(req.username)
+        .ip(ip)
+        .record(&app.conn)
+        .await;
+
     Ok(Json(UserCreateResp {
         user_id: new_user.last_insert_id,
     }))
@@ -2,11 +2,17 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::prelude::*;
+use protocol::AuditAction;
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -23,6 +29,7 @@ pub struct UserDeleteResp {
 pub async fn route(
     State(app): State<Arc<AppState>>,
     Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<UserDeleteReq>,
 ) -> JsonResult<UserDeleteResp> {
     if user_id == req.user_id {
@@ -36,9 +43,16 @@ pub async fn route(
         .await
         .kind(ErrorKind::Internal)?;
 
-    log::info!("user({}) is deleted by {}", req.user_id, user_id);
+    let deleted = res.rows_affected == 1;
+    if deleted {
+        log::info!("user({}) is deleted by {}", req.user_id, user_id);
+        Audit::new(AuditAction::UserDelete)
+            .actor(user_id)
+            .target(req.user_id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
 
-    Ok(Json(UserDeleteResp {
-        deleted: res.rows_affected == 1,
-    }))
+    Ok(Json(UserDeleteResp { deleted }))
 }
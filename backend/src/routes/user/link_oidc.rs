@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use entity::{oidc_identity, prelude::*};
+use sea_orm::{ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct UserLinkOidcReq {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct UserLinkOidcResp {}
+
+/// Finish an OIDC login started with `/auth/oidc/start` and link the identity
+/// to the current user instead of signing in.
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    Json(req): Json<UserLinkOidcReq>,
+) -> JsonResult<UserLinkOidcResp> {
+    let oidc = app
+        .oidc
+        .as_ref()
+        .ok_or("OIDC is not configured")
+        .kind(ErrorKind::ResourceNotFound)?;
+
+    let claims = oidc
+        .exchange(&req.code, &req.state)
+        .await
+        .kind(ErrorKind::LoginFail)?;
+    let (issuer, subject) = oidc
+        .identity(&claims)
+        .ok_or("missing claim sub")
+        .kind(ErrorKind::LoginFail)?;
+
+    let linked = OidcIdentity::find()
+        .filter(oidc_identity::Column::Issuer.eq(issuer.as_str()))
+        .filter(oidc_identity::Column::Subject.eq(subject.as_str()))
+        .one(&app.conn)
+        .await
+        .kind(ErrorKind::Internal)?;
+
+    match linked {
+        Some(x) if x.user_id == user_id => {}
+        Some(_) => {
+            return Err(Json(Error {
+                error: ErrorKind::Forbidden,
+                reason: "identity is linked to another user".to_owned(),
+            }));
+        }
+        None => {
+            OidcIdentity::insert(oidc_identity::ActiveModel {
+                user_id: ActiveValue::Set(user_id),
+                issuer: ActiveValue::Set(issuer),
+                subject: ActiveValue::Set(subject),
+                created_at: ActiveValue::Set(timestamp::now()),
+                ..Default::default()
+            })
+            .exec(&app.conn)
+            .await
+            .kind(ErrorKind::Internal)?;
+        }
+    }
+
+    Ok(Json(UserLinkOidcResp {}))
+}
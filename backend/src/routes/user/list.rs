@@ -2,11 +2,12 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::user;
+use protocol::UserRole;
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{AppState, errors::*, middlewares::auth::UserId, utils::timestamp};
 
 #[derive(Debug, Serialize)]
 #[typeshare]
@@ -19,6 +20,10 @@ pub struct UserListResp {
 pub struct UserList {
     pub id: i32,
     pub name: String,
+    pub role: UserRole,
+    pub display_name: Option<String>,
+    /// Set while the account is locked after repeated login failures
+    pub locked_until: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,14 +39,20 @@ pub async fn route(
         .all(&app.conn)
         .await
         .kind(ErrorKind::Internal)?;
-    let list = models
-        .into_iter()
-        .filter_map(|m| {
-            Some(UserList {
-                id: m.id,
-                name: m.name,
-            })
-        })
-        .collect::<Vec<_>>();
+    let mut list = Vec::with_capacity(models.len());
+    for m in models {
+        let locked_until = app
+            .throttle
+            .locked_for(m.id)
+            .await
+            .map(|x| timestamp::format(timestamp::now() + x.as_secs() as i64));
+        list.push(UserList {
+            id: m.id,
+            name: m.name,
+            role: m.role,
+            display_name: m.display_name,
+            locked_until,
+        });
+    }
     Ok(Json(UserListResp { list }))
 }
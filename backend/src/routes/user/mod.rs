@@ -1,20 +1,96 @@
 use std::sync::Arc;
 
-use axum::{Router, routing::post};
+use axum::{Router, middleware, routing::post};
 
-use crate::AppState;
+use crate::{AppState, middlewares::admin};
 
 mod create;
 mod delete;
+mod link_oidc;
 mod list;
 mod read;
+mod unlock;
 mod update;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/create", post(create::route))
         .route("/delete", post(delete::route))
+        .route("/list", post(list::route))
+        .route("/unlock", post(unlock::route))
+        .route_layer(middleware::from_fn(admin::handle))
+        .route("/link_oidc", post(link_oidc::route))
         .route("/read", post(read::route))
         .route("/update", post(update::route))
-        .route("/list", post(list::route))
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{prelude::*, user};
+    use protocol::UserRole;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use serde_json::json;
+
+    use super::*;
+    use crate::routes::testing;
+
+    #[tokio::test]
+    async fn test_members_cannot_manage_users() {
+        let app = testing::state().await;
+        let member = testing::insert_user(&app, "member", UserRole::Member).await;
+        let other = testing::insert_user(&app, "other", UserRole::Member).await;
+
+        for (path, body) in [
+            (
+                "/create",
+                json!({ "username": "intruder", "password": "correct horse battery" }),
+            ),
+            ("/delete", json!({ "user_id": other.id })),
+            ("/list", json!({})),
+            ("/unlock", json!({ "user_id": other.id })),
+        ] {
+            let res = testing::post(&app, routes(), &member, path, body).await;
+            assert_eq!(res["error"], "forbidden", "{path}: {res}");
+        }
+
+        let intruder = User::find()
+            .filter(user::Column::Name.eq("intruder"))
+            .one(&app.conn)
+            .await
+            .unwrap();
+        assert!(intruder.is_none());
+        let other = User::find_by_id(other.id).one(&app.conn).await.unwrap();
+        assert!(other.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admins_pass_the_guard() {
+        let app = testing::state().await;
+        let admin = testing::insert_user(&app, "root", UserRole::Admin).await;
+
+        let res = testing::post(&app, routes(), &admin, "/list", json!({})).await;
+        let names = res["list"].as_array().expect("list of users");
+        assert!(names.iter().any(|x| x["name"] == "root"));
+    }
+
+    #[tokio::test]
+    async fn test_members_cannot_raise_their_role() {
+        let app = testing::state().await;
+        let member = testing::insert_user(&app, "member", UserRole::Member).await;
+
+        for body in [
+            json!({ "role": "admin" }),
+            json!({ "user_id": member.id, "role": "admin" }),
+        ] {
+            let res = testing::post(&app, routes(), &member, "/update", body).await;
+            assert_eq!(res["error"], "forbidden", "{res}");
+        }
+
+        let member = User::find_by_id(member.id)
+            .one(&app.conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(member.role, UserRole::Member);
+    }
 }
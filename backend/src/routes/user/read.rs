@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
 use entity::prelude::*;
-use protocol::UserPreference;
+use protocol::{UserPreference, UserRole};
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
@@ -22,16 +22,28 @@ pub struct UserReadResp {
     pub user_id: i32,
     pub username: String,
     pub preference: UserPreference,
+    pub role: UserRole,
+    pub display_name: Option<String>,
+    /// The user has to change the password before doing anything else
+    pub must_change_password: bool,
 }
 
 pub async fn route(
     State(app): State<Arc<AppState>>,
     Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
     Json(req): Json<UserReadReq>,
 ) -> JsonResult<UserReadResp> {
-    let user_id = req.user_id.unwrap_or(user_id);
+    let target_id = req.user_id.unwrap_or(user_id);
 
-    let res = User::find_by_id(user_id)
+    if target_id != user_id && role != UserRole::Admin {
+        return Err(Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: "only administrators can read other users".to_owned(),
+        }));
+    }
+
+    let res = User::find_by_id(target_id)
         .one(&app.conn)
         .await
         .kind(ErrorKind::Internal)?
@@ -42,5 +54,8 @@ pub async fn route(
         user_id: res.id,
         username: res.name,
         preference: res.preference,
+        role: res.role,
+        display_name: res.display_name,
+        must_change_password: res.must_change_password,
     }))
 }
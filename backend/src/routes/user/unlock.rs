@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State};
+use protocol::AuditAction;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::audit::Audit,
+};
+
+#[derive(Debug, Deserialize)]
+#[typeshare]
+pub struct UserUnlockReq {
+    pub user_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[typeshare]
+pub struct UserUnlockResp {
+    /// Whether the account was locked
+    pub unlocked: bool,
+}
+
+pub async fn route(
+    State(app): State<Arc<AppState>>,
+    Extension(UserId(user_id)): Extension<UserId>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<UserUnlockReq>,
+) -> JsonResult<UserUnlockResp> {
+    let unlocked = app.throttle.unlock(req.user_id).await;
+
+    if unlocked {
+        log::info!("user({}) is unlocked by {}", req.user_id, user_id);
+        Audit::new(AuditAction::UserUnlock)
+            .actor(user_id)
+            .target(req.user_id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(UserUnlockResp { unlocked }))
+}
@@ -1,13 +1,20 @@
 use std::sync::Arc;
 
 use axum::{Extension, Json, extract::State};
-use entity::prelude::*;
-use protocol::UserPreference;
-use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, TransactionTrait};
+use entity::{prelude::*, session};
+use protocol::{AuditAction, UserPreference, UserRole};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::{AppState, errors::*, middlewares::auth::UserId};
+use crate::{
+    AppState,
+    errors::*,
+    middlewares::{auth::UserId, client_ip::ClientIp},
+    utils::{audit::Audit, chat},
+};
 
 #[derive(Debug, Deserialize)]
 #[typeshare]
@@ -16,6 +23,8 @@ pub struct UserUpdateReq {
     pub user_id: Option<i32>,
     pub preference: Option<UserPreference>,
     pub password: Option<String>,
+    /// Only administrators can change roles, and never their own
+    pub role: Option<UserRole>,
 }
 
 #[derive(Debug, Serialize)]
@@ -27,31 +36,80 @@ pub struct UserUpdateResp {
 pub async fn route(
     State(app): State<Arc<AppState>>,
     Extension(UserId(user_id)): Extension<UserId>,
+    Extension(role): Extension<UserRole>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<UserUpdateReq>,
 ) -> JsonResult<UserUpdateResp> {
     let UserUpdateReq {
         user_id: user_id_req,
         preference,
         password,
+        role: new_role,
     } = req;
-    let user_id = user_id_req.unwrap_or(user_id);
+    let target_id = user_id_req.unwrap_or(user_id);
+
+    if target_id != user_id && role != UserRole::Admin {
+        return Err(Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: "only administrators can update other users".to_owned(),
+        }));
+    }
+    if new_role.is_some() && (role != UserRole::Admin || target_id == user_id) {
+        return Err(Json(Error {
+            error: ErrorKind::Forbidden,
+            reason: "cannot change the role of this user".to_owned(),
+        }));
+    }
 
     // Please note that update to same value does not result in error
     // But update no value does result in error
     debug_assert!(
-        preference.is_some() || password.is_some(),
+        preference.is_some() || password.is_some() || new_role.is_some(),
         "no field to update"
     );
 
+    if preference
+        .as_ref()
+        .and_then(|x| x.custom_instructions.as_ref())
+        .is_some_and(|x| !chat::instructions_fit(x))
+    {
+        return Err(Json(Error {
+            error: ErrorKind::MalformedRequest,
+            reason: "custom instructions are too long".to_owned(),
+        }));
+    }
+
     let txn = app.conn.begin().await.kind(ErrorKind::Internal)?;
 
-    let res = User::find_by_id(user_id)
+    let res = User::find_by_id(target_id)
         .one(&txn)
         .await
         .kind(ErrorKind::Internal)?
         .ok_or("")
         .kind(ErrorKind::ResourceNotFound)?;
 
+    if target_id == user_id
+        && res.must_change_password
+        && (password.is_none() || preference.is_some() || new_role.is_some())
+    {
+        return Err(Json(Error {
+            error: ErrorKind::PasswordChangeRequired,
+            reason: "only the password can be changed".to_owned(),
+        }));
+    }
+    if let Some(password) = &password {
+        app.password_policy
+            .check(&res.name, password)
+            .kind(ErrorKind::WeakPassword)?;
+        if res.must_change_password && app.hasher.verify_password(&res.password, password) {
+            return Err(Json(Error {
+                error: ErrorKind::WeakPassword,
+                reason: "new password must differ from the current one".to_owned(),
+            }));
+        }
+    }
+
+    let password_changed = password.is_some();
     let mut active_model = res.into_active_model();
 
     // merge two preferences
@@ -67,16 +125,52 @@ pub async fn route(
         if let Some(language) = preference.submit_on_enter {
             new_preference.submit_on_enter = Some(language);
         }
+        if let Some(instructions) = preference.custom_instructions {
+            // blank fields clear the instruction
+            new_preference.custom_instructions = Some(instructions.normalized());
+        }
         active_model.preference = sea_orm::ActiveValue::Set(new_preference);
     }
     if let Some(password) = password {
         let password_hash = app.hasher.hash_password(&password);
         active_model.password = sea_orm::ActiveValue::Set(password_hash);
+        if target_id == user_id {
+            active_model.must_change_password = sea_orm::ActiveValue::Set(false);
+        }
+
+        // a password change signs the user out everywhere
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(target_id))
+            .exec(&txn)
+            .await
+            .kind(ErrorKind::Internal)?;
+    }
+
+    if let Some(new_role) = new_role {
+        active_model.role = sea_orm::ActiveValue::Set(new_role);
     }
 
     active_model.update(&txn).await.kind(ErrorKind::Internal)?;
 
     txn.commit().await.kind(ErrorKind::Internal)?;
 
-    Ok(Json(UserUpdateResp { user_id }))
+    if password_changed {
+        Audit::new(AuditAction::UserPasswordChange)
+            .actor(user_id)
+            .target(target_id)
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+    if let Some(new_role) = new_role {
+        Audit::new(AuditAction::UserRoleChange)
+            .actor(user_id)
+            .target(target_id)
+            .detail(format!("{:?}", new_role))
+            .ip(ip)
+            .record(&app.conn)
+            .await;
+    }
+
+    Ok(Json(UserUpdateResp { user_id: target_id }))
 }
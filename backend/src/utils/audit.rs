@@ -0,0 +1,73 @@
+//! Append-only record of administrative and security-relevant actions.
+//!
+//! Entries are written best effort: a failed write is logged but never fails
+//! the request that caused it.
+
+use std::net::IpAddr;
+
+use entity::audit_log;
+use protocol::AuditAction;
+use sea_orm::{ActiveValue, ConnectionTrait, EntityTrait};
+
+use crate::utils::timestamp;
+
+#[derive(Debug, Clone)]
+pub struct Audit {
+    action: AuditAction,
+    actor_id: Option<i32>,
+    target_id: Option<i32>,
+    detail: Option<String>,
+    ip: Option<IpAddr>,
+}
+
+impl Audit {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            action,
+            actor_id: None,
+            target_id: None,
+            detail: None,
+            ip: None,
+        }
+    }
+
+    /// User who performed the action
+    pub fn actor(mut self, user_id: i32) -> Self {
+        self.actor_id = Some(user_id);
+        self
+    }
+
+    /// Id of what the action applies to, see [`AuditAction::target`]
+    pub fn target(mut self, id: i32) -> Self {
+        self.target_id = Some(id);
+        self
+    }
+
+    /// Free-form context, such as the username of a failed login
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn ip(mut self, ip: IpAddr) -> Self {
+        self.ip = Some(ip);
+        self
+    }
+
+    pub async fn record<C: ConnectionTrait>(self, conn: &C) {
+        let action = self.action;
+        let model = audit_log::ActiveModel {
+            actor_id: ActiveValue::Set(self.actor_id),
+            action: ActiveValue::Set(self.action),
+            target_id: ActiveValue::Set(self.target_id),
+            detail: ActiveValue::Set(self.detail),
+            ip: ActiveValue::Set(self.ip.map(|x| x.to_string())),
+            created_at: ActiveValue::Set(timestamp::now()),
+            ..Default::default()
+        };
+
+        if let Err(e) = audit_log::Entity::insert(model).exec(conn).await {
+            log::error!("cannot write audit log entry {:?}: {}", action, e);
+        }
+    }
+}
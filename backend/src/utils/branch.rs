@@ -0,0 +1,169 @@
+//! Tree-shaped message history.
+//!
+//! Every message points at the one it answers or follows, so editing a prompt
+//! or regenerating a reply adds a sibling instead of replacing the old branch.
+//! A chat remembers the last message of the branch it shows, the active path
+//! is found by walking parents up from there.
+
+use std::collections::HashMap;
+
+use entity::{chat, message, prelude::*};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect, sea_query::Expr,
+};
+
+/// `(id, parent_id)` of a message
+pub type Node = (i32, Option<i32>);
+
+/// Ids from the root down to `leaf`, in order.
+pub fn active_path(nodes: &[Node], leaf: i32) -> Vec<i32> {
+    let parents = nodes.iter().copied().collect::<HashMap<_, _>>();
+    let mut path = Vec::new();
+    let mut current = parents.contains_key(&leaf).then_some(leaf);
+    while let Some(id) = current {
+        path.push(id);
+        // parents always have smaller ids, this also stops corrupted cycles
+        current = parents[&id].filter(|x| *x < id && parents.contains_key(x));
+    }
+    path.reverse();
+    path
+}
+
+/// Follow the newest child from `from` down to a leaf.
+pub fn newest_leaf(nodes: &[Node], from: i32) -> i32 {
+    let mut leaf = from;
+    while let Some(child) = nodes
+        .iter()
+        .filter(|(_, parent)| *parent == Some(leaf))
+        .map(|(id, _)| *id)
+        .max()
+    {
+        leaf = child;
+    }
+    leaf
+}
+
+/// `root` and every message below it.
+pub fn subtree(nodes: &[Node], root: i32) -> Vec<i32> {
+    let mut ids = vec![root];
+    // children are newer than their parent, one pass in id order is enough
+    let mut sorted = nodes.to_vec();
+    sorted.sort_unstable();
+    for (id, parent) in sorted {
+        if parent.is_some_and(|x| ids.contains(&x)) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// Messages sharing the parent of `id`, including itself, oldest first.
+pub fn siblings(nodes: &[Node], id: i32) -> Vec<i32> {
+    let Some(parent) = nodes.iter().find(|x| x.0 == id).map(|x| x.1) else {
+        return Vec::new();
+    };
+    let mut ids = nodes
+        .iter()
+        .filter(|x| x.1 == parent)
+        .map(|x| x.0)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids
+}
+
+/// Load the shape of the message tree of `chat_id`.
+pub async fn nodes<C: ConnectionTrait>(conn: &C, chat_id: i32) -> Result<Vec<Node>, DbErr> {
+    Message::find()
+        .select_only()
+        .column(message::Column::Id)
+        .column(message::Column::ParentId)
+        .filter(message::Column::ChatId.eq(chat_id))
+        .into_tuple::<Node>()
+        .all(conn)
+        .await
+}
+
+/// Last message of the active branch, falling back to the newest message.
+pub fn leaf(chat: &chat::Model, nodes: &[Node]) -> Option<i32> {
+    chat.leaf_id
+        .filter(|x| nodes.iter().any(|n| n.0 == *x))
+        .or_else(|| nodes.iter().map(|x| x.0).max())
+}
+
+/// Make `leaf_id` the end of the active branch of `chat_id`.
+pub async fn set_leaf<C: ConnectionTrait>(
+    conn: &C,
+    chat_id: i32,
+    leaf_id: Option<i32>,
+) -> Result<(), DbErr> {
+    Chat::update(chat::ActiveModel {
+        id: sea_orm::ActiveValue::Set(chat_id),
+        leaf_id: sea_orm::ActiveValue::Set(leaf_id),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+/// Set the leaf of a chat back to `leaf_id`, unless it moved on from
+/// `current` meanwhile.
+pub async fn reset_leaf<C: ConnectionTrait>(
+    conn: &C,
+    chat_id: i32,
+    current: i32,
+    leaf_id: Option<i32>,
+) -> Result<(), DbErr> {
+    Chat::update_many()
+        .col_expr(chat::Column::LeafId, Expr::value(leaf_id))
+        .filter(chat::Column::Id.eq(chat_id))
+        .filter(chat::Column::LeafId.eq(current))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //     1
+    //     2
+    //   3   5
+    //   4   6
+    //       7  8
+    fn tree() -> Vec<Node> {
+        vec![
+            (1, None),
+            (2, Some(1)),
+            (3, Some(2)),
+            (4, Some(3)),
+            (5, Some(2)),
+            (6, Some(5)),
+            (7, Some(6)),
+            (8, Some(6)),
+        ]
+    }
+
+    #[test]
+    fn test_active_path() {
+        assert_eq!(active_path(&tree(), 4), vec![1, 2, 3, 4]);
+        assert_eq!(active_path(&tree(), 7), vec![1, 2, 5, 6, 7]);
+        assert_eq!(active_path(&tree(), 42), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_newest_leaf() {
+        assert_eq!(newest_leaf(&tree(), 3), 4);
+        assert_eq!(newest_leaf(&tree(), 2), 8);
+        assert_eq!(newest_leaf(&tree(), 8), 8);
+    }
+
+    #[test]
+    fn test_subtree_and_siblings() {
+        assert_eq!(subtree(&tree(), 5), vec![5, 6, 7, 8]);
+        assert_eq!(subtree(&tree(), 4), vec![4]);
+        assert_eq!(siblings(&tree(), 5), vec![3, 5]);
+        assert_eq!(siblings(&tree(), 1), vec![1]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Per-user spending budgets.
+//!
+//! Spending is recorded in its own table when a completion is saved, so the
+//! total survives chat deletion. A user without a budget row is unlimited.
+
+use entity::{prelude::*, spending};
+use protocol::BudgetPeriod;
+use sea_orm::{ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect};
+use time::{OffsetDateTime, Time};
+
+const SECS_PER_DAY: i64 = 60 * 60 * 24;
+
+/// Spending of a user against their budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub limit: f64,
+    pub warn: Option<f64>,
+    pub spent: f64,
+    /// Start of the period `spent` covers, as a unix timestamp
+    pub since: i64,
+}
+
+impl BudgetStatus {
+    pub fn exceeded(&self) -> bool {
+        self.spent >= self.limit
+    }
+
+    /// Whether the soft warning threshold is reached
+    pub fn warning(&self) -> bool {
+        self.warn.is_some_and(|x| self.spent >= x)
+    }
+}
+
+/// Returned when a completion is refused because the budget is used up.
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetExceeded {
+    pub spent: f64,
+    pub limit: f64,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "spending budget of ${:.2} is used up (${:.2} spent)",
+            self.limit, self.spent
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Start of the budget period containing `now`.
+pub fn period_start(period: BudgetPeriod, window_days: i32, now: i64) -> i64 {
+    match period {
+        BudgetPeriod::Monthly => OffsetDateTime::from_unix_timestamp(now)
+            .ok()
+            .and_then(|x| x.replace_day(1).ok())
+            .map(|x| x.replace_time(Time::MIDNIGHT).unix_timestamp())
+            .unwrap_or(now),
+        BudgetPeriod::Rolling => now - window_days.max(1) as i64 * SECS_PER_DAY,
+    }
+}
+
+/// Load the budget of `user_id` and what was spent in the current period.
+pub async fn status<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    now: i64,
+) -> Result<Option<BudgetStatus>, DbErr> {
+    let Some(budget) = Budget::find_by_id(user_id).one(conn).await? else {
+        return Ok(None);
+    };
+    let since = period_start(budget.period, budget.window_days, now);
+
+    let spent = Spending::find()
+        .select_only()
+        .column_as(spending::Column::Amount.sum(), "spent")
+        .filter(spending::Column::UserId.eq(user_id))
+        .filter(spending::Column::CreatedAt.gte(since))
+        .into_tuple::<Option<f64>>()
+        .one(conn)
+        .await?
+        .flatten()
+        .unwrap_or(0.0);
+
+    Ok(Some(BudgetStatus {
+        limit: budget.limit,
+        warn: budget.warn,
+        spent,
+        since,
+    }))
+}
+
+/// Why spending more is refused, if the budget of `user_id` is used up.
+pub async fn exceeded<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    now: i64,
+) -> Result<Option<BudgetExceeded>, DbErr> {
+    Ok(status(conn, user_id, now)
+        .await?
+        .filter(|x| x.exceeded())
+        .map(|x| BudgetExceeded {
+            spent: x.spent,
+            limit: x.limit,
+        }))
+}
+
+/// Add `amount` to the spending of `user_id`.
+pub async fn record<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    amount: f64,
+    now: i64,
+) -> Result<(), DbErr> {
+    if amount <= 0.0 {
+        return Ok(());
+    }
+    Spending::insert(spending::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        amount: ActiveValue::Set(amount),
+        created_at: ActiveValue::Set(now),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_start() {
+        // 2023-11-14T22:13:20Z
+        let now = 1_700_000_000;
+        assert_eq!(period_start(BudgetPeriod::Monthly, 30, now), 1_698_796_800);
+        assert_eq!(
+            period_start(BudgetPeriod::Rolling, 7, now),
+            now - 7 * SECS_PER_DAY
+        );
+        assert_eq!(
+            period_start(BudgetPeriod::Rolling, 0, now),
+            now - SECS_PER_DAY
+        );
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let status = BudgetStatus {
+            limit: 10.0,
+            warn: Some(8.0),
+            spent: 8.5,
+            since: 0,
+        };
+        assert!(status.warning());
+        assert!(!status.exceeded());
+        assert!(
+            BudgetStatus {
+                spent: 10.0,
+                ..status
+            }
+            .exceeded()
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use protocol::CustomInstructions;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
+use crate::config::MAX_CUSTOM_INSTRUCTIONS_LEN;
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[typeshare]
 #[serde(rename_all = "snake_case")]
@@ -32,3 +35,39 @@ impl From<ChatMode> for protocol::ModeKind {
         }
     }
 }
+
+/// Whether every field of `instructions` is within the length limit.
+pub fn instructions_fit(instructions: &CustomInstructions) -> bool {
+    [&instructions.about_me, &instructions.response_style]
+        .into_iter()
+        .flatten()
+        .all(|x| x.chars().count() <= MAX_CUSTOM_INSTRUCTIONS_LEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instructions_fit() {
+        let mut instructions = CustomInstructions {
+            about_me: Some("I'm a Rust engineer".to_owned()),
+            response_style: None,
+        };
+        assert!(instructions_fit(&instructions));
+
+        instructions.response_style = Some("é".repeat(MAX_CUSTOM_INSTRUCTIONS_LEN + 1));
+        assert!(!instructions_fit(&instructions));
+    }
+
+    #[test]
+    fn test_normalized() {
+        let instructions = CustomInstructions {
+            about_me: Some("  ".to_owned()),
+            response_style: Some("tersely".to_owned()),
+        }
+        .normalized();
+        assert_eq!(instructions.about_me, None);
+        assert_eq!(instructions.response_style.as_deref(), Some("tersely"));
+    }
+}
@@ -6,6 +6,8 @@
 
 use clap::Parser;
 
+use crate::config::DEFAULT_EMBEDDING_MODEL;
+
 /// Llumen Backend — LLM Chat Application Server
 #[derive(Parser, Debug)]
 #[command(name = "llumen")]
@@ -38,6 +40,81 @@ pub struct CliArgs {
     #[arg(short = 'H', long = "trusted-header", env = "TRUSTED_HEADER")]
     pub trusted_header: Option<String>,
 
+    /// Create users on first trusted header login.
+    /// Also settable via TRUSTED_HEADER_AUTO_CREATE=true env var.
+    #[arg(long = "trusted-header-auto-create", default_value_t = false, action = clap::ArgAction::SetTrue)]
+    pub trusted_header_auto_create: bool,
+
+    /// HTTP header carrying the display name of header-authenticated users.
+    #[arg(
+        long = "trusted-header-display-name",
+        env = "TRUSTED_HEADER_DISPLAY_NAME"
+    )]
+    pub trusted_header_display_name: Option<String>,
+
+    /// HTTP header carrying comma separated groups of header-authenticated users.
+    #[arg(long = "trusted-header-groups", env = "TRUSTED_HEADER_GROUPS")]
+    pub trusted_header_groups: Option<String>,
+
+    /// Comma separated groups whose members become administrators.
+    #[arg(
+        long = "trusted-header-admin-groups",
+        env = "TRUSTED_HEADER_ADMIN_GROUPS"
+    )]
+    pub trusted_header_admin_groups: Option<String>,
+
+    /// Comma separated addresses or CIDR ranges of reverse proxies whose
+    /// X-Forwarded-For header is trusted.
+    #[arg(long = "trusted-proxies", env = "TRUSTED_PROXIES")]
+    pub trusted_proxies: Option<String>,
+
+    /// Minimum length of passwords set by users (default 8).
+    #[arg(long = "password-min-length", env = "PASSWORD_MIN_LENGTH")]
+    pub password_min_length: Option<String>,
+
+    /// Accept passwords from the built-in list of common passwords.
+    /// Also settable via PASSWORD_ALLOW_COMMON=true env var.
+    #[arg(long = "password-allow-common", default_value_t = false, action = clap::ArgAction::SetTrue)]
+    pub password_allow_common: bool,
+
+    /// OpenID Connect issuer url, enables OIDC login together with client id
+    /// and redirect uri.
+    #[arg(long = "oidc-issuer", env = "OIDC_ISSUER")]
+    pub oidc_issuer: Option<String>,
+
+    /// OpenID Connect client id.
+    #[arg(long = "oidc-client-id", env = "OIDC_CLIENT_ID")]
+    pub oidc_client_id: Option<String>,
+
+    /// OpenID Connect client secret, omit for public clients.
+    #[arg(
+        long = "oidc-client-secret",
+        env = "OIDC_CLIENT_SECRET",
+        hide_env_values = true
+    )]
+    pub oidc_client_secret: Option<String>,
+
+    /// Redirect uri registered at the provider, e.g. https://llumen.example.com/login/oidc
+    #[arg(long = "oidc-redirect-uri", env = "OIDC_REDIRECT_URI")]
+    pub oidc_redirect_uri: Option<String>,
+
+    /// Space separated scopes requested from the provider.
+    #[arg(long = "oidc-scopes", env = "OIDC_SCOPES")]
+    pub oidc_scopes: Option<String>,
+
+    /// ID token claim used as llumen username.
+    #[arg(long = "oidc-username-claim", env = "OIDC_USERNAME_CLAIM")]
+    pub oidc_username_claim: Option<String>,
+
+    /// Create a member account on first OIDC login.
+    /// Also settable via OIDC_AUTO_CREATE=true env var.
+    #[arg(long = "oidc-auto-create", default_value_t = false, action = clap::ArgAction::SetTrue)]
+    pub oidc_auto_create: bool,
+
+    /// Model used to embed user memories and knowledge bases.
+    #[arg(long = "embedding-model", env = "EMBEDDING_MODEL", default_value_t = String::from(DEFAULT_EMBEDDING_MODEL))]
+    pub embedding_model: String,
+
     /// Log level filter.
     #[arg(short = 'l', long = "log-level", env = "RUST_LOG", default_value_t = String::from("info"))]
     pub log_level: String,
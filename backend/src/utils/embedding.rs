@@ -0,0 +1,127 @@
+//! Text embeddings shared by user memory and knowledge bases.
+//!
+//! Vectors are stored as little-endian f32 blobs next to the name of the
+//! model that produced them, since vectors of different models are not
+//! comparable.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{config::EMBEDDING_BATCH_SIZE, openrouter::Openrouter};
+
+pub struct Embedder {
+    openrouter: Arc<Openrouter>,
+    model: String,
+}
+
+impl Embedder {
+    pub fn new(openrouter: Arc<Openrouter>, model: String) -> Self {
+        Self { openrouter, model }
+    }
+
+    /// Name of the embedding model, stored with every vector.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Embed `input` in batches, one vector per input in the same order.
+    /// Also returns what the batches cost, in USD.
+    pub async fn embed(&self, input: &[String]) -> Result<(Vec<Vec<f32>>, f64)> {
+        let mut vectors = Vec::with_capacity(input.len());
+        let mut cost = 0.0;
+        for batch in input.chunks(EMBEDDING_BATCH_SIZE) {
+            let embedding = self.openrouter.embed(&self.model, batch).await?;
+            anyhow::ensure!(
+                embedding.response.len() == batch.len(),
+                "expected {} embeddings, got {}",
+                batch.len(),
+                embedding.response.len()
+            );
+            vectors.extend(embedding.response);
+            cost += embedding.price;
+        }
+        Ok((vectors, cost))
+    }
+
+    pub async fn embed_one(&self, input: &str) -> Result<(Vec<f32>, f64)> {
+        let (mut vectors, cost) = self.embed(&[input.to_owned()]).await?;
+        let vector = vectors
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("no embedding returned"))?;
+        Ok((vector, cost))
+    }
+}
+
+pub fn encode(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+        .collect()
+}
+
+/// Cosine similarity, zero for vectors of different length or zero norm.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm = norm(a) * norm(b);
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+/// Indices of the `limit` candidates most similar to `query`, best first,
+/// leaving out those below `min_similarity`.
+pub fn rank(
+    query: &[f32],
+    candidates: &[Vec<f32>],
+    limit: usize,
+    min_similarity: f32,
+) -> Vec<usize> {
+    let mut scored = candidates
+        .iter()
+        .map(|x| cosine(query, x))
+        .enumerate()
+        .filter(|(_, score)| *score >= min_similarity)
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().take(limit).map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let embedding = vec![0.5, -1.25, 3.0];
+        assert_eq!(encode(&embedding).len(), 12);
+        assert_eq!(decode(&encode(&embedding)), embedding);
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let candidates = vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.1],
+            vec![1.0, 1.0],
+            vec![-1.0, 0.0],
+        ];
+        assert_eq!(rank(&[1.0, 0.0], &candidates, 5, 0.3), vec![1, 2]);
+        assert_eq!(rank(&[1.0, 0.0], &candidates, 1, 0.3), vec![1]);
+        assert!(rank(&[1.0, 0.0], &[], 5, 0.3).is_empty());
+    }
+}
@@ -6,9 +6,28 @@
 
 use std::path::PathBuf;
 
+use crate::{
+    middlewares::client_ip::TrustedProxy,
+    utils::{oidc::OidcConfig, password_policy::PasswordPolicy},
+};
+
+use crate::config::DEFAULT_EMBEDDING_MODEL;
 #[cfg(not(feature = "cli"))]
 use crate::config::DEFAULT_BIND_ADDR;
 
+/// Optional behaviour of `TRUSTED_HEADER` logins.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderProvision {
+    /// Create a user the first time an unknown username shows up
+    pub auto_create: bool,
+    /// Header carrying the display name
+    pub display_name_header: Option<String>,
+    /// Header carrying comma separated group names
+    pub groups_header: Option<String>,
+    /// Members of any of these groups are administrators
+    pub admin_groups: Vec<String>,
+}
+
 /// All configuration values needed to start the server.
 ///
 /// Populated from environment variables (always) and CLI args (when the `cli`
@@ -20,7 +39,15 @@ pub struct Environment {
     pub data_path: PathBuf,
     pub bind_addr: String,
     pub auth_header: Option<String>,
+    pub header_provision: HeaderProvision,
+    /// Proxies whose `X-Forwarded-For` header is used to find the client address
+    pub trusted_proxies: Vec<TrustedProxy>,
+    pub password_policy: PasswordPolicy,
     pub log_level: String,
+    /// Model used to embed user memories and knowledge bases
+    pub embedding_model: String,
+    /// Set only when issuer, client id and redirect uri are all configured
+    pub oidc: Option<OidcConfig>,
 }
 
 impl Environment {
@@ -37,7 +64,35 @@ impl Environment {
         let data_path = PathBuf::from(dotenvy::var("DATA_PATH").unwrap_or_else(|_| ".".to_owned()));
         let bind_addr = dotenvy::var("BIND_ADDR").unwrap_or_else(|_| DEFAULT_BIND_ADDR.to_owned());
         let auth_header = dotenvy::var("TRUSTED_HEADER").ok();
+        let header_provision = HeaderProvision {
+            auto_create: dotenvy::var("TRUSTED_HEADER_AUTO_CREATE")
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false),
+            display_name_header: dotenvy::var("TRUSTED_HEADER_DISPLAY_NAME").ok(),
+            groups_header: dotenvy::var("TRUSTED_HEADER_GROUPS").ok(),
+            admin_groups: Self::split_groups(dotenvy::var("TRUSTED_HEADER_ADMIN_GROUPS").ok()),
+        };
+        let trusted_proxies = Self::parse_trusted_proxies(dotenvy::var("TRUSTED_PROXIES").ok());
+        let password_policy = Self::password_policy(
+            dotenvy::var("PASSWORD_MIN_LENGTH").ok(),
+            dotenvy::var("PASSWORD_ALLOW_COMMON")
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false),
+        );
         let log_level = dotenvy::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+        let embedding_model =
+            dotenvy::var("EMBEDDING_MODEL").unwrap_or_else(|_| DEFAULT_EMBEDDING_MODEL.to_owned());
+        let oidc = Self::oidc_config(
+            dotenvy::var("OIDC_ISSUER").ok(),
+            dotenvy::var("OIDC_CLIENT_ID").ok(),
+            dotenvy::var("OIDC_CLIENT_SECRET").ok(),
+            dotenvy::var("OIDC_REDIRECT_URI").ok(),
+            dotenvy::var("OIDC_SCOPES").ok(),
+            dotenvy::var("OIDC_USERNAME_CLAIM").ok(),
+            dotenvy::var("OIDC_AUTO_CREATE")
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false),
+        );
 
         Self {
             api_key,
@@ -46,7 +101,12 @@ impl Environment {
             data_path,
             bind_addr,
             auth_header,
+            header_provision,
+            trusted_proxies,
+            password_policy,
             log_level,
+            embedding_model,
+            oidc,
         }
     }
 
@@ -86,8 +146,55 @@ impl Environment {
             .clone()
             .or_else(|| dotenvy::var("TRUSTED_HEADER").ok());
 
+        let header_provision = HeaderProvision {
+            auto_create: if cli.trusted_header_auto_create {
+                true
+            } else {
+                dotenvy::var("TRUSTED_HEADER_AUTO_CREATE")
+                    .ok()
+                    .map(|v| v.to_lowercase() == "true" || v == "1")
+                    .unwrap_or(false)
+            },
+            display_name_header: cli.trusted_header_display_name.clone(),
+            groups_header: cli.trusted_header_groups.clone(),
+            admin_groups: Self::split_groups(cli.trusted_header_admin_groups.clone()),
+        };
+
+        let trusted_proxies = Self::parse_trusted_proxies(cli.trusted_proxies.clone());
+
+        let password_allow_common = if cli.password_allow_common {
+            true
+        } else {
+            dotenvy::var("PASSWORD_ALLOW_COMMON")
+                .ok()
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false)
+        };
+        let password_policy =
+            Self::password_policy(cli.password_min_length.clone(), password_allow_common);
+
         let log_level = cli.log_level.clone();
 
+        let embedding_model = cli.embedding_model.clone();
+
+        let oidc_auto_create = if cli.oidc_auto_create {
+            true
+        } else {
+            dotenvy::var("OIDC_AUTO_CREATE")
+                .ok()
+                .map(|v| v.to_lowercase() == "true" || v == "1")
+                .unwrap_or(false)
+        };
+        let oidc = Self::oidc_config(
+            cli.oidc_issuer.clone(),
+            cli.oidc_client_id.clone(),
+            cli.oidc_client_secret.clone(),
+            cli.oidc_redirect_uri.clone(),
+            cli.oidc_scopes.clone(),
+            cli.oidc_username_claim.clone(),
+            oidc_auto_create,
+        );
+
         Self {
             api_key,
             api_base,
@@ -95,10 +202,86 @@ impl Environment {
             data_path,
             bind_addr,
             auth_header,
+            header_provision,
+            trusted_proxies,
+            password_policy,
             log_level,
+            embedding_model,
+            oidc,
+        }
+    }
+
+    fn split_groups(groups: Option<String>) -> Vec<String> {
+        groups
+            .unwrap_or_default()
+            .split(',')
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty())
+            .collect()
+    }
+
+    fn parse_trusted_proxies(proxies: Option<String>) -> Vec<TrustedProxy> {
+        let proxies = proxies
+            .unwrap_or_default()
+            .split(',')
+            .filter(|x| !x.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>();
+
+        match proxies {
+            Ok(proxies) => proxies,
+            Err(e) => {
+                println!("Error: invalid TRUSTED_PROXIES entry {}", e);
+                println!(
+                    "Expected comma separated addresses or CIDR ranges, e.g. 127.0.0.1,10.0.0.0/8"
+                );
+                std::process::exit(1);
+            }
         }
     }
 
+    fn password_policy(min_length: Option<String>, allow_common: bool) -> PasswordPolicy {
+        let default = PasswordPolicy::default();
+        let min_length = match min_length.map(|x| x.trim().parse::<usize>()) {
+            None => default.min_length,
+            Some(Ok(min_length)) => min_length,
+            Some(Err(e)) => {
+                println!("Error: invalid PASSWORD_MIN_LENGTH: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        PasswordPolicy {
+            min_length,
+            reject_common: !allow_common,
+        }
+    }
+
+    fn oidc_config(
+        issuer: Option<String>,
+        client_id: Option<String>,
+        client_secret: Option<String>,
+        redirect_uri: Option<String>,
+        scopes: Option<String>,
+        username_claim: Option<String>,
+        auto_create: bool,
+    ) -> Option<OidcConfig> {
+        let (Some(issuer), Some(client_id), Some(redirect_uri)) = (issuer, client_id, redirect_uri)
+        else {
+            return None;
+        };
+
+        Some(OidcConfig {
+            issuer,
+            client_id,
+            client_secret,
+            redirect_uri,
+            scopes: scopes.unwrap_or_else(|| "openid profile email".to_owned()),
+            username_claim: username_claim.unwrap_or_else(|| "preferred_username".to_owned()),
+            auto_create,
+        })
+    }
+
     fn print_api_key_help() {
         println!("Error: API_KEY environment variable not found.");
         println!("Note: llumen read environment variable as well as .env file.");
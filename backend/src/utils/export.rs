@@ -0,0 +1,477 @@
+//! Render chats for archiving outside llumen.
+//!
+//! Markdown and HTML show the active branch the way the chat page does, JSON
+//! keeps every message of every branch.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use entity::{chat, message};
+use protocol::{AssistantChunk, Deep, MessageInner, UrlCitation};
+use serde::Serialize;
+
+use crate::utils::chat::ChatMode;
+
+#[derive(Debug, Serialize)]
+pub struct JsonExport<'a> {
+    pub version: u32,
+    pub id: i32,
+    pub title: Option<&'a str>,
+    pub mode: ChatMode,
+    pub model_id: Option<i32>,
+    pub leaf_id: Option<i32>,
+    pub messages: Vec<JsonExportMessage<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonExportMessage<'a> {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub created_at: i64,
+    pub token_count: i32,
+    pub price: f32,
+    pub inner: &'a MessageInner,
+}
+
+/// Every message of the chat with its metadata.
+pub fn json(chat: &chat::Model, messages: &[message::Model]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&JsonExport {
+        version: 1,
+        id: chat.id,
+        title: chat.title.as_deref(),
+        mode: chat.mode.into(),
+        model_id: chat.model_id,
+        leaf_id: chat.leaf_id,
+        messages: messages
+            .iter()
+            .map(|x| JsonExportMessage {
+                id: x.id,
+                parent_id: x.parent_id,
+                created_at: x.created_at,
+                token_count: x.token_count,
+                price: x.price,
+                inner: &x.inner,
+            })
+            .collect(),
+    })
+}
+
+fn title(chat: &chat::Model) -> &str {
+    chat.title.as_deref().unwrap_or("Untitled chat")
+}
+
+/// A code fence longer than any run of backticks in `content`.
+fn fence(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+/// Markdown of the messages, in order.
+pub fn markdown(chat: &chat::Model, messages: &[message::Model]) -> String {
+    let mut out = format!("# {}\n\n", title(chat));
+    for msg in messages {
+        match &msg.inner {
+            MessageInner::User { text, files } => {
+                out.push_str("## User\n\n");
+                out.push_str(text.trim_end());
+                out.push_str("\n\n");
+                for file in files {
+                    let _ = writeln!(out, "- Attachment: {}", file.name);
+                }
+                if !files.is_empty() {
+                    out.push('\n');
+                }
+            }
+            MessageInner::Assistant(chunks) => {
+                if chunks.is_empty() {
+                    continue;
+                }
+                out.push_str("## Assistant\n\n");
+                markdown_chunks(&mut out, chunks);
+            }
+        }
+    }
+    out
+}
+
+fn markdown_chunks(out: &mut String, chunks: &[AssistantChunk]) {
+    for chunk in chunks {
+        match chunk {
+            AssistantChunk::Text(text) => {
+                out.push_str(text.trim_end());
+                out.push_str("\n\n");
+            }
+            AssistantChunk::Reasoning(text) => {
+                let _ = writeln!(
+                    out,
+                    "<details>\n<summary>Reasoning</summary>\n\n{}\n\n</details>\n",
+                    text.trim()
+                );
+            }
+            AssistantChunk::ToolCall { name, arg, .. } => {
+                let fence = fence(arg);
+                let _ = writeln!(
+                    out,
+                    "**Tool call** `{}`\n\n{}json\n{}\n{}\n",
+                    name, fence, arg, fence
+                );
+            }
+            AssistantChunk::ToolResult {
+                response, files, ..
+            } => {
+                let fence = fence(response);
+                let _ = writeln!(
+                    out,
+                    "**Tool result**\n\n{}\n{}\n{}\n",
+                    fence, response, fence
+                );
+                for file in files {
+                    let _ = writeln!(out, "- Attachment: {}", file.name);
+                }
+            }
+            AssistantChunk::UrlCitation(citations) => markdown_citations(out, citations),
+            AssistantChunk::Error(error) => {
+                let _ = writeln!(out, "> **Error:** {}\n", error);
+            }
+            AssistantChunk::DeepAgent(deep) => markdown_deep(out, deep),
+            AssistantChunk::Image(id) | AssistantChunk::ImageWithDimensions { id, .. } => {
+                let _ = writeln!(out, "![image {}](image-{})\n", id, id);
+            }
+            AssistantChunk::Annotation(_) | AssistantChunk::ReasoningDetail(_) => {}
+        }
+    }
+}
+
+fn markdown_citations(out: &mut String, citations: &[UrlCitation]) {
+    out.push_str("Sources:\n\n");
+    for citation in citations {
+        let title = citation.title.as_deref().unwrap_or(&citation.url);
+        match web_url(&citation.url) {
+            true => {
+                let _ = writeln!(out, "- [{}]({})", title, citation.url);
+            }
+            false => {
+                let _ = writeln!(out, "- {}", title);
+            }
+        }
+    }
+    out.push('\n');
+}
+
+fn markdown_deep(out: &mut String, deep: &Deep) {
+    let _ = writeln!(out, "### Plan: {}\n\n{}\n", deep.title, deep.thought.trim());
+    for (i, step) in deep.steps.iter().enumerate() {
+        let _ = writeln!(out, "{}. **{}** {}", i + 1, step.title, step.description);
+    }
+    out.push('\n');
+    for step in deep.steps.iter().filter(|x| !x.progress.is_empty()) {
+        let _ = writeln!(out, "#### {}\n", step.title);
+        markdown_chunks(out, &step.progress);
+    }
+}
+
+/// Whether `url` is a http(s) link, other schemes such as `javascript:` are
+/// not linked.
+fn web_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:48rem;margin:2rem auto;\
+padding:0 1rem;line-height:1.5;color:#1f2328}\
+.msg{margin:1.5rem 0;padding:1rem;border-radius:.5rem}\
+.user{background:#f0f4ff}.assistant{background:#f6f8fa}\
+.role{font-weight:600;margin-bottom:.5rem}.text{white-space:pre-wrap}\
+pre{white-space:pre-wrap;background:#eaeef2;padding:.5rem;border-radius:.25rem}\
+.error{color:#cf222e}img{max-width:100%}";
+
+/// A single self-contained page, `images` maps file ids to data URIs.
+pub fn html(
+    chat: &chat::Model,
+    messages: &[message::Model],
+    images: &HashMap<i32, String>,
+) -> String {
+    let title = escape(title(chat));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+    for msg in messages {
+        match &msg.inner {
+            MessageInner::User { text, files } => {
+                out.push_str("<div class=\"msg user\"><div class=\"role\">User</div>");
+                let _ = write!(out, "<div class=\"text\">{}</div>", escape(text.trim_end()));
+                for file in files {
+                    match images.get(&file.id) {
+                        Some(uri) => {
+                            let _ =
+                                write!(out, "<img src=\"{}\" alt=\"{}\">", uri, escape(&file.name));
+                        }
+                        None => {
+                            let _ = write!(out, "<p>Attachment: {}</p>", escape(&file.name));
+                        }
+                    }
+                }
+                out.push_str("</div>\n");
+            }
+            MessageInner::Assistant(chunks) => {
+                if chunks.is_empty() {
+                    continue;
+                }
+                out.push_str("<div class=\"msg assistant\"><div class=\"role\">Assistant</div>");
+                html_chunks(&mut out, chunks, images);
+                out.push_str("</div>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn html_chunks(out: &mut String, chunks: &[AssistantChunk], images: &HashMap<i32, String>) {
+    for chunk in chunks {
+        match chunk {
+            AssistantChunk::Text(text) => {
+                let _ = write!(out, "<div class=\"text\">{}</div>", escape(text.trim_end()));
+            }
+            AssistantChunk::Reasoning(text) => {
+                let _ = write!(
+                    out,
+                    "<details><summary>Reasoning</summary><div class=\"text\">{}</div></details>",
+                    escape(text.trim())
+                );
+            }
+            AssistantChunk::ToolCall { name, arg, .. } => {
+                let _ = write!(
+                    out,
+                    "<p><b>Tool call</b> <code>{}</code></p><pre>{}</pre>",
+                    escape(name),
+                    escape(arg)
+                );
+            }
+            AssistantChunk::ToolResult { response, .. } => {
+                let _ = write!(
+                    out,
+                    "<p><b>Tool result</b></p><pre>{}</pre>",
+                    escape(response)
+                );
+            }
+            AssistantChunk::UrlCitation(citations) => {
+                out.push_str("<p>Sources:</p><ul>");
+                for citation in citations {
+                    let title = citation.title.as_deref().unwrap_or(&citation.url);
+                    // urls come from the model, only web links are clickable
+                    if web_url(&citation.url) {
+                        let _ = write!(
+                            out,
+                            "<li><a href=\"{}\">{}</a></li>",
+                            escape(&citation.url),
+                            escape(title)
+                        );
+                    } else {
+                        let _ = write!(out, "<li>{}</li>", escape(title));
+                    }
+                }
+                out.push_str("</ul>");
+            }
+            AssistantChunk::Error(error) => {
+                let _ = write!(out, "<p class=\"error\">{}</p>", escape(error));
+            }
+            AssistantChunk::DeepAgent(deep) => {
+                let _ = write!(
+                    out,
+                    "<h3>Plan: {}</h3><div class=\"text\">{}</div><ol>",
+                    escape(&deep.title),
+                    escape(deep.thought.trim())
+                );
+                for step in &deep.steps {
+                    let _ = write!(
+                        out,
+                        "<li><b>{}</b> {}</li>",
+                        escape(&step.title),
+                        escape(&step.description)
+                    );
+                }
+                out.push_str("</ol>");
+                for step in deep.steps.iter().filter(|x| !x.progress.is_empty()) {
+                    let _ = write!(out, "<h4>{}</h4>", escape(&step.title));
+                    html_chunks(out, &step.progress, images);
+                }
+            }
+            AssistantChunk::Image(id) | AssistantChunk::ImageWithDimensions { id, .. } => {
+                if let Some(uri) = images.get(id) {
+                    let _ = write!(out, "<img src=\"{}\" alt=\"image {}\">", uri, id);
+                }
+            }
+            AssistantChunk::Annotation(_) | AssistantChunk::ReasoningDetail(_) => {}
+        }
+    }
+}
+
+/// Ids of files the messages show or link to.
+pub fn file_ids<'a>(messages: impl IntoIterator<Item = &'a MessageInner>) -> Vec<i32> {
+    fn walk(ids: &mut Vec<i32>, chunks: &[AssistantChunk]) {
+        for chunk in chunks {
+            match chunk {
+                AssistantChunk::Image(id) | AssistantChunk::ImageWithDimensions { id, .. } => {
+                    ids.push(*id)
+                }
+                AssistantChunk::DeepAgent(deep) => {
+                    for step in &deep.steps {
+                        walk(ids, &step.progress);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut ids = Vec::new();
+    for inner in messages {
+        match inner {
+            MessageInner::User { files, .. } => ids.extend(files.iter().map(|x| x.id)),
+            MessageInner::Assistant(chunks) => walk(&mut ids, chunks),
+        }
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Name for an exported file, from the chat title.
+pub fn file_name(chat: &chat::Model, extension: &str) -> String {
+    let slug = title(chat)
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .take(40)
+        .collect::<String>();
+    let slug = slug.trim_matches('-');
+    let slug = if slug.is_empty() { "chat" } else { slug };
+    format!("{}-{}.{}", chat.id, slug, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat() -> chat::Model {
+        chat::Model {
+            id: 3,
+            owner_id: 1,
+            model_id: None,
+            mode: protocol::ModeKind::Normal,
+            title: Some("Rust <traits>".to_owned()),
+            leaf_id: None,
+            folder_id: None,
+            pinned: false,
+            archived: false,
+            custom_instructions: None,
+            summary: None,
+            assistant_id: None,
+        }
+    }
+
+    fn message(id: i32, inner: MessageInner) -> message::Model {
+        message::Model {
+            id,
+            chat_id: 3,
+            price: 0.0,
+            token_count: 0,
+            inner,
+            created_at: 0,
+            parent_id: None,
+        }
+    }
+
+    fn messages() -> Vec<message::Model> {
+        vec![
+            message(
+                1,
+                MessageInner::User {
+                    text: "What is a trait?".to_owned(),
+                    files: Vec::new(),
+                },
+            ),
+            message(
+                2,
+                MessageInner::Assistant(vec![
+                    AssistantChunk::Reasoning("think".to_owned()),
+                    AssistantChunk::Text("A shared `interface`.".to_owned()),
+                    AssistantChunk::Image(9),
+                ]),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = markdown(&chat(), &messages());
+        assert!(md.starts_with("# Rust <traits>\n\n## User\n\nWhat is a trait?\n\n"));
+        assert!(md.contains("<summary>Reasoning</summary>\n\nthink\n\n"));
+        assert!(md.contains("A shared `interface`.\n\n![image 9](image-9)"));
+    }
+
+    #[test]
+    fn test_html() {
+        let images = HashMap::from([(9, "data:image/png;base64,AA==".to_owned())]);
+        let html = html(&chat(), &messages(), &images);
+        assert!(html.contains("<title>Rust &lt;traits&gt;</title>"));
+        assert!(html.contains("<img src=\"data:image/png;base64,AA==\""));
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(fence("a ``` b"), "````");
+        assert_eq!(fence("plain"), "```");
+        assert_eq!(file_ids(messages().iter().map(|x| &x.inner)), vec![9]);
+        assert_eq!(file_name(&chat(), "md"), "3-Rust--traits.md");
+        assert!(web_url("HTTPS://example.com"));
+        assert!(!web_url("javascript:alert(1)"));
+        assert!(!web_url("knowledge:3"));
+    }
+
+    #[test]
+    fn test_html_citation_links() {
+        let citation = |url: &str| UrlCitation {
+            url: url.to_owned(),
+            title: Some("source".to_owned()),
+            content: None,
+            start_index: None,
+            end_index: None,
+            favicon: None,
+        };
+        let mut out = String::new();
+        html_chunks(
+            &mut out,
+            &[AssistantChunk::UrlCitation(vec![
+                citation("https://example.com"),
+                citation("javascript:alert(1)"),
+            ])],
+            &HashMap::new(),
+        );
+        assert!(out.contains("<li><a href=\"https://example.com\">source</a></li>"));
+        assert!(out.contains("<li>source</li>"));
+        assert!(!out.contains("javascript"));
+    }
+}
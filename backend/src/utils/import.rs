@@ -0,0 +1,603 @@
+//! Read chat history exported by other tools.
+//!
+//! Supported are the `conversations.json` of a ChatGPT data export and chat
+//! exports of Open WebUI, either a single chat or a list. Both keep messages
+//! as a tree, which maps onto parent pointers of messages. Anything that
+//! cannot be represented is left out and reported.
+
+use std::collections::HashMap;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use protocol::AssistantChunk;
+use serde::Deserialize;
+use serde_json::Value;
+use typeshare::typeshare;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    #[serde(rename = "chatgpt")]
+    ChatGpt,
+    OpenWebui,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedChat {
+    pub title: Option<String>,
+    /// Parents always come before their children
+    pub messages: Vec<ImportedMessage>,
+    /// Index of the last message of the branch shown in the source tool
+    pub leaf: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedMessage {
+    /// Index of the parent in [`ImportedChat::messages`]
+    pub parent: Option<usize>,
+    pub created_at: Option<i64>,
+    pub content: ImportedContent,
+    pub images: Vec<ImportedImage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportedContent {
+    User(String),
+    Assistant(Vec<AssistantChunk>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedImage {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    pub chats: Vec<ImportedChat>,
+    /// Human readable notes about what was left out
+    pub skipped: Vec<String>,
+}
+
+/// Guess the tool that produced `value`.
+pub fn detect(value: &Value) -> Option<ImportSource> {
+    let sample = match value {
+        Value::Array(items) => items.first()?,
+        value => value,
+    };
+    if sample.get("mapping").is_some() {
+        Some(ImportSource::ChatGpt)
+    } else if sample.get("chat").is_some() || sample.get("history").is_some() {
+        Some(ImportSource::OpenWebui)
+    } else {
+        None
+    }
+}
+
+/// Convert an export into chats.
+pub fn parse(source: ImportSource, value: Value) -> Import {
+    let items = match value {
+        Value::Array(items) => items,
+        value => vec![value],
+    };
+
+    let mut import = Import::default();
+    for (i, item) in items.into_iter().enumerate() {
+        let chat = match source {
+            ImportSource::ChatGpt => serde_json::from_value::<ChatGptConversation>(item)
+                .map(|x| chatgpt(x, &mut import.skipped)),
+            ImportSource::OpenWebui => serde_json::from_value::<OpenWebuiRecord>(item)
+                .map(|x| open_webui(x.into_chat(), &mut import.skipped)),
+        };
+        match chat {
+            Ok(chat) if chat.messages.is_empty() => import
+                .skipped
+                .push(format!("{}: no messages", label(i, chat.title.as_deref()))),
+            Ok(chat) => import.chats.push(chat),
+            Err(e) => {
+                import
+                    .skipped
+                    .push(format!("{}: unrecognized format ({})", label(i, None), e))
+            }
+        }
+    }
+    import
+}
+
+fn label(index: usize, title: Option<&str>) -> String {
+    match title {
+        Some(_) => chat_label(title),
+        None => format!("conversation #{}", index + 1),
+    }
+}
+
+fn chat_label(title: Option<&str>) -> String {
+    match title {
+        Some(title) => format!("conversation \"{}\"", title),
+        None => "untitled conversation".to_owned(),
+    }
+}
+
+/// Seconds or milliseconds since the epoch, as seconds.
+fn timestamp(value: f64) -> i64 {
+    if value > 1e12 {
+        (value / 1000.0) as i64
+    } else {
+        value as i64
+    }
+}
+
+/// Decode a `data:<mime>;base64,<data>` URI.
+fn data_uri(uri: &str) -> Option<ImportedImage> {
+    let (header, data) = uri.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    Some(ImportedImage {
+        mime_type: mime_type.to_owned(),
+        data: STANDARD.decode(data.trim()).ok()?,
+    })
+}
+
+enum RawContent {
+    User(String),
+    Assistant(Vec<AssistantChunk>),
+    /// Left out and reported
+    Skip(String),
+    /// Left out silently, such as empty system messages
+    Hidden,
+}
+
+struct RawNode {
+    key: String,
+    parent: Option<String>,
+    created_at: Option<i64>,
+    content: RawContent,
+    images: Vec<ImportedImage>,
+}
+
+/// Flatten a tree of source messages, keeping parents before children.
+///
+/// Left out messages are bridged so their children hang off the nearest kept
+/// ancestor, and consecutive assistant messages (tool use) are merged.
+fn build(
+    title: Option<String>,
+    nodes: Vec<RawNode>,
+    current: Option<&str>,
+    skipped: &mut Vec<String>,
+) -> ImportedChat {
+    let index = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (x.key.clone(), i))
+        .collect::<HashMap<_, _>>();
+    let mut children: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let parent = node.parent.as_ref().and_then(|x| index.get(x)).copied();
+        children.entry(parent).or_default().push(i);
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|&i| (nodes[i].created_at, i));
+    }
+
+    // depth first from the roots, each node is only visited once
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut visited = vec![false; nodes.len()];
+    let mut stack = children.get(&None).cloned().unwrap_or_default();
+    stack.reverse();
+    while let Some(i) = stack.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            continue;
+        }
+        order.push(i);
+        if let Some(list) = children.get(&Some(i)) {
+            stack.extend(list.iter().rev());
+        }
+    }
+
+    let label = chat_label(title.as_deref());
+    let mut mapped: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut parents: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut nodes = nodes.into_iter().map(Some).collect::<Vec<_>>();
+    let mut chat = ImportedChat {
+        title,
+        ..Default::default()
+    };
+    for i in order {
+        let node = nodes[i].take().unwrap();
+        let parent = node
+            .parent
+            .as_ref()
+            .and_then(|x| index.get(x))
+            .and_then(|&x| mapped[x]);
+        parents[i] = parent;
+
+        mapped[i] = match node.content {
+            RawContent::Hidden => parent,
+            RawContent::Skip(reason) => {
+                skipped.push(format!("{}: {}", label, reason));
+                parent
+            }
+            RawContent::User(text) => {
+                chat.messages.push(ImportedMessage {
+                    parent,
+                    created_at: node.created_at,
+                    content: ImportedContent::User(text),
+                    images: node.images,
+                });
+                Some(chat.messages.len() - 1)
+            }
+            RawContent::Assistant(chunks) => {
+                let previous = parent.and_then(|x| {
+                    let message = &mut chat.messages[x];
+                    match &mut message.content {
+                        ImportedContent::Assistant(existing) => {
+                            Some((existing, &mut message.images))
+                        }
+                        ImportedContent::User(_) => None,
+                    }
+                });
+                match previous {
+                    Some((existing, images)) => {
+                        existing.extend(chunks);
+                        images.extend(node.images);
+                        parent
+                    }
+                    None => {
+                        chat.messages.push(ImportedMessage {
+                            parent,
+                            created_at: node.created_at,
+                            content: ImportedContent::Assistant(chunks),
+                            images: node.images,
+                        });
+                        Some(chat.messages.len() - 1)
+                    }
+                }
+            }
+        };
+    }
+
+    chat.leaf = current
+        .and_then(|x| index.get(x))
+        .and_then(|&x| mapped[x])
+        .or_else(|| chat.messages.len().checked_sub(1));
+    chat
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptConversation {
+    title: Option<String>,
+    mapping: HashMap<String, ChatGptNode>,
+    current_node: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptNode {
+    parent: Option<String>,
+    message: Option<ChatGptMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptMessage {
+    author: ChatGptAuthor,
+    create_time: Option<f64>,
+    content: ChatGptContent,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptAuthor {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptContent {
+    content_type: String,
+    #[serde(default)]
+    parts: Vec<Value>,
+    text: Option<String>,
+    #[serde(default)]
+    thoughts: Vec<ChatGptThought>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptThought {
+    #[serde(default)]
+    content: String,
+}
+
+fn chatgpt(conversation: ChatGptConversation, skipped: &mut Vec<String>) -> ImportedChat {
+    let mut nodes = Vec::with_capacity(conversation.mapping.len());
+    for (key, node) in conversation.mapping {
+        let (created_at, content) = match node.message {
+            Some(message) => (
+                message.create_time.map(timestamp),
+                chatgpt_content(&message.author.role, message.content),
+            ),
+            None => (None, RawContent::Hidden),
+        };
+        nodes.push(RawNode {
+            key,
+            parent: node.parent,
+            created_at,
+            content,
+            images: Vec::new(),
+        });
+    }
+    // the mapping is unordered, keep siblings stable
+    nodes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.key.cmp(&b.key)));
+
+    build(
+        conversation.title,
+        nodes,
+        conversation.current_node.as_deref(),
+        skipped,
+    )
+}
+
+fn chatgpt_content(role: &str, content: ChatGptContent) -> RawContent {
+    let mut images = 0;
+    let text = content
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Value::String(text) => Some(text.as_str()),
+            _ => {
+                images += 1;
+                None
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match (role, content.content_type.as_str()) {
+        ("system", _) if text.trim().is_empty() => RawContent::Hidden,
+        ("system", _) => RawContent::Skip("system message left out".to_owned()),
+        ("user", "text" | "multimodal_text") => {
+            if images > 0 && text.trim().is_empty() {
+                RawContent::Skip(
+                    "attachment left out, it is not part of conversations.json".to_owned(),
+                )
+            } else {
+                RawContent::User(text)
+            }
+        }
+        ("assistant", "text" | "multimodal_text") if !text.trim().is_empty() => {
+            RawContent::Assistant(vec![AssistantChunk::Text(text)])
+        }
+        ("assistant", "thoughts") => RawContent::Assistant(vec![AssistantChunk::Reasoning(
+            content
+                .thoughts
+                .into_iter()
+                .map(|x| x.content)
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )]),
+        ("assistant", "code") => RawContent::Assistant(vec![AssistantChunk::ToolCall {
+            id: String::new(),
+            name: "code".to_owned(),
+            arg: content.text.unwrap_or_default(),
+        }]),
+        ("tool", "execution_output") => RawContent::Assistant(vec![AssistantChunk::ToolResult {
+            id: String::new(),
+            response: content.text.unwrap_or_default(),
+            files: Vec::new(),
+        }]),
+        ("assistant", "text" | "multimodal_text" | "reasoning_recap") | ("tool", _) => {
+            RawContent::Hidden
+        }
+        (role, kind) => RawContent::Skip(format!("{} message of type {} left out", role, kind)),
+    }
+}
+
+/// Open WebUI exports chat records, the chat itself sits in `chat`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OpenWebuiRecord {
+    Record { chat: OpenWebuiChat },
+    Chat(OpenWebuiChat),
+}
+
+impl OpenWebuiRecord {
+    fn into_chat(self) -> OpenWebuiChat {
+        match self {
+            OpenWebuiRecord::Record { chat } | OpenWebuiRecord::Chat(chat) => chat,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenWebuiChat {
+    title: Option<String>,
+    history: OpenWebuiHistory,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenWebuiHistory {
+    messages: HashMap<String, OpenWebuiMessage>,
+    current_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenWebuiMessage {
+    parent_id: Option<String>,
+    role: String,
+    #[serde(default)]
+    content: String,
+    timestamp: Option<f64>,
+    #[serde(default)]
+    files: Vec<OpenWebuiFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenWebuiFile {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    url: Option<String>,
+    name: Option<String>,
+}
+
+fn open_webui(chat: OpenWebuiChat, skipped: &mut Vec<String>) -> ImportedChat {
+    let label = chat_label(chat.title.as_deref());
+    let mut nodes = Vec::with_capacity(chat.history.messages.len());
+    for (key, message) in chat.history.messages {
+        let mut images = Vec::new();
+        for file in message.files {
+            let image = file
+                .url
+                .as_deref()
+                .filter(|_| file.kind.as_deref() == Some("image"))
+                .and_then(data_uri);
+            match image {
+                Some(image) => images.push(image),
+                None => skipped.push(format!(
+                    "{}: file {} left out, only embedded images are imported",
+                    label,
+                    file.name.as_deref().unwrap_or("without name")
+                )),
+            }
+        }
+
+        let content = match message.role.as_str() {
+            "user" => RawContent::User(message.content),
+            "assistant" => RawContent::Assistant(vec![AssistantChunk::Text(message.content)]),
+            "system" => RawContent::Hidden,
+            role => RawContent::Skip(format!("{} message left out", role)),
+        };
+        nodes.push(RawNode {
+            key,
+            parent: message.parent_id,
+            created_at: message.timestamp.map(timestamp),
+            content,
+            images,
+        });
+    }
+    nodes.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.key.cmp(&b.key)));
+
+    build(
+        chat.title,
+        nodes,
+        chat.history.current_id.as_deref(),
+        skipped,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_chatgpt() {
+        let export = json!([{
+            "title": "Greeting",
+            "current_node": "c",
+            "mapping": {
+                "root": { "parent": null, "message": null },
+                "sys": { "parent": "root", "message": {
+                    "author": { "role": "system" }, "create_time": null,
+                    "content": { "content_type": "text", "parts": [""] }
+                }},
+                "a": { "parent": "sys", "message": {
+                    "author": { "role": "user" }, "create_time": 1700000000.5,
+                    "content": { "content_type": "text", "parts": ["hi"] }
+                }},
+                "b": { "parent": "a", "message": {
+                    "author": { "role": "assistant" }, "create_time": 1700000001.0,
+                    "content": { "content_type": "text", "parts": ["hello"] }
+                }},
+                "c": { "parent": "a", "message": {
+                    "author": { "role": "assistant" }, "create_time": 1700000002.0,
+                    "content": { "content_type": "text", "parts": ["hey"] }
+                }}
+            }
+        }]);
+        assert_eq!(detect(&export), Some(ImportSource::ChatGpt));
+
+        let import = parse(ImportSource::ChatGpt, export);
+        assert!(import.skipped.is_empty());
+        let chat = &import.chats[0];
+        assert_eq!(chat.messages.len(), 3);
+        assert_eq!(
+            chat.messages[0].content,
+            ImportedContent::User("hi".to_owned())
+        );
+        assert_eq!(chat.messages[0].parent, None);
+        assert_eq!(chat.messages[0].created_at, Some(1_700_000_000));
+        assert_eq!(chat.messages[1].parent, Some(0));
+        assert_eq!(chat.messages[2].parent, Some(0));
+        assert_eq!(chat.leaf, Some(2));
+    }
+
+    #[test]
+    fn test_open_webui() {
+        let export = json!([{
+            "id": "x",
+            "chat": {
+                "title": "Picture",
+                "history": {
+                    "currentId": "2",
+                    "messages": {
+                        "1": { "id": "1", "parentId": null, "role": "user", "content": "look",
+                               "timestamp": 1700000000,
+                               "files": [
+                                   { "type": "image", "url": "data:image/png;base64,AAEC" },
+                                   { "type": "file", "name": "notes.pdf" }
+                               ] },
+                        "2": { "id": "2", "parentId": "1", "role": "assistant", "content": "nice",
+                               "timestamp": 1700000001 }
+                    }
+                }
+            }
+        }]);
+        assert_eq!(detect(&export), Some(ImportSource::OpenWebui));
+
+        let import = parse(ImportSource::OpenWebui, export);
+        assert_eq!(import.skipped.len(), 1);
+        let chat = &import.chats[0];
+        assert_eq!(chat.messages.len(), 2);
+        assert_eq!(
+            chat.messages[0].images,
+            vec![ImportedImage {
+                mime_type: "image/png".to_owned(),
+                data: vec![0, 1, 2],
+            }]
+        );
+        assert_eq!(chat.leaf, Some(1));
+    }
+
+    #[test]
+    fn test_merge_tool_use() {
+        let node = |key: &str, parent: Option<&str>, content| RawNode {
+            key: key.to_owned(),
+            parent: parent.map(str::to_owned),
+            created_at: None,
+            content,
+            images: Vec::new(),
+        };
+        let nodes = vec![
+            node("1", None, RawContent::User("run it".to_owned())),
+            node(
+                "2",
+                Some("1"),
+                RawContent::Assistant(vec![AssistantChunk::Text("a".to_owned())]),
+            ),
+            node("3", Some("2"), RawContent::Hidden),
+            node(
+                "4",
+                Some("3"),
+                RawContent::Assistant(vec![AssistantChunk::Text("b".to_owned())]),
+            ),
+        ];
+        let chat = build(None, nodes, Some("4"), &mut Vec::new());
+        assert_eq!(chat.messages.len(), 2);
+        assert_eq!(
+            chat.messages[1].content,
+            ImportedContent::Assistant(vec![
+                AssistantChunk::Text("a".to_owned()),
+                AssistantChunk::Text("b".to_owned()),
+            ])
+        );
+        assert_eq!(chat.leaf, Some(1));
+    }
+}
@@ -0,0 +1,295 @@
+//! Knowledge bases: named collections of documents searched by the model.
+//!
+//! Text is extracted from each added document, split into overlapping
+//! passages and embedded once. Searching embeds the query and ranks the
+//! passages of the selected collections by cosine similarity, like user
+//! memories do. Passages embedded by another model are skipped, see
+//! [`super::embedding`]. Like there, what embedding cost is returned for the
+//! caller to charge the user.
+//!
+//! Searching loads every passage of the selected collections, so each one is
+//! capped at [`crate::config::MAX_KNOWLEDGE_PASSAGES`] when documents are
+//! added; with 1536 dimensions that is about 12 MiB of embeddings per
+//! collection.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use entity::{knowledge_chunk, knowledge_file, prelude::*};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    TransactionTrait,
+};
+
+use crate::{
+    config::{
+        KNOWLEDGE_CHUNK_CHARS, KNOWLEDGE_CHUNK_OVERLAP, KNOWLEDGE_MIN_SIMILARITY,
+        KNOWLEDGE_SEARCH_LIMIT,
+    },
+    utils::{
+        embedding::{self, Embedder},
+        timestamp,
+    },
+};
+
+/// Text of a document, or a piece of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    /// One-based page number, none for documents without pages
+    pub page: Option<i32>,
+    pub content: String,
+}
+
+/// A passage found by [`search`].
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub file_id: i32,
+    pub file: String,
+    pub page: Option<i32>,
+    pub content: String,
+}
+
+/// Extract the text of a document, one passage per page.
+///
+/// PDF and UTF-8 text are supported.
+pub fn extract(data: &[u8]) -> Result<Vec<Passage>> {
+    if infer::archive::is_pdf(data) {
+        let pages = pdf_extract::extract_text_from_mem_by_pages(data)?;
+        return Ok(pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| Passage {
+                page: Some(i as i32 + 1),
+                content,
+            })
+            .collect());
+    }
+
+    match std::str::from_utf8(data) {
+        Ok(text) => Ok(vec![Passage {
+            page: None,
+            content: text.to_owned(),
+        }]),
+        Err(_) => anyhow::bail!("only PDF and plain text documents are supported"),
+    }
+}
+
+/// Split pages into passages small enough to embed, never across pages.
+pub fn chunk(pages: Vec<Passage>) -> Vec<Passage> {
+    pages
+        .into_iter()
+        .flat_map(|page| {
+            split(
+                &page.content,
+                KNOWLEDGE_CHUNK_CHARS,
+                KNOWLEDGE_CHUNK_OVERLAP,
+            )
+            .into_iter()
+            .map(move |content| Passage {
+                page: page.page,
+                content,
+            })
+        })
+        .collect()
+}
+
+/// Split text into pieces of at most `size` characters, each repeating about
+/// `overlap` characters of the previous one. Whitespace is collapsed and
+/// pieces end at a word boundary when possible.
+fn split(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let chars = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect::<Vec<_>>();
+
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + size).min(chars.len());
+        if end < chars.len() {
+            let half = start + size / 2;
+            if let Some(space) = chars[half..end].iter().rposition(|x| *x == ' ') {
+                end = half + space;
+            }
+        }
+        pieces.push(
+            chars[start..end]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_owned(),
+        );
+        if end == chars.len() {
+            break;
+        }
+
+        let mut next = end.saturating_sub(overlap).max(start + 1);
+        while next < end && chars[next - 1] != ' ' {
+            next += 1;
+        }
+        start = next;
+    }
+    pieces
+}
+
+/// Embed the passages of a document and add it to a knowledge base.
+pub async fn ingest(
+    conn: &DatabaseConnection,
+    embedder: &Embedder,
+    knowledge_id: i32,
+    name: &str,
+    passages: Vec<Passage>,
+) -> Result<(knowledge_file::Model, f64)> {
+    let (vectors, cost) = embedder
+        .embed(
+            &passages
+                .iter()
+                .map(|x| x.content.clone())
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+
+    let txn = conn.begin().await?;
+    let file = knowledge_file::ActiveModel {
+        knowledge_id: Set(knowledge_id),
+        name: Set(name.to_owned()),
+        created_at: Set(timestamp::now()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    let chunks = passages
+        .into_iter()
+        .zip(vectors)
+        .map(|(passage, vector)| knowledge_chunk::ActiveModel {
+            knowledge_id: Set(knowledge_id),
+            file_id: Set(file.id),
+            page: Set(passage.page),
+            content: Set(passage.content),
+            embedding: Set(embedding::encode(&vector)),
+            embedding_model: Set(embedder.model().to_owned()),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    if !chunks.is_empty() {
+        KnowledgeChunk::insert_many(chunks).exec(&txn).await?;
+    }
+    txn.commit().await?;
+
+    Ok((file, cost))
+}
+
+/// Passages of the given knowledge bases most related to `query`, best
+/// first.
+pub async fn search(
+    conn: &DatabaseConnection,
+    embedder: &Embedder,
+    knowledge_ids: &[i32],
+    query: &str,
+) -> Result<(Vec<Hit>, f64)> {
+    if knowledge_ids.is_empty() || query.trim().is_empty() {
+        return Ok((Vec::new(), 0.0));
+    }
+
+    let chunks = KnowledgeChunk::find()
+        .filter(knowledge_chunk::Column::KnowledgeId.is_in(knowledge_ids.iter().copied()))
+        .filter(knowledge_chunk::Column::EmbeddingModel.eq(embedder.model()))
+        .all(conn)
+        .await?;
+    if chunks.is_empty() {
+        return Ok((Vec::new(), 0.0));
+    }
+
+    let (query, cost) = embedder.embed_one(query).await?;
+    let candidates = chunks
+        .iter()
+        .map(|x| embedding::decode(&x.embedding))
+        .collect::<Vec<_>>();
+    let ranked = embedding::rank(
+        &query,
+        &candidates,
+        KNOWLEDGE_SEARCH_LIMIT,
+        KNOWLEDGE_MIN_SIMILARITY,
+    );
+
+    let files = KnowledgeFile::find()
+        .filter(knowledge_file::Column::Id.is_in(ranked.iter().map(|i| chunks[*i].file_id)))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x.name))
+        .collect::<HashMap<_, _>>();
+
+    let hits = ranked
+        .into_iter()
+        .map(|i| {
+            let chunk = &chunks[i];
+            Hit {
+                file_id: chunk.file_id,
+                file: files.get(&chunk.file_id).cloned().unwrap_or_default(),
+                page: chunk.page,
+                content: chunk.content.clone(),
+            }
+        })
+        .collect();
+    Ok((hits, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_text() {
+        assert_eq!(
+            extract(b"hello").unwrap(),
+            vec![Passage {
+                page: None,
+                content: "hello".to_owned(),
+            }]
+        );
+        assert!(extract(&[0xff, 0xfe, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_split_short() {
+        assert_eq!(split("  one\n\ntwo  ", 100, 10), vec!["one two"]);
+        assert!(split(" \n ", 100, 10).is_empty());
+    }
+
+    #[test]
+    fn test_split_overlaps_at_words() {
+        let pieces = split("aaaa bbbb cccc dddd eeee", 10, 5);
+        assert_eq!(
+            pieces,
+            vec!["aaaa bbbb", "bbbb cccc", "cccc dddd", "dddd eeee"]
+        );
+        assert!(pieces.iter().all(|x| x.chars().count() <= 10));
+    }
+
+    #[test]
+    fn test_split_long_word() {
+        let pieces = split(&"x".repeat(25), 10, 2);
+        assert_eq!(pieces[0], "x".repeat(10));
+        assert!(pieces.concat().len() >= 25);
+    }
+
+    #[test]
+    fn test_chunk_keeps_pages() {
+        let pages = vec![
+            Passage {
+                page: Some(1),
+                content: "first".to_owned(),
+            },
+            Passage {
+                page: Some(2),
+                content: "second".to_owned(),
+            },
+        ];
+        let chunks = chunk(pages.clone());
+        assert_eq!(chunks, pages);
+    }
+}
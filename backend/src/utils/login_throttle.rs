@@ -0,0 +1,340 @@
+//! Login throttling keyed by client address and by account.
+//!
+//! Every key gets a few free failures, after which each further attempt has to
+//! wait twice as long as the previous one. Addresses are additionally capped
+//! to a fixed number of attempts per window, and accounts are locked for a
+//! while after too many consecutive failures. Usernames nobody has are
+//! locked alike, so a lockout doesn't tell which accounts exist. An
+//! administrator can clear an account lockout early.
+//!
+//! State is kept in memory, a restart clears every counter.
+
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::config;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Ip(IpAddr),
+    Account(i32),
+    Unknown(String),
+}
+
+/// Account a login attempt is for.
+#[derive(Debug, Clone, Copy)]
+pub enum Account<'a> {
+    /// An existing user, with its name
+    User(i32, &'a str),
+    /// A username nobody has
+    Unknown(&'a str),
+}
+
+impl Account<'_> {
+    fn key(&self) -> Key {
+        match self {
+            Account::User(id, _) => Key::Account(*id),
+            Account::Unknown(name) => Key::Unknown((*name).to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    // consecutive failures
+    failures: u32,
+    last_failure: Instant,
+    // attempts within the current rate limit window, only used for addresses
+    attempts: u64,
+    window_start: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Entry {
+    fn new(now: Instant) -> Self {
+        Self {
+            failures: 0,
+            last_failure: now,
+            attempts: 0,
+            window_start: now,
+            locked_until: None,
+        }
+    }
+
+    /// Time left before another attempt is allowed.
+    fn wait(&self, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.locked_until.filter(|x| *x > now) {
+            return Some(until - now);
+        }
+        let retry_at = self.last_failure + backoff(self.failures);
+        (retry_at > now).then(|| retry_at - now)
+    }
+}
+
+/// Delay imposed after `failures` consecutive failures.
+fn backoff(failures: u32) -> Duration {
+    let exceeded = failures.saturating_sub(config::LOGIN_BACKOFF_FREE_ATTEMPTS);
+    if exceeded == 0 {
+        return Duration::ZERO;
+    }
+    let secs = 1u64
+        .checked_shl(exceeded - 1)
+        .unwrap_or(u64::MAX)
+        .min(config::LOGIN_BACKOFF_MAX_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Why an attempt was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Throttled {
+    RateLimited(Duration),
+    AccountLocked(Duration),
+}
+
+impl std::fmt::Display for Throttled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Throttled::RateLimited(wait) => write!(
+                f,
+                "too many login attempts, retry in {} seconds",
+                wait.as_secs().max(1)
+            ),
+            Throttled::AccountLocked(wait) => write!(
+                f,
+                "account is temporarily locked, retry in {} seconds",
+                wait.as_secs().max(1)
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LoginThrottle {
+    entries: Mutex<HashMap<Key, Entry>>,
+}
+
+impl LoginThrottle {
+    /// Check whether an attempt from `ip`, optionally for `account`, may run.
+    ///
+    /// Counts toward the per-address rate limit.
+    pub async fn check(&self, ip: IpAddr, account: Option<Account<'_>>) -> Result<(), Throttled> {
+        self.check_at(ip, account, Instant::now()).await
+    }
+
+    async fn check_at(
+        &self,
+        ip: IpAddr,
+        account: Option<Account<'_>>,
+        now: Instant,
+    ) -> Result<(), Throttled> {
+        let mut entries = self.entries.lock().await;
+        let reset = Duration::from_secs(config::LOGIN_FAILURE_RESET_SECS);
+        entries.retain(|_, x| {
+            now.duration_since(x.last_failure) < reset
+                || now.duration_since(x.window_start).as_secs()
+                    < config::LOGIN_RATE_LIMIT_WINDOW_SECS
+                || x.locked_until.is_some_and(|x| x > now)
+        });
+
+        let locked = account
+            .and_then(|x| entries.get(&x.key()))
+            .and_then(|x| x.wait(now));
+        if let Some(wait) = locked {
+            return Err(Throttled::AccountLocked(wait));
+        }
+
+        let entry = entries
+            .entry(Key::Ip(ip))
+            .or_insert_with(|| Entry::new(now));
+        if now.duration_since(entry.window_start).as_secs() >= config::LOGIN_RATE_LIMIT_WINDOW_SECS
+        {
+            entry.attempts = 0;
+            entry.window_start = now;
+        }
+        entry.attempts += 1;
+        if entry.attempts > config::LOGIN_RATE_LIMIT_MAX {
+            let window = Duration::from_secs(config::LOGIN_RATE_LIMIT_WINDOW_SECS);
+            return Err(Throttled::RateLimited(
+                (entry.window_start + window).saturating_duration_since(now),
+            ));
+        }
+        if let Some(wait) = entry.wait(now) {
+            return Err(Throttled::RateLimited(wait));
+        }
+
+        Ok(())
+    }
+
+    /// Record a failed attempt, locking `account` when it failed too often.
+    ///
+    /// Returns whether this attempt locked the account.
+    pub async fn failure(&self, ip: IpAddr, account: Option<Account<'_>>) -> bool {
+        self.failure_at(ip, account, Instant::now()).await
+    }
+
+    async fn failure_at(&self, ip: IpAddr, account: Option<Account<'_>>, now: Instant) -> bool {
+        let mut entries = self.entries.lock().await;
+
+        let entry = entries
+            .entry(Key::Ip(ip))
+            .or_insert_with(|| Entry::new(now));
+        entry.failures += 1;
+        entry.last_failure = now;
+
+        let Some(account) = account else {
+            return false;
+        };
+        let entry = entries
+            .entry(account.key())
+            .or_insert_with(|| Entry::new(now));
+        entry.failures += 1;
+        entry.last_failure = now;
+        if entry.failures >= config::LOGIN_LOCKOUT_THRESHOLD {
+            entry.failures = 0;
+            entry.locked_until = Some(now + Duration::from_secs(config::LOGIN_LOCKOUT_SECS));
+            let (id, name) = match account {
+                Account::User(id, name) => (id.to_string(), name),
+                Account::Unknown(name) => ("unknown".to_owned(), name),
+            };
+            log::warn!(
+                "user({}) {} is locked for {} seconds after repeated login failures, last from {}",
+                id,
+                name,
+                config::LOGIN_LOCKOUT_SECS,
+                ip
+            );
+            return true;
+        }
+        false
+    }
+
+    /// Forget failures of a successful attempt.
+    pub async fn success(&self, ip: IpAddr, account: i32) {
+        let mut entries = self.entries.lock().await;
+        if let Some(entry) = entries.get_mut(&Key::Ip(ip)) {
+            entry.failures = 0;
+        }
+        entries.remove(&Key::Account(account));
+    }
+
+    /// Clear failures and lockout of an account, returning whether it was locked.
+    pub async fn unlock(&self, account: i32) -> bool {
+        let now = Instant::now();
+        let entry = self.entries.lock().await.remove(&Key::Account(account));
+        entry.is_some_and(|x| x.locked_until.is_some_and(|x| x > now))
+    }
+
+    /// Time left of the lockout of `account`, if any.
+    pub async fn locked_for(&self, account: i32) -> Option<Duration> {
+        let now = Instant::now();
+        self.entries
+            .lock()
+            .await
+            .get(&Key::Account(account))
+            .and_then(|x| x.locked_until)
+            .filter(|x| *x > now)
+            .map(|x| x - now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip() -> IpAddr {
+        "192.0.2.1".parse().unwrap()
+    }
+
+    #[test]
+    fn test_backoff() {
+        let free = config::LOGIN_BACKOFF_FREE_ATTEMPTS;
+        assert_eq!(backoff(0), Duration::ZERO);
+        assert_eq!(backoff(free), Duration::ZERO);
+        assert_eq!(backoff(free + 1), Duration::from_secs(1));
+        assert_eq!(backoff(free + 3), Duration::from_secs(4));
+        assert_eq!(
+            backoff(u32::MAX),
+            Duration::from_secs(config::LOGIN_BACKOFF_MAX_SECS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backoff_per_ip() {
+        let throttle = LoginThrottle::default();
+        let now = Instant::now();
+
+        for _ in 0..=config::LOGIN_BACKOFF_FREE_ATTEMPTS {
+            assert!(throttle.check_at(ip(), None, now).await.is_ok());
+            throttle.failure_at(ip(), None, now).await;
+        }
+        assert!(matches!(
+            throttle.check_at(ip(), None, now).await,
+            Err(Throttled::RateLimited(_))
+        ));
+        // other addresses are unaffected
+        assert!(
+            throttle
+                .check_at("192.0.2.2".parse().unwrap(), None, now)
+                .await
+                .is_ok()
+        );
+        assert!(
+            throttle
+                .check_at(ip(), None, now + Duration::from_secs(1))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_lockout() {
+        let throttle = LoginThrottle::default();
+        let now = Instant::now();
+
+        for i in 0..config::LOGIN_LOCKOUT_THRESHOLD {
+            // spread attempts over many addresses to dodge the address backoff
+            let ip = IpAddr::from([192, 0, 2, i as u8]);
+            throttle
+                .failure_at(ip, Some(Account::User(1, "alice")), now)
+                .await;
+        }
+        let alice = Some(Account::User(1, "alice"));
+        let bob = Some(Account::User(2, "bob"));
+        assert!(matches!(
+            throttle.check_at(ip(), alice, now).await,
+            Err(Throttled::AccountLocked(_))
+        ));
+        assert!(throttle.check_at(ip(), bob, now).await.is_ok());
+
+        assert!(throttle.unlock(1).await);
+        assert!(throttle.check_at(ip(), alice, now).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_username_lockout() {
+        let throttle = LoginThrottle::default();
+        let now = Instant::now();
+
+        for i in 0..config::LOGIN_LOCKOUT_THRESHOLD {
+            let ip = IpAddr::from([192, 0, 2, i as u8]);
+            throttle
+                .failure_at(ip, Some(Account::Unknown("mallory")), now)
+                .await;
+        }
+        // refused like an existing account would be
+        assert!(matches!(
+            throttle
+                .check_at(ip(), Some(Account::Unknown("mallory")), now)
+                .await,
+            Err(Throttled::AccountLocked(_))
+        ));
+        assert!(
+            throttle
+                .check_at(ip(), Some(Account::Unknown("eve")), now)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Long-term memories of users, recalled by embedding similarity.
+//!
+//! Memories are written by the user or by the model through the remember
+//! tool. Each is embedded once when stored; recalling embeds the query and
+//! ranks the memories of the user by cosine similarity. Embeddings of another
+//! model are skipped, see [`super::embedding`].
+//!
+//! Both return what embedding cost, in USD, for the caller to charge the user.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use entity::{memory, prelude::*};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+};
+
+use crate::{
+    config::{MAX_MEMORY_LEN, MEMORY_MIN_SIMILARITY, MEMORY_RECALL_LIMIT},
+    utils::{
+        embedding::{self, Embedder},
+        timestamp,
+    },
+};
+
+pub struct MemoryStore {
+    embedder: Arc<Embedder>,
+}
+
+impl MemoryStore {
+    pub fn new(embedder: Arc<Embedder>) -> Self {
+        Self { embedder }
+    }
+
+    /// Embed and store a memory, an identical memory of the user is returned
+    /// instead of storing it twice, at no cost.
+    ///
+    /// `content` must pass [`valid`].
+    pub async fn remember<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: i32,
+        chat_id: Option<i32>,
+        content: &str,
+    ) -> Result<(memory::Model, f64)> {
+        let content = content.trim();
+        let existing = Memory::find()
+            .filter(memory::Column::OwnerId.eq(user_id))
+            .filter(memory::Column::Content.eq(content))
+            .one(conn)
+            .await?;
+        if let Some(existing) = existing {
+            return Ok((existing, 0.0));
+        }
+
+        let (vector, cost) = self.embedder.embed_one(content).await?;
+        let model = memory::ActiveModel {
+            owner_id: Set(user_id),
+            content: Set(content.to_owned()),
+            embedding: Set(embedding::encode(&vector)),
+            embedding_model: Set(self.embedder.model().to_owned()),
+            chat_id: Set(chat_id),
+            created_at: Set(timestamp::now()),
+            ..Default::default()
+        }
+        .insert(conn)
+        .await?;
+
+        Ok((model, cost))
+    }
+
+    /// Memories of the user most related to `query`, best first.
+    pub async fn recall<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: i32,
+        query: &str,
+    ) -> Result<(Vec<String>, f64)> {
+        if query.trim().is_empty() {
+            return Ok((Vec::new(), 0.0));
+        }
+
+        let memories = Memory::find()
+            .filter(memory::Column::OwnerId.eq(user_id))
+            .filter(memory::Column::EmbeddingModel.eq(self.embedder.model()))
+            .all(conn)
+            .await?;
+        if memories.is_empty() {
+            return Ok((Vec::new(), 0.0));
+        }
+
+        let (query, cost) = self.embedder.embed_one(query).await?;
+        let candidates = memories
+            .iter()
+            .map(|x| embedding::decode(&x.embedding))
+            .collect::<Vec<_>>();
+
+        let recalled = embedding::rank(
+            &query,
+            &candidates,
+            MEMORY_RECALL_LIMIT,
+            MEMORY_MIN_SIMILARITY,
+        )
+        .into_iter()
+        .map(|i| memories[i].content.clone())
+        .collect();
+        Ok((recalled, cost))
+    }
+}
+
+/// Whether `content` can be stored as a memory.
+pub fn valid(content: &str) -> bool {
+    let content = content.trim();
+    !content.is_empty() && content.chars().count() <= MAX_MEMORY_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid() {
+        assert!(valid("prefers metric units"));
+        assert!(!valid("  "));
+        assert!(!valid(&"x".repeat(MAX_MEMORY_LEN + 1)));
+    }
+}
@@ -1,11 +1,27 @@
+pub mod audit;
 pub mod blob;
+pub mod branch;
+pub mod budget;
 pub mod chat;
 #[cfg(feature = "cli")]
 pub mod cli;
+pub mod embedding;
 pub mod environment;
+pub mod export;
 pub mod file_cleanup;
+pub mod import;
+pub mod knowledge;
 pub mod logger;
+pub mod login_throttle;
+pub mod memory;
 pub mod model;
+pub mod model_access;
+pub mod oidc;
 pub mod password_hash;
+pub mod password_policy;
+pub mod random;
+pub mod search;
+pub mod timestamp;
+pub mod totp;
 pub mod url_validation;
 pub mod webp;
@@ -70,6 +70,10 @@ impl ModelChecker for ModelConfig {
             }
         }
 
+        if self.context_length == Some(0) {
+            anyhow::bail!("context_length must be greater than 0");
+        }
+
         if let Some(repetition_penalty) = self.parameter.repeat_penalty {
             if repetition_penalty < 1.0 {
                 anyhow::bail!("repetition_penalty must be greater than or equal to 1.0");
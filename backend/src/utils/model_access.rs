@@ -0,0 +1,105 @@
+//! Which model configs a user may list and run.
+//!
+//! A model is open to everyone until an administrator allows it for specific
+//! users or groups; from then on only those, and administrators, can use it.
+
+use std::collections::HashSet;
+
+use entity::{model_access, user_group_member};
+use protocol::UserRole;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect};
+
+/// Returned when a session is requested for a model the user may not use.
+///
+/// Reads like a missing model so ids of restricted models are not confirmed.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelNotAllowed;
+
+impl std::fmt::Display for ModelNotAllowed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "model not found")
+    }
+}
+
+impl std::error::Error for ModelNotAllowed {}
+
+#[derive(Debug, Default)]
+pub struct ModelAccess {
+    // models with an allowlist
+    restricted: HashSet<i32>,
+    // models whose allowlist includes the user
+    allowed: HashSet<i32>,
+}
+
+impl ModelAccess {
+    /// Build from allowlist rows, given the groups of the user.
+    fn from_rows(rows: &[model_access::Model], user_id: i32, groups: &[i32]) -> Self {
+        let mut access = Self::default();
+        for row in rows {
+            access.restricted.insert(row.model_id);
+            if row.user_id == Some(user_id) || row.group_id.is_some_and(|x| groups.contains(&x)) {
+                access.allowed.insert(row.model_id);
+            }
+        }
+        access
+    }
+
+    pub fn permits(&self, model_id: i32) -> bool {
+        !self.restricted.contains(&model_id) || self.allowed.contains(&model_id)
+    }
+}
+
+/// Load the model access of `user_id`.
+pub async fn load<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    role: UserRole,
+) -> Result<ModelAccess, DbErr> {
+    if role == UserRole::Admin {
+        return Ok(ModelAccess::default());
+    }
+
+    let groups = user_group_member::Entity::find()
+        .select_only()
+        .column(user_group_member::Column::GroupId)
+        .filter(user_group_member::Column::UserId.eq(user_id))
+        .into_tuple::<i32>()
+        .all(conn)
+        .await?;
+    let rows = model_access::Entity::find().all(conn).await?;
+
+    Ok(ModelAccess::from_rows(&rows, user_id, &groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(model_id: i32, user_id: Option<i32>, group_id: Option<i32>) -> model_access::Model {
+        model_access::Model {
+            id: 0,
+            model_id,
+            user_id,
+            group_id,
+        }
+    }
+
+    #[test]
+    fn test_permits() {
+        let rows = [
+            row(2, Some(1), None),
+            row(3, None, Some(7)),
+            row(4, Some(9), None),
+        ];
+
+        let access = ModelAccess::from_rows(&rows, 1, &[]);
+        assert!(access.permits(1));
+        assert!(access.permits(2));
+        assert!(!access.permits(3));
+        assert!(!access.permits(4));
+
+        let access = ModelAccess::from_rows(&rows, 5, &[7]);
+        assert!(!access.permits(2));
+        assert!(access.permits(3));
+    }
+}
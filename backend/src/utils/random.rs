@@ -0,0 +1,15 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+/// Random url-safe string carrying `bytes` bytes of entropy.
+pub fn url_safe(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    getrandom::fill(&mut buf).unwrap();
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// Random lowercase hex string carrying `bytes` bytes of entropy.
+pub fn hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    getrandom::fill(&mut buf).unwrap();
+    buf.iter().map(|x| format!("{:02x}", x)).collect()
+}
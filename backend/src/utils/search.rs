@@ -0,0 +1,126 @@
+//! Full-text index of message text, backed by the SQLite FTS5 table
+//! `message_fts`.
+//!
+//! Only what people read is indexed: the text of user messages and the text
+//! chunks of replies. Rows of deleted messages are removed by a trigger.
+
+use protocol::{AssistantChunk, MessageInner};
+use sea_orm::{ConnectionTrait, DbErr, Statement};
+
+// Private use characters wrapped around matches by `snippet()`, replaced once
+// the rest of the snippet is escaped.
+pub const MATCH_START: &str = "\u{e000}";
+pub const MATCH_END: &str = "\u{e001}";
+
+/// Text of a message worth searching for.
+pub fn searchable_text(inner: &MessageInner) -> String {
+    match inner {
+        MessageInner::User { text, .. } => text.clone(),
+        MessageInner::Assistant(chunks) => chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                AssistantChunk::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Turn user input into an FTS5 query matching every word.
+///
+/// Words are quoted so operators and punctuation are searched literally, the
+/// last one also matches as a prefix to support search-as-you-type.
+pub fn match_query(input: &str) -> Option<String> {
+    let words = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.join(" ") + "*")
+}
+
+/// Escape a snippet for HTML and wrap matches in `<mark>`.
+pub fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html.replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Add or refresh the index entry of a message.
+pub async fn index<C: ConnectionTrait>(
+    conn: &C,
+    message_id: i32,
+    chat_id: i32,
+    inner: &MessageInner,
+) -> Result<(), DbErr> {
+    let backend = conn.get_database_backend();
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        "DELETE FROM message_fts WHERE rowid = ?",
+        [message_id.into()],
+    ))
+    .await?;
+
+    let content = searchable_text(inner);
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+    conn.execute(Statement::from_sql_and_values(
+        backend,
+        "INSERT INTO message_fts (rowid, content, chat_id) VALUES (?, ?, ?)",
+        [message_id.into(), content.into(), chat_id.into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_searchable_text() {
+        let inner = MessageInner::Assistant(vec![
+            AssistantChunk::Reasoning("hidden".to_owned()),
+            AssistantChunk::Text("first".to_owned()),
+            AssistantChunk::Error("oops".to_owned()),
+            AssistantChunk::Text("second".to_owned()),
+        ]);
+        assert_eq!(searchable_text(&inner), "first\nsecond");
+    }
+
+    #[test]
+    fn test_match_query() {
+        assert_eq!(match_query("  "), None);
+        assert_eq!(
+            match_query("rust lifetimes").unwrap(),
+            "\"rust\" \"lifetimes\"*"
+        );
+        assert_eq!(
+            match_query("say \"hi\" OR").unwrap(),
+            "\"say\" \"\"\"hi\"\"\" \"OR\"*"
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let snippet = format!("a <b> {}match{} & c", MATCH_START, MATCH_END);
+        assert_eq!(
+            highlight(&snippet),
+            "a &lt;b&gt; <mark>match</mark> &amp; c"
+        );
+    }
+}
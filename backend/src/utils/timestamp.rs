@@ -0,0 +1,39 @@
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Current unix timestamp in seconds.
+pub fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// Format a unix timestamp as RFC 3339, which is how timestamps cross the API.
+pub fn format(ts: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(ts)
+        .ok()
+        .and_then(|x| x.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
+/// Parse an RFC 3339 timestamp into a unix timestamp.
+pub fn parse(input: &str) -> Option<i64> {
+    OffsetDateTime::parse(input, &Rfc3339)
+        .ok()
+        .map(|x| x.unix_timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("2023-11-14T22:13:20Z"), Some(1_700_000_000));
+        assert_eq!(parse("2023-11-15T06:13:20+08:00"), Some(1_700_000_000));
+        assert_eq!(parse("yesterday"), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! RFC 6238 time-based one-time passwords (SHA-1, 6 digits, 30 seconds).
+//!
+//! These are the parameters every authenticator app supports, so they are not
+//! configurable.
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::Sha256;
+
+const DIGITS: u32 = 6;
+const PERIOD: i64 = 30;
+// accept codes from one step before and after to tolerate clock drift
+const WINDOW: i64 = 1;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encode bytes as unpadded RFC 4648 base32, the format of TOTP secrets.
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Decode base32, ignoring case, spaces and padding.
+pub fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|x| *x as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Generate a new 160 bits secret, base32 encoded.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; 20];
+    getrandom::fill(&mut secret).unwrap();
+    base32_encode(&secret)
+}
+
+/// `otpauth://` uri understood by authenticator apps (usually as a QR code).
+pub fn provisioning_uri(secret: &str, issuer: &str, account: &str) -> String {
+    let issuer = urlencode(issuer);
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        issuer,
+        urlencode(account),
+        secret,
+        issuer,
+        DIGITS,
+        PERIOD
+    )
+}
+
+fn urlencode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn hotp(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+/// Verify `code` at unix time `now`, returning the matched time step.
+///
+/// Callers should persist the step and reject steps not greater than it, so
+/// a code cannot be replayed.
+pub fn verify(secret: &str, code: &str, now: i64) -> Option<i64> {
+    let secret = base32_decode(secret)?;
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+
+    let step = now / PERIOD;
+    (step - WINDOW..=step + WINDOW).find(|step| *step >= 0 && hotp(&secret, *step as u64) == code)
+}
+
+/// Hash of a recovery code as stored, lowercase hex HMAC-SHA256 under `key`.
+///
+/// Recovery codes are random, so a fast hash can be looked up directly. The
+/// key keeps leaked hashes from being guessed offline.
+pub fn recovery_code_hash(key: &[u8], code: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(code.trim().to_ascii_lowercase().as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|x| format!("{:02x}", x))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B, SHA-1 seed
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_base32_roundtrip() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+        assert_eq!(base32_decode("mzxw 6ytb oi======").unwrap(), b"foobar");
+        assert!(base32_decode("not base32!").is_none());
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // 8 digit vectors truncated to 6 digits
+        assert_eq!(hotp(RFC_SECRET, 59 / 30), 287082);
+        assert_eq!(hotp(RFC_SECRET, 1111111109 / 30), 81804);
+        assert_eq!(hotp(RFC_SECRET, 1234567890 / 30), 5924);
+        assert_eq!(hotp(RFC_SECRET, 2000000000 / 30), 279037);
+    }
+
+    #[test]
+    fn test_verify_window() {
+        let secret = base32_encode(RFC_SECRET);
+        assert_eq!(verify(&secret, "081804", 1111111109), Some(1111111109 / 30));
+        assert_eq!(
+            verify(&secret, "081804", 1111111109 + 30),
+            Some(1111111109 / 30)
+        );
+        assert_eq!(verify(&secret, "081804", 1111111109 + 90), None);
+        assert_eq!(verify(&secret, "000000", 1111111109), None);
+    }
+
+    #[test]
+    fn test_recovery_code_hash() {
+        let hash = recovery_code_hash(b"key", "abcde-01234");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(recovery_code_hash(b"key", " ABCDE-01234\n"), hash);
+        assert_ne!(recovery_code_hash(b"other key", "abcde-01234"), hash);
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        assert_eq!(
+            provisioning_uri("ABC", "llumen", "alice smith"),
+            "otpauth://totp/llumen:alice%20smith?secret=ABC&issuer=llumen&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
@@ -3,7 +3,15 @@ import { page } from '$app/state';
 import { goto } from '$app/navigation';
 import { APIFetch } from './http.svelte';
 
-import type { LoginReq, LoginResp, RenewResp, RenewReq, HeaderAuthResp } from './types';
+import type {
+	LoginReq,
+	LoginResp,
+	RenewResp,
+	RenewReq,
+	HeaderAuthResp,
+	TotpLoginReq,
+	TotpLoginResp
+} from './types';
 import type { MutationStatus } from '.';
 
 export interface User {
@@ -22,7 +30,14 @@ function applyToken(data: { token: string; exp: string | number }) {
 	};
 }
 
-export async function Login(username: string, password: string): Promise<MutationStatus> {
+/**
+ * Sign in with a password, returning the challenge to pass to `TotpLogin`
+ * when the account has two-factor authentication enabled.
+ */
+export async function Login(
+	username: string,
+	password: string
+): Promise<MutationStatus | { challenge: string }> {
 	let data = await APIFetch<LoginResp>({
 		path: 'auth/login',
 		body: { username, password } as LoginReq,
@@ -30,6 +45,21 @@ export async function Login(username: string, password: string): Promise<Mutatio
 	});
 	if (!data) return 'failed';
 
+	if (data.t == 'challenge') return { challenge: data.c };
+
+	applyToken(data.c);
+
+	return 'success';
+}
+
+export async function TotpLogin(challenge: string, code: string): Promise<MutationStatus> {
+	let data = await APIFetch<TotpLoginResp>({
+		path: 'auth/totp',
+		body: { challenge, code } as TotpLoginReq,
+		token: false
+	});
+	if (!data) return 'failed';
+
 	applyToken(data);
 
 	return 'success';
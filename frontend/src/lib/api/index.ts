@@ -1,7 +1,7 @@
 export type MutationStatus = 'pending' | 'failed' | 'success' | 'untried';
 
 // Auth
-export { Login, RenewToken, type User } from './auth.svelte';
+export { Login, TotpLogin, RenewToken, type User } from './auth.svelte';
 
 // Chatroom
 export {
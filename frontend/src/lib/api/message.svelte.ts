@@ -492,7 +492,8 @@ export async function createMessage(params: MessageCreateReq): Promise<MutationS
 		body: params,
 		token: token.value?.value
 	});
-	if (resp) pushUserMessage(resp.user_id, params.text, params.files);
+	// queued messages show up once the streaming reply is done
+	if (resp?.t == 'created') pushUserMessage(resp.c.user_id, params.text, params.files);
 	return resp ? 'success' : 'failed';
 }
 
@@ -546,7 +547,7 @@ export function syncMessage(
 			},
 			token: token_
 		});
-		if (resp) pushUserMessage(resp.user_id, text, files);
+		if (resp?.t == 'created') pushUserMessage(resp.c.user_id, text, files);
 
 		return resp ? 'success' : 'failed';
 	})();
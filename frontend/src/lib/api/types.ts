@@ -275,7 +275,19 @@ export interface LoginReq {
 	password: string;
 }
 
-export interface LoginResp {
+export interface LoginToken {
+	token: string;
+	exp: string;
+}
+
+export interface TotpLoginReq {
+	/** Challenge returned by `/auth/login` */
+	challenge: string;
+	/** Current code or a recovery code */
+	code: string;
+}
+
+export interface TotpLoginResp {
 	token: string;
 	exp: string;
 }
@@ -293,7 +305,8 @@ export interface MessageCreateReq {
 	files: MessageCreateReqFile[];
 }
 
-export interface MessageCreateResp {
+/** Ids of the reply and the user message. */
+export interface MessageCreated {
 	id: number;
 	user_id: number;
 }
@@ -331,6 +344,19 @@ export interface MessagePaginateReqRange {
 	lower: number;
 }
 
+export type LoginResp =
+	| { t: 'token'; c: LoginToken }
+	/** A second factor is required, pass it to `/auth/totp` together with the code */
+	| { t: 'challenge'; c: string };
+
+export type MessageCreateResp =
+	| { t: 'created'; c: MessageCreated }
+	/**
+	 * Id in the queue of the chat, the message waits for the streaming reply
+	 * and is answered after the messages queued before it
+	 */
+	| { t: 'queued'; c: number };
+
 export type MessageInner =
 	| {
 			t: 'user';